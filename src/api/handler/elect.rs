@@ -0,0 +1,126 @@
+use axum::{
+    extract::State, http::StatusCode, response::Json
+};
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::{
+    api::routes::root::AppState, miner_config, models::{Algorithm, SyntheticElection}, multi_block_state_client::StorageTrait, primitives::{AccountId, Storage}, simulate::{self, SimulateService}, snapshot::SnapshotService
+};
+
+#[derive(Deserialize)]
+pub struct ElectRequestBody {
+    pub voters: Vec<(String, u64, Vec<String>)>,
+    pub targets: Vec<String>,
+    pub desired_targets: u32,
+    pub algorithm: Option<Algorithm>,
+    pub iterations: Option<usize>,
+    pub reduce: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct ElectResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<SyntheticElection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Permits for the elections of a chain mined at once, one per core as mining is CPU bound.
+/// Further requests wait for a permit instead of each taking a blocking thread
+pub fn election_permits() -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(std::thread::available_parallelism().map_or(1, |cores| cores.get())))
+}
+
+pub async fn elect_handler<
+Sim: SimulateService + Send + Sync + 'static,
+Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+MC: MinerConfig<AccountId = AccountId> + Send + Sync + Clone + 'static,
+S: StorageTrait + From<Storage> + Clone + 'static,
+>(
+    State(state): State<AppState<Sim, Snap, MC, S>>,
+    Json(body): Json<ElectRequestBody>,
+) -> (StatusCode, Json<ElectResponse>)
+{
+    let algorithm = body.algorithm.unwrap_or(Algorithm::SeqPhragmen);
+    let iterations = body.iterations.unwrap_or(0);
+    let reduce = body.reduce.unwrap_or(false);
+
+    let permit = match state.elections.clone().acquire_owned().await {
+        Ok(permit) => permit,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(ElectResponse { result: None, error: Some(format!("Election could not be started: {}", e)) })),
+    };
+
+    let span = tracing::Span::current();
+    // Mining is CPU bound, so it runs on a blocking thread with the chain scoped again there.
+    // The permit is held until mining ends, even when the request is dropped before
+    let context = state.context.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        let _enter = span.enter();
+        let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+        rt.block_on(context.scope(miner_config::with_election_config(algorithm, iterations, None, async move {
+            simulate::elect::<MC>(&body.voters, &body.targets, body.desired_targets, reduce)
+        })))
+    }).await;
+
+    // Every failure of the election comes from the posted electorate, there is no chain to blame
+    match result {
+        Ok(Ok(election)) => (StatusCode::OK, Json(ElectResponse { result: Some(election), error: None })),
+        Ok(Err(e)) => (StatusCode::BAD_REQUEST, Json(ElectResponse { result: None, error: Some(e.to_string()) })),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ElectResponse { result: None, error: Some(format!("Election task failed: {}", e)) })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::handler::test_state;
+    use crate::miner_config::initialize_runtime_constants;
+    use crate::simulate::MockSimulateService;
+    use crate::snapshot::MockSnapshotService;
+
+    fn body(vote: &str) -> ElectRequestBody {
+        ElectRequestBody {
+            voters: vec![("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(), 100, vec![vote.to_string()])],
+            targets: vec!["5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string()],
+            desired_targets: 1,
+            algorithm: Some(Algorithm::Phragmms),
+            iterations: Some(2),
+            reduce: Some(true),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_elect_handler() {
+        initialize_runtime_constants();
+        let (status, Json(response)) = elect_handler(State(test_state(MockSimulateService::new(), MockSnapshotService::new())), Json(body("5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2"))).await;
+        assert_eq!(status, StatusCode::OK);
+        let result = response.result.unwrap();
+        assert_eq!(result.winners.len(), 1);
+        assert_eq!(result.winners[0].total, "100");
+    }
+
+    #[tokio::test]
+    async fn test_elect_handler_invalid_account() {
+        initialize_runtime_constants();
+        let (status, Json(response)) = elect_handler(State(test_state(MockSimulateService::new(), MockSnapshotService::new())), Json(body("not-an-address"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_elect_handler_no_permits() {
+        initialize_runtime_constants();
+        let state = test_state(MockSimulateService::new(), MockSnapshotService::new());
+        state.elections.close();
+        let (status, Json(response)) = elect_handler(State(state), Json(body("5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2"))).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response.error.unwrap().starts_with("Election could not be started"));
+    }
+}
@@ -0,0 +1,105 @@
+use axum::{
+    extract::{Query, State}, http::StatusCode, response::Json
+};
+
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    api::{routes::root::AppState, utils}, models::EraInfo, multi_block_state_client::StorageTrait, primitives::Storage, simulate::SimulateService, snapshot::SnapshotService
+};
+
+#[derive(Deserialize)]
+pub struct EraRequest {
+    pub block: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EraResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<EraInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub async fn era_handler<
+Sim: SimulateService + Send + Sync + 'static,
+Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+MC: MinerConfig + Send + Sync + Clone + 'static,
+S: StorageTrait + From<Storage> + Clone + 'static,
+>(
+    State(state): State<AppState<Sim, Snap, MC, S>>,
+    Query(params): Query<EraRequest>,
+) -> (StatusCode, Json<EraResponse>)
+{
+    let block = match utils::parse_block(params.block) {
+        Ok(block) => block,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(EraResponse {
+                result: None,
+                error: Some(e.to_string()),
+            }));
+        }
+    };
+
+    info!("Block: {:?}", block);
+
+    let (status, response) = match state.snapshot_service.get_era(block).await {
+        Ok(era) => {
+            info!("Era {} ({})", era.index, era.start_description());
+            (
+                StatusCode::OK,
+                EraResponse {
+                    result: Some(era),
+                    error: None,
+                }
+            )
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            EraResponse {
+                result: None,
+                error: Some(e.to_string()),
+            }
+        ),
+    };
+
+    (status, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::handler::test_state;
+    use crate::snapshot::MockSnapshotService;
+    use crate::simulate::MockSimulateService;
+    use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
+
+    #[tokio::test]
+    async fn test_era_handler() {
+        let mut snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        snapshot_service.expect_get_era().returning(|_| {
+            Ok(EraInfo { index: 10, start: Some(1_700_000_000_000), start_session_index: Some(56) })
+        });
+        let result = era_handler(State(test_state(MockSimulateService::new(), snapshot_service)), Query(EraRequest { block: None })).await;
+        assert_eq!(result.0, StatusCode::OK);
+        assert_eq!(result.1.result.as_ref().unwrap().index, 10);
+    }
+
+    #[tokio::test]
+    async fn test_era_handler_invalid_block() {
+        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        let result = era_handler(State(test_state(MockSimulateService::new(), snapshot_service)), Query(EraRequest { block: Some("invalid".to_string()) })).await;
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_era_handler_error() {
+        let mut snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        snapshot_service.expect_get_era().returning(|_| Err("Staking::ActiveEra not found".into()));
+        let result = era_handler(State(test_state(MockSimulateService::new(), snapshot_service)), Query(EraRequest { block: None })).await;
+        assert_eq!(result.0, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(result.1.error.is_some());
+    }
+}
@@ -1,2 +1,20 @@
+pub mod elect;
+pub mod era;
 pub mod simulate;
 pub mod snapshot;
+pub mod status;
+pub mod version;
+
+#[cfg(test)]
+use crate::{
+    api::routes::root::AppState, miner_config::{polkadot::MinerConfig as PolkadotMinerConfig, test_chain_context}, models::VersionInfo, primitives::Storage, simulate::MockSimulateService, snapshot::MockSnapshotService
+};
+
+// Handler state over the given mocks, connected to Polkadot
+#[cfg(test)]
+pub fn test_state(
+    simulate_service: MockSimulateService,
+    snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage>,
+) -> AppState<MockSimulateService, MockSnapshotService<PolkadotMinerConfig, Storage>, PolkadotMinerConfig, Storage> {
+    AppState::new(std::sync::Arc::new(simulate_service), std::sync::Arc::new(snapshot_service), test_chain_context(), VersionInfo::default())
+}
@@ -1,16 +1,17 @@
 use axum::{
-    extract::{Query, State}, http::StatusCode, response::Json
+    extract::{Query, State}, http::{header, HeaderMap, StatusCode}, response::{IntoResponse, Json, Response}
 };
 use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    api::{routes::root::AppState, utils}, miner_config, models::Algorithm, multi_block_state_client::StorageTrait, primitives::Storage, simulate::{Override, SimulateService}, snapshot::SnapshotService
+    api::{routes::root::AppState, utils}, compare, explain, miner_config, models::{Algorithm, OutputFormat, OutputOptions, to_msgpack}, multi_block_state_client::StorageTrait, primitives::Storage, simulate::{Override, SimulateService}, snapshot::SnapshotService
 };
 
 #[derive(Deserialize)]
 pub struct SimulateRequestQuery {
     pub block: Option<String>,
+    pub format: Option<OutputFormat>,
 }
 
 #[derive(Deserialize)]
@@ -23,6 +24,17 @@ pub struct SimulateRequestBody {
     pub min_nominator_bond: Option<u128>,
     pub min_validator_bond: Option<u128>,
     pub manual_override: Option<Override>,
+    pub include_unelected: Option<bool>,
+    pub include_unused_stake: Option<bool>,
+    pub include_wasted_voters: Option<bool>,
+    pub explain_score: Option<bool>,
+    pub precision: Option<u32>,
+    pub raw_commission: Option<bool>,
+    pub commission_bps: Option<bool>,
+    pub top_backers: Option<usize>,
+    pub min_nomination_display: Option<u128>,
+    pub explain: Option<bool>,
+    pub overlap_top: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -46,16 +58,32 @@ S: StorageTrait + From<Storage> + Clone + 'static,
         S,
     >>,
     Query(params): Query<SimulateRequestQuery>,
+    headers: HeaderMap,
     Json(body): Json<SimulateRequestBody>,
-) -> (StatusCode, Json<SimulateResponse>)
+) -> Response
 {
+    // CSV is selected either explicitly with ?format=csv or through the Accept header
+    let csv_requested = params.format == Some(OutputFormat::Csv) || headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/csv"));
+
     let block = match utils::parse_block(params.block) {
         Ok(block) => block,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(SimulateResponse {
                 result: None,
                 error: Some(e.to_string()),
-            }));
+            })).into_response();
+        }
+    };
+    let precision = match utils::parse_precision(body.precision) {
+        Ok(precision) => precision,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(SimulateResponse {
+                result: None,
+                error: Some(e.to_string()),
+            })).into_response();
         }
     };
     
@@ -67,8 +95,25 @@ S: StorageTrait + From<Storage> + Clone + 'static,
     let min_nominator_bond = body.min_nominator_bond;
     let min_validator_bond = body.min_validator_bond;
     let manual_override = body.manual_override;
+    let output_options = OutputOptions {
+        precision,
+        raw_commission: body.raw_commission.unwrap_or(false),
+        commission_bps: body.commission_bps.unwrap_or(false),
+        include_unelected: body.include_unelected.unwrap_or(false),
+        include_unused_stake: body.include_unused_stake.unwrap_or(false),
+        include_wasted_voters: body.include_wasted_voters.unwrap_or(false),
+        top_backers: body.top_backers,
+        min_nomination_display: body.min_nomination_display.unwrap_or(0),
+        explain_score: body.explain_score.unwrap_or(false),
+        ..OutputOptions::default()
+    };
+    let explain_mode = body.explain.unwrap_or(false);
+    let overlap_top = body.overlap_top;
     
     let span = tracing::Span::current();
+    // Task-locals do not follow the request onto the blocking thread, so the chain is scoped again there
+    let context = state.context.clone();
+    let progress = state.progress.clone();
     let result = tokio::task::spawn_blocking(move || {
         // Maintain the same scope as the main function
         let _enter = span.enter();
@@ -78,26 +123,52 @@ S: StorageTrait + From<Storage> + Clone + 'static,
         .build()
         .unwrap();
 
-        rt.block_on(async {
+        rt.block_on(context.scope(progress.track("simulate", async {
             // Run simulation within task-local scope for algorithm, iterations, and max nominations
             miner_config::with_election_config(algorithm, iterations, max_nominations, 
                 async move {
-                    state.simulate_service.simulate(
+                    let simulation = state.simulate_service.simulate(
                         block,
                         desired_validators,
                         apply_reduce,
                         manual_override,
                         min_nominator_bond,
                         min_validator_bond,
-                    ).await
+                        None,
+                    );
+                    if explain_mode {
+                        let (result, exclusions) = explain::with_explain(simulation).await;
+                        result.map(|result| (result, Some(exclusions)))
+                    } else {
+                        simulation.await.map(|result| (result, None))
+                    }
                 }
             ).await
-        })
+        })))
     }).await.unwrap();
 
     let (status, response) = match result {
-        Ok(result) => {
-            let output_result = result.to_output(state.chain);
+        Ok((result, exclusions)) => {
+            let mut output_result = result.to_output(state.context.chain, &output_options);
+            output_result.exclusions = exclusions;
+            output_result.nominator_overlap = overlap_top
+                .map(|top_k| compare::nominator_overlap(&result.active_validators, top_k, output_options.precision));
+            if params.format == Some(OutputFormat::CommissionBands) {
+                return (StatusCode::OK, Json(compare::commission_bands(&result.active_validators, state.context.chain))).into_response();
+            }
+            if params.format == Some(OutputFormat::Msgpack) {
+                return match to_msgpack(&output_result) {
+                    Ok(bytes) => (StatusCode::OK, [(header::CONTENT_TYPE, "application/msgpack")], bytes).into_response(),
+                    Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(SimulateResponse { result: None, error: Some(e.to_string()) })).into_response(),
+                };
+            }
+            if csv_requested {
+                return (
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+                    output_result.to_csv(),
+                ).into_response();
+            }
             (
                 StatusCode::OK,
                 SimulateResponse {
@@ -115,84 +186,77 @@ S: StorageTrait + From<Storage> + Clone + 'static,
         ),
     };
 
-    (status, Json(response))
+    (status, Json(response)).into_response()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::handler::test_state;
     use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
     use crate::simulate::MockSimulateService;
     use crate::snapshot::MockSnapshotService;
-    use crate::models::Chain;
-    use crate::models::{RunParameters, SimulationResult, StakingStats};
-    use std::sync::Arc;
+    use crate::models::SimulationResult;
 
     #[tokio::test]
     async fn test_simulate_handler() {
         let mut simulate_service = MockSimulateService::new();
-        simulate_service.expect_simulate().returning( move |_, _, _, _, _, _| {
-            Ok(SimulationResult {
-                run_parameters: RunParameters {
-                    algorithm: Algorithm::SeqPhragmen,
-                    iterations: 0,
-                    reduce: false,
-                    max_nominations: 0,
-                    min_nominator_bond: 0,
-                    min_validator_bond: 0,
-                    desired_validators: 0,
-                },
-                active_validators: vec![],
-                staking_stats: StakingStats {
-                    total_staked: 0,
-                    lowest_staked: 0,
-                    avg_staked: 0,
-                },
-            })
+        simulate_service.expect_simulate().returning( move |_, _, _, _, _, _, _| {
+            Ok(SimulationResult::default())
         });
         let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
-        let app_state = AppState {
-            simulate_service: Arc::new(simulate_service),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(simulate_service, snapshot_service);
         let app_state_extract = State(app_state);
-        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None }), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None })).await;
-        assert_eq!(result.0, StatusCode::OK);
+        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None, format: None }), HeaderMap::new(), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None, include_unelected: None, include_unused_stake: None, include_wasted_voters: None, explain_score: None, precision: None, raw_commission: None, commission_bps: None, top_backers: None, min_nomination_display: None, explain: None, overlap_top: None })).await;
+        assert_eq!(result.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_handler_csv() {
+        let mut simulate_service = MockSimulateService::new();
+        simulate_service.expect_simulate().returning( move |_, _, _, _, _, _, _| {
+            Ok(SimulationResult::default())
+        });
+        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        let app_state = test_state(simulate_service, snapshot_service);
+        let app_state_extract = State(app_state);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, "text/csv".parse().unwrap());
+        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None, format: None }), headers, Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None, include_unelected: None, include_unused_stake: None, include_wasted_voters: None, explain_score: None, precision: None, raw_commission: None, commission_bps: None, top_backers: None, min_nomination_display: None, explain: None, overlap_top: None })).await;
+        assert_eq!(result.status(), StatusCode::OK);
+        assert_eq!(result.headers()[header::CONTENT_TYPE], "text/csv; charset=utf-8");
     }
 
     #[tokio::test]
     async fn test_simulate_handler_invalid_block() {
         let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
-        let app_state = AppState {
-            simulate_service: Arc::new(MockSimulateService::new()),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
+        let app_state_extract = State(app_state);
+        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: Some("invalid".to_string()), format: None }), HeaderMap::new(), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None, include_unelected: None, include_unused_stake: None, include_wasted_voters: None, explain_score: None, precision: None, raw_commission: None, commission_bps: None, top_backers: None, min_nomination_display: None, explain: None, overlap_top: None })).await;
+        assert_eq!(result.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_handler_invalid_precision() {
+        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
         let app_state_extract = State(app_state);
-        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: Some("invalid".to_string()) }), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None })).await;
-        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None, format: None }), HeaderMap::new(), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None, include_unelected: None, include_unused_stake: None, include_wasted_voters: None, explain_score: None, precision: Some(13), raw_commission: None, commission_bps: None, top_backers: None, min_nomination_display: None, explain: None, overlap_top: None })).await;
+        assert_eq!(result.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
     async fn test_simulate_handler_error() {
         let mut simulate_service = MockSimulateService::new();
-        simulate_service.expect_simulate().returning( move |_, _, _, _, _, _| {
+        simulate_service.expect_simulate().returning( move |_, _, _, _, _, _, _| {
             Err(Box::new(
-                std::io::Error::new(std::io::ErrorKind::Other, "Error")
+                std::io::Error::other("Error")
             ))
         });
         let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
-        let app_state = AppState {
-            simulate_service: Arc::new(simulate_service),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(simulate_service, snapshot_service);
         let app_state_extract = State(app_state);
-        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None }), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None })).await;
-        assert_eq!(result.0, StatusCode::INTERNAL_SERVER_ERROR);
+        let result = simulate_handler(app_state_extract, Query(SimulateRequestQuery { block: None, format: None }), HeaderMap::new(), Json(SimulateRequestBody { algorithm: None, iterations: None, reduce: None, desired_validators: None, max_nominations: None, min_nominator_bond: None, min_validator_bond: None, manual_override: None, include_unelected: None, include_unused_stake: None, include_wasted_voters: None, explain_score: None, precision: None, raw_commission: None, commission_bps: None, top_backers: None, min_nomination_display: None, explain: None, overlap_top: None })).await;
+        assert_eq!(result.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 }
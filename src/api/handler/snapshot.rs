@@ -7,12 +7,15 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::{
-    api::{routes::root::AppState, utils}, multi_block_state_client::StorageTrait, primitives::Storage, simulate::SimulateService, snapshot::SnapshotService
+    api::{routes::root::AppState, utils}, models::{NominatorSort, OutputOptions}, multi_block_state_client::StorageTrait, primitives::Storage, simulate::SimulateService, snapshot::SnapshotService
 };
 
 #[derive(Deserialize)]
 pub struct SnapshotRequest {
     pub block: Option<String>,
+    pub precision: Option<u32>,
+    pub commission_bps: Option<bool>,
+    pub sort_by: Option<NominatorSort>,
 }
 
 #[derive(Serialize)]
@@ -41,14 +44,29 @@ S: StorageTrait + From<Storage> + Clone + 'static,
             }));
         }
     };
+    let precision = match utils::parse_precision(params.precision) {
+        Ok(precision) => precision,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(SnapshotResponse {
+                result: None,
+                error: Some(e.to_string()),
+            }));
+        }
+    };
 
     info!("Block: {:?}", block);
 
-    let build_result = state.snapshot_service.build(block).await;
+    let build_result = state.progress.track("snapshot", state.snapshot_service.build(block)).await;
 
     let (status, response) = match build_result {
         Ok(result) => {
-            let output_result = result.to_output(state.chain);
+            let output_options = OutputOptions {
+                precision,
+                commission_bps: params.commission_bps.unwrap_or(false),
+                nominator_sort: params.sort_by.unwrap_or_default(),
+                ..OutputOptions::default()
+            };
+            let output_result = result.to_output(state.context.chain, &output_options);
             (
                 StatusCode::OK,
                 SnapshotResponse {
@@ -72,12 +90,11 @@ S: StorageTrait + From<Storage> + Clone + 'static,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::handler::test_state;
     use crate::snapshot::MockSnapshotService;
-    use crate::models::Chain;
     use crate::simulate::MockSimulateService;
     use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
-    use crate::models::{Snapshot, StakingConfig};
-    use std::sync::Arc;
+    use crate::models::{ReconstructionCounts, Snapshot, StakingConfig};
 
     #[tokio::test]
     async fn test_snapshot_handler() {
@@ -89,33 +106,32 @@ mod tests {
                 config: StakingConfig {
                     desired_validators: 0,
                     max_nominations: 0,
-                    min_nominator_bond: 0,
-                    min_validator_bond: 0,
+                    ..StakingConfig::default()
                 },
+                reconstruction: ReconstructionCounts::default(),
             })
         });
-        let app_state = AppState {
-            simulate_service: Arc::new(MockSimulateService::new()),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
         let app_state_extract = State(app_state);
-        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: None })).await;
+        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: None, precision: None, commission_bps: None, sort_by: None })).await;
         assert_eq!(result.0, StatusCode::OK);
     }  
 
     #[tokio::test]
     async fn test_snapshot_handler_invalid_block() {
         let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
-        let app_state = AppState {
-            simulate_service: Arc::new(MockSimulateService::new()),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
+        let app_state_extract = State(app_state);
+        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: Some("invalid".to_string()), precision: None, commission_bps: None, sort_by: None })).await;
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_handler_invalid_precision() {
+        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
         let app_state_extract = State(app_state);
-        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: Some("invalid".to_string()) })).await;
+        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: None, precision: Some(13), commission_bps: None, sort_by: None })).await;
         assert_eq!(result.0, StatusCode::BAD_REQUEST);
     }
 
@@ -124,17 +140,12 @@ mod tests {
         let mut snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
         snapshot_service.expect_build().returning(move |_| {
             Err(Box::new(
-                std::io::Error::new(std::io::ErrorKind::Other, "Error")
+                std::io::Error::other("Error")
             ))
         });
-        let app_state = AppState {
-            simulate_service: Arc::new(MockSimulateService::new()),
-            snapshot_service: Arc::new(snapshot_service),
-            chain: Chain::Polkadot,
-            _phantom: std::marker::PhantomData,
-        };
+        let app_state = test_state(MockSimulateService::new(), snapshot_service);
         let app_state_extract = State(app_state);
-        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: None })).await;
+        let result = snapshot_handler(app_state_extract, Query(SnapshotRequest { block: None, precision: None, commission_bps: None, sort_by: None })).await;
         assert_eq!(result.0, StatusCode::INTERNAL_SERVER_ERROR);
     }
 }
\ No newline at end of file
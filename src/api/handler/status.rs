@@ -0,0 +1,46 @@
+use axum::{
+    extract::State, http::StatusCode, response::Json
+};
+
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+
+use crate::{
+    api::routes::root::AppState, multi_block_state_client::StorageTrait, primitives::Storage, progress::ProgressStatus, simulate::SimulateService, snapshot::SnapshotService
+};
+
+pub async fn status_handler<
+Sim: SimulateService + Send + Sync + 'static,
+Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+MC: MinerConfig + Send + Sync + Clone + 'static,
+S: StorageTrait + From<Storage> + Clone + 'static,
+>(
+    State(state): State<AppState<Sim, Snap, MC, S>>,
+) -> (StatusCode, Json<ProgressStatus>)
+{
+    (StatusCode::OK, Json(state.progress.status()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::handler::test_state;
+    use crate::progress;
+    use crate::snapshot::MockSnapshotService;
+    use crate::simulate::MockSimulateService;
+
+    #[tokio::test]
+    async fn test_status_handler() {
+        let app_state = test_state(MockSimulateService::new(), MockSnapshotService::new());
+        let (status, Json(response)) = status_handler(State(app_state.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response.state, "idle");
+
+        let tracker = app_state.progress.clone();
+        tracker.track("snapshot", async {
+            progress::step("fetching voter snapshot page", 3, 32);
+            let (_, Json(response)) = status_handler(State(app_state.clone())).await;
+            assert_eq!(response.state, "busy");
+            assert_eq!(response.operations[0].done, Some(3));
+        }).await;
+    }
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::State, http::StatusCode, response::Json
+};
+
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+
+use crate::{
+    api::routes::root::AppState, models::VersionInfo, multi_block_state_client::StorageTrait, primitives::Storage, simulate::SimulateService, snapshot::SnapshotService
+};
+
+pub async fn version_handler<
+Sim: SimulateService + Send + Sync + 'static,
+Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+MC: MinerConfig + Send + Sync + Clone + 'static,
+S: StorageTrait + From<Storage> + Clone + 'static,
+>(
+    State(state): State<AppState<Sim, Snap, MC, S>>,
+) -> (StatusCode, Json<VersionInfo>)
+{
+    (StatusCode::OK, Json(state.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::handler::test_state;
+    use crate::snapshot::MockSnapshotService;
+    use crate::simulate::MockSimulateService;
+
+    #[tokio::test]
+    async fn test_version_handler() {
+        let version = VersionInfo {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            spec_name: "statemint".to_string(),
+            spec_version: 2_000_000,
+            metadata_version: Some(16),
+        };
+        let app_state = AppState { version: version.clone(), ..test_state(MockSimulateService::new(), MockSnapshotService::new()) };
+        let (status, Json(response)) = version_handler(State(app_state)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response, version);
+    }
+}
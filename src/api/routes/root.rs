@@ -1,13 +1,17 @@
 use std::sync::Arc;
-use crate::{models::Chain, multi_block_state_client::StorageTrait, primitives::Storage};
+use crate::{miner_config::ChainContext, models::{Chain, VersionInfo}, multi_block_state_client::StorageTrait, primitives::{AccountId, Storage}, progress::ProgressTracker};
 use axum::{
     Router,
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
     routing::{IntoMakeService, get, post},
 };
 use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use tokio::sync::Semaphore;
 use tower_http::trace::TraceLayer;
 
-use crate::api::handler::{simulate, snapshot};
+use crate::api::handler::{elect, era, simulate, snapshot, status, version};
 use crate::simulate::{SimulateService};
 use crate::snapshot::{SnapshotService};
 
@@ -19,7 +23,10 @@ pub struct AppState<
 > {
     pub simulate_service: Arc<Sim>,
     pub snapshot_service: Arc<Snap>,
-    pub chain: Chain,
+    pub context: ChainContext,
+    pub version: VersionInfo,
+    pub progress: ProgressTracker,
+    pub elections: Arc<Semaphore>,
     pub _phantom: std::marker::PhantomData<(MC, S)>,
 }
 
@@ -33,63 +40,123 @@ impl<
         Self {
             simulate_service: self.simulate_service.clone(),
             snapshot_service: self.snapshot_service.clone(),
-            chain: self.chain.clone(),
+            context: self.context.clone(),
+            version: self.version.clone(),
+            progress: self.progress.clone(),
+            elections: self.elections.clone(),
             _phantom: std::marker::PhantomData,
         }
     }
 }
 
-pub fn routes<
+impl<
     Sim: SimulateService + Send + Sync + 'static,
     Snap: SnapshotService<MC, S> + Send + Sync + 'static,
     MC: MinerConfig + Send + Sync + Clone + 'static,
     S: StorageTrait + From<Storage> + Clone + 'static,
+> AppState<Sim, Snap, MC, S> {
+    /// State of a chain's routes, with nothing running yet
+    pub fn new(simulate_service: Arc<Sim>, snapshot_service: Arc<Snap>, context: ChainContext, version: VersionInfo) -> Self {
+        Self {
+            simulate_service,
+            snapshot_service,
+            context,
+            version,
+            progress: ProgressTracker::default(),
+            elections: elect::election_permits(),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+// Run every request of a chain with its miner constants and address format
+async fn scope_chain(State(context): State<ChainContext>, request: Request, next: Next) -> Response {
+    context.scope(next.run(request)).await
+}
+
+/// Routes of a single chain, backed by the services built for its `MinerConfig`.
+pub fn chain_routes<
+    Sim: SimulateService + Send + Sync + 'static,
+    Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+    MC: MinerConfig<AccountId = AccountId> + Send + Sync + Clone + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
 >(
     simulate_service: Arc<Sim>,
     snapshot_service: Arc<Snap>,
-    chain: Chain,
-) -> IntoMakeService<Router>
+    context: ChainContext,
+    version: VersionInfo,
+) -> Router
 {
-
-    
-    let app_state = AppState {
-        simulate_service,
-        snapshot_service,
-        chain,
-        _phantom: std::marker::PhantomData,
-    };
+    let app_state = AppState::new(simulate_service, snapshot_service, context.clone(), version);
     
-    let app_router = Router::new()
+    Router::new()
         .route("/simulate", post(simulate::simulate_handler))
+        .route("/elect", post(elect::elect_handler))
         .route("/snapshot", get(snapshot::snapshot_handler))
+        .route("/era", get(era::era_handler))
+        .route("/version", get(version::version_handler))
+        .route("/status", get(status::status_handler))
+        .route_layer(middleware::from_fn_with_state(context, scope_chain))
         .with_state(app_state)
-        .layer(TraceLayer::new_for_http());
-    app_router.into_make_service()
+}
+
+/// Serve every chain under its own prefix (e.g. `/kusama/simulate`); the first chain is also
+/// served without a prefix, as when the server handled a single chain.
+pub fn routes(chains: Vec<(Chain, Router)>) -> IntoMakeService<Router>
+{
+    let mut app_router = Router::new();
+    for (index, (chain, router)) in chains.into_iter().enumerate() {
+        if index == 0 {
+            app_router = app_router.merge(router.clone());
+        }
+        app_router = app_router.nest(&format!("/{}", chain.name()), router);
+    }
+    app_router
+        .layer(TraceLayer::new_for_http())
+        .into_make_service()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum_test::TestServer;
-    use crate::miner_config::initialize_runtime_constants;
+    use crate::miner_config::{initialize_runtime_constants, test_chain_context};
     use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
     use crate::snapshot::MockSnapshotService;
     use crate::simulate::MockSimulateService;
 
+    fn version_routes(spec_name: &str) -> Router {
+        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
+        chain_routes(
+            Arc::new(MockSimulateService::new()),
+            Arc::new(snapshot_service),
+            test_chain_context(),
+            VersionInfo { spec_name: spec_name.to_string(), ..VersionInfo::default() },
+        )
+    }
+
     #[tokio::test]
     async fn test_routes() {
         initialize_runtime_constants();
-        let simulate_service = Arc::new(MockSimulateService::new());
-        let snapshot_service: MockSnapshotService<PolkadotMinerConfig, Storage> = MockSnapshotService::new();
-        let snapshot_service = Arc::new(snapshot_service);
-        let app_service = routes(
-            simulate_service,
-            snapshot_service,
-            Chain::Polkadot,
-        );
+        let app_service = routes(vec![(Chain::Polkadot, version_routes("statemint"))]);
         let client = TestServer::new(app_service);
         assert!(client.is_ok());
         let client = client.unwrap();
         assert!(client.is_running());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_routes_per_chain() {
+        initialize_runtime_constants();
+        let app_service = routes(vec![
+            (Chain::Polkadot, version_routes("statemint")),
+            (Chain::Kusama, version_routes("statemine")),
+        ]);
+        let client = TestServer::new(app_service).unwrap();
+        let spec_name = |response: axum_test::TestResponse| response.json::<serde_json::Value>()["spec_name"].clone();
+        assert_eq!(spec_name(client.get("/version").await), "statemint");
+        assert_eq!(spec_name(client.get("/polkadot/version").await), "statemint");
+        assert_eq!(spec_name(client.get("/kusama/version").await), "statemine");
+        client.get("/substrate/version").expect_failure().await.assert_status_not_found();
+    }
+}
@@ -2,6 +2,8 @@ use std::str::FromStr;
 
 use sp_core::H256;
 
+use crate::models::{DEFAULT_FLOAT_PRECISION, MAX_FLOAT_PRECISION};
+
 pub fn parse_block(block_str: Option<String>) -> Result<Option<H256>, Box<dyn std::error::Error>> {
     if block_str.is_none() {
         return Ok(None);
@@ -18,6 +20,14 @@ pub fn parse_block(block_str: Option<String>) -> Result<Option<H256>, Box<dyn st
     Ok(Some(block))
 }
 
+pub fn parse_precision(precision: Option<u32>) -> Result<u32, Box<dyn std::error::Error>> {
+    let precision = precision.unwrap_or(DEFAULT_FLOAT_PRECISION);
+    if precision > MAX_FLOAT_PRECISION {
+        return Err(format!("Invalid precision: {} (at most {} decimal places)", precision, MAX_FLOAT_PRECISION).into());
+    }
+    Ok(precision)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +63,13 @@ mod tests {
         let block = block.unwrap();
         assert!(block.is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_precision() {
+        assert_eq!(parse_precision(None).unwrap(), DEFAULT_FLOAT_PRECISION);
+        assert_eq!(parse_precision(Some(0)).unwrap(), 0);
+        assert_eq!(parse_precision(Some(MAX_FLOAT_PRECISION)).unwrap(), MAX_FLOAT_PRECISION);
+        assert!(parse_precision(Some(MAX_FLOAT_PRECISION + 1)).is_err());
+        assert!(parse_precision(Some(u32::MAX)).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use chrono::DateTime;
+use sp_core::H256;
+use tracing::info;
+
+use crate::raw_state_client::{RawClientTrait, RpcClient};
+
+// Milliseconds since the Unix epoch of an RFC 3339 time, matching Timestamp::Now
+pub fn parse_time(time: &str) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let parsed = DateTime::parse_from_rfc3339(time)
+        .map_err(|e| format!("Invalid --at-time '{}', expected RFC 3339 (e.g. 2025-01-31T12:00:00Z): {}", time, e))?;
+    u64::try_from(parsed.timestamp_millis())
+        .map_err(|_| format!("--at-time {} predates the Unix epoch", time).into())
+}
+
+async fn timestamp_of<C: RpcClient + Send + Sync + 'static, R: RawClientTrait<C>>(client: &R, number: u32) -> Result<(H256, u64), Box<dyn std::error::Error + Send + Sync>> {
+    let hash = client.get_block_hash(number).await?
+        .ok_or_else(|| format!("Block {} not found", number))?;
+    let timestamp = client.get_timestamp(Some(hash)).await?
+        .ok_or_else(|| format!("Timestamp::Now not found at block {}", number))?;
+    Ok((hash, timestamp))
+}
+
+// Binary search block timestamps for the block closest to `target` (ms); `now` bounds future times
+pub async fn find_block_at_time<C: RpcClient + Send + Sync + 'static, R: RawClientTrait<C>>(
+    client: &R,
+    target: u64,
+    now: u64,
+) -> Result<H256, Box<dyn std::error::Error + Send + Sync>> {
+    if target > now {
+        return Err(format!("--at-time {} ms is in the future", target).into());
+    }
+    let latest = client.get_block_number(None).await?;
+    if latest == 0 {
+        return Err("Chain has no blocks after genesis".into());
+    }
+    // Genesis has no timestamp, so block 1 is the earliest block with a time
+    let (first_hash, first_time) = timestamp_of(client, 1).await?;
+    if target < first_time {
+        return Err(format!("--at-time {} ms predates the chain's first block at {} ms", target, first_time).into());
+    }
+    if target == first_time {
+        return Ok(first_hash);
+    }
+
+    // Largest block whose timestamp is at or before the target
+    let (mut low, mut high) = (1u32, latest);
+    let mut low_block = (first_hash, first_time);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        let block = timestamp_of(client, mid).await?;
+        if block.1 <= target {
+            low = mid;
+            low_block = block;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    let (mut hash, mut timestamp, mut number) = (low_block.0, low_block.1, low);
+    if low < latest {
+        let (next_hash, next_time) = timestamp_of(client, low + 1).await?;
+        if next_time - target < target - timestamp {
+            (hash, timestamp, number) = (next_hash, next_time, low + 1);
+        }
+    }
+    info!("Resolved time {} ms to block {} ({:?}) at {} ms", target, number, hash, timestamp);
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw_state_client::{MockRawClientTrait, MockRpcClient};
+
+    // Blocks every 6 seconds from block 1 at 1_000_000 ms up to block 1000
+    fn chain() -> MockRawClientTrait<MockRpcClient> {
+        let mut client = MockRawClientTrait::<MockRpcClient>::new();
+        client.expect_get_block_number().returning(|_| Ok(1000));
+        client.expect_get_block_hash().returning(|number| Ok(Some(H256::from_low_u64_be(number as u64))));
+        client.expect_get_timestamp().returning(|at| {
+            let number = at.unwrap().to_low_u64_be();
+            Ok(Some(1_000_000 + (number - 1) * 6_000))
+        });
+        client
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("2023-11-14T22:13:20Z").unwrap(), 1_700_000_000_000);
+        assert_eq!(parse_time("2023-11-14T23:13:20+01:00").unwrap(), 1_700_000_000_000);
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("1969-12-31T23:59:59Z").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_block_at_time() {
+        let client = chain();
+        let now = u64::MAX;
+        // Exact, closest below, closest above
+        assert_eq!(find_block_at_time(&client, 1_000_000 + 99 * 6_000, now).await.unwrap(), H256::from_low_u64_be(100));
+        assert_eq!(find_block_at_time(&client, 1_000_000 + 99 * 6_000 + 2_000, now).await.unwrap(), H256::from_low_u64_be(100));
+        assert_eq!(find_block_at_time(&client, 1_000_000 + 99 * 6_000 + 4_000, now).await.unwrap(), H256::from_low_u64_be(101));
+        assert_eq!(find_block_at_time(&client, 1_000_000, now).await.unwrap(), H256::from_low_u64_be(1));
+        // Past the latest block but not in the future
+        assert_eq!(find_block_at_time(&client, 1_000_000 + 2_000 * 6_000, now).await.unwrap(), H256::from_low_u64_be(1000));
+    }
+
+    #[tokio::test]
+    async fn test_find_block_at_time_out_of_range() {
+        let client = chain();
+        let err = find_block_at_time(&client, 999_999, u64::MAX).await.unwrap_err();
+        assert!(err.to_string().contains("predates"));
+        let err = find_block_at_time(&client, 2_000_000, 1_500_000).await.unwrap_err();
+        assert!(err.to_string().contains("in the future"));
+    }
+}
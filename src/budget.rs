@@ -0,0 +1,111 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::channel::oneshot;
+use tokio::task_local;
+use tracing::warn;
+
+use crate::miner_config::MinerScope;
+
+struct Budget {
+    max_duration: Duration,
+    // Data gathering stops here, leaving the rest of the budget to mine what was gathered
+    gather_deadline: Instant,
+    deadline: Instant,
+    cuts: Arc<Mutex<Vec<String>>>,
+}
+
+// Time budget of the current run, only present while running under `with_max_duration`
+task_local! {
+    static BUDGET: Budget;
+}
+
+/// Run `f` under a `--max-duration` budget, returning what data gathering it cut short.
+/// Without a budget `f` runs unbounded and nothing is ever cut.
+pub async fn with_max_duration<F, R>(max_duration: Option<Duration>, f: F) -> (R, Vec<String>)
+where
+    F: std::future::Future<Output = R>,
+{
+    let Some(max_duration) = max_duration else {
+        return (f.await, Vec::new());
+    };
+    let started = Instant::now();
+    let cuts = Arc::new(Mutex::new(Vec::new()));
+    let budget = Budget {
+        max_duration,
+        gather_deadline: started + max_duration * 3 / 4,
+        deadline: started + max_duration,
+        cuts: cuts.clone(),
+    };
+    let result = BUDGET.scope(budget, f).await;
+    let cuts = std::mem::take(&mut *cuts.lock().unwrap());
+    (result, cuts)
+}
+
+/// Whether the data gathering share of the budget ran out. Always false outside `with_max_duration`
+pub fn exceeded() -> bool {
+    BUDGET.try_with(|budget| Instant::now() >= budget.gather_deadline).unwrap_or(false)
+}
+
+/// Record that data gathering stopped early because the budget ran out
+pub fn cut(note: String) {
+    let _ = BUDGET.try_with(|budget| {
+        warn!("--max-duration of {}s ran out: {}", budget.max_duration.as_secs(), note);
+        budget.cuts.lock().unwrap().push(note);
+    });
+}
+
+/// Run the blocking `step`, e.g. mining, on a thread of its own so it can be given up on when
+/// the budget runs out, failing with an error naming the step. Without a budget `f` runs here
+pub async fn run_blocking<T: Send + 'static>(step: &str, f: impl FnOnce() -> T + Send + 'static) -> Result<T, String> {
+    let Ok((max_duration, deadline)) = BUDGET.try_with(|budget| (budget.max_duration, budget.deadline)) else {
+        return Ok(f());
+    };
+    let scope = MinerScope::current();
+    let (sender, receiver) = oneshot::channel();
+    // Left running when the budget runs out; it ends with the process
+    std::thread::spawn(move || {
+        let _ = sender.send(scope.run(f));
+    });
+    match tokio::time::timeout(deadline.saturating_duration_since(Instant::now()), receiver).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err(format!("{} stopped without a result", step)),
+        Err(_) => Err(format!("{} exceeded the --max-duration budget of {}s", step, max_duration.as_secs())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cuts_only_recorded_under_budget() {
+        cut("outside any budget".to_string());
+        assert!(!exceeded());
+
+        let (value, cuts) = with_max_duration(None, async { exceeded() }).await;
+        assert!(!value);
+        assert!(cuts.is_empty());
+
+        let (value, cuts) = with_max_duration(Some(Duration::ZERO), async {
+            cut("fetched 1 of 4 voter snapshot pages".to_string());
+            exceeded()
+        }).await;
+        assert!(value);
+        assert_eq!(cuts, vec!["fetched 1 of 4 voter snapshot pages".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_run_blocking_within_budget() {
+        assert_eq!(run_blocking("Mining", || 7).await, Ok(7));
+
+        let (value, _) = with_max_duration(Some(Duration::from_secs(60)), run_blocking("Mining", || 7)).await;
+        assert_eq!(value, Ok(7));
+
+        let (value, _) = with_max_duration(
+            Some(Duration::ZERO),
+            run_blocking("Mining", || std::thread::sleep(Duration::from_millis(200))),
+        ).await;
+        assert_eq!(value, Err("Mining exceeded the --max-duration budget of 0s".to_string()));
+    }
+}
@@ -0,0 +1,73 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+use pallet_staking::ValidatorPrefs;
+
+use crate::primitives::{AccountId, Hash};
+
+/// Default number of cached prefs, enough for the active set of a few recent blocks.
+pub const DEFAULT_PREFS_CACHE_SIZE: usize = 4096;
+
+/// Bounded LRU of validator prefs shared across simulations.
+///
+/// Entries are keyed by block hash and stash so prefs read at one block are never
+/// served for another. Requests against the latest block have no fixed hash and
+/// bypass the cache.
+#[derive(Clone)]
+pub struct ValidatorPrefsCache {
+    inner: Arc<Mutex<LruCache<(Hash, AccountId), ValidatorPrefs>>>,
+}
+
+impl ValidatorPrefsCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self { inner: Arc::new(Mutex::new(LruCache::new(capacity))) }
+    }
+
+    pub fn get(&self, block_hash: Hash, stash: &AccountId) -> Option<ValidatorPrefs> {
+        let mut cache = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cache.get(&(block_hash, stash.clone())).cloned()
+    }
+
+    pub fn insert(&self, block_hash: Hash, stash: AccountId, prefs: ValidatorPrefs) {
+        let mut cache = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        cache.put((block_hash, stash), prefs);
+    }
+}
+
+impl Default for ValidatorPrefsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PREFS_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_runtime::Perbill;
+
+    fn prefs(commission: u32) -> ValidatorPrefs {
+        ValidatorPrefs { commission: Perbill::from_parts(commission), blocked: false }
+    }
+
+    #[test]
+    fn test_cache_keyed_by_block_hash() {
+        let cache = ValidatorPrefsCache::new(10);
+        let stash = AccountId::from([1u8; 32]);
+        cache.insert(Hash::zero(), stash.clone(), prefs(5));
+        assert_eq!(cache.get(Hash::zero(), &stash), Some(prefs(5)));
+        assert_eq!(cache.get(Hash::repeat_byte(1), &stash), None);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let cache = ValidatorPrefsCache::new(1);
+        let first = AccountId::from([1u8; 32]);
+        let second = AccountId::from([2u8; 32]);
+        cache.insert(Hash::zero(), first.clone(), prefs(1));
+        cache.insert(Hash::zero(), second.clone(), prefs(2));
+        assert_eq!(cache.get(Hash::zero(), &first), None);
+        assert_eq!(cache.get(Hash::zero(), &second), Some(prefs(2)));
+    }
+}
@@ -0,0 +1,149 @@
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use parity_scale_codec::Encode;
+use serde::Serialize;
+use sp_core::H256;
+use sp_npos_elections::ElectionScore as NposElectionScore;
+
+use crate::miner_config::election_sub_pallet;
+use crate::models::{ElectionScore, EncodedSolutionPage};
+use crate::multi_block_state_client::{ChainClientTrait, MultiBlockClientTrait, StorageTrait};
+use crate::primitives::Storage;
+
+// Calls of the pallet that takes signed multi-block solutions
+const REGISTER_CALL: &str = "register";
+const SUBMIT_PAGE_CALL: &str = "submit_page";
+
+// Pallet and call variant indices identifying a call in the runtime
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CallIndex {
+    pub pallet: u8,
+    pub call: u8,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PageCalldata {
+    pub page: u32,
+    pub call_data: String,
+}
+
+// Hex call data of a signed submission: register the score, then submit each solution page
+#[derive(Debug, Serialize)]
+pub struct SubmissionCalldata {
+    pub register: String,
+    pub submit_pages: Vec<PageCalldata>,
+}
+
+pub fn call_index(metadata: &subxt::Metadata, pallet: &str, call: &str) -> Result<CallIndex, Box<dyn std::error::Error + Send + Sync>> {
+    let pallet_metadata = metadata.pallet_by_name(pallet)
+        .ok_or_else(|| format!("Pallet {} not found in metadata", pallet))?;
+    let variant = pallet_metadata.call_variant_by_name(call)
+        .ok_or_else(|| format!("Call {}::{} not found in metadata", pallet, call))?;
+    Ok(CallIndex { pallet: pallet_metadata.index(), call: variant.index })
+}
+
+pub fn signed_submission_indices(metadata: &subxt::Metadata) -> Result<(CallIndex, CallIndex), Box<dyn std::error::Error + Send + Sync>> {
+    let signed_pallet = election_sub_pallet("Signed");
+    Ok((call_index(metadata, &signed_pallet, REGISTER_CALL)?, call_index(metadata, &signed_pallet, SUBMIT_PAGE_CALL)?))
+}
+
+// The chain scores a submission against the paged snapshot it stored, so a solution mined from a
+// reconstruction of staking storage would be rejected
+pub async fn require_stored_snapshot<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    block: Option<H256>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let storage = client.get_storage(block).await?;
+    let block_details = client.get_block_details(&storage, block).await?;
+    if !block_details.phase.has_snapshot() {
+        return Err(format!(
+            "--emit-calldata needs the snapshot stored by the chain, but the block has none (phase {:?}) and it would be reconstructed; use a block from the Signed phase",
+            block_details.phase
+        ).into());
+    }
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+pub fn signed_submission(
+    register: CallIndex,
+    submit_page: CallIndex,
+    score: &ElectionScore,
+    pages: &[EncodedSolutionPage],
+) -> SubmissionCalldata {
+    let claimed_score = NposElectionScore {
+        minimal_stake: score.minimal_stake,
+        sum_stake: score.sum_stake,
+        sum_stake_squared: score.sum_stake_squared,
+    };
+    let mut register_call = vec![register.pallet, register.call];
+    claimed_score.encode_to(&mut register_call);
+
+    let submit_pages = pages.iter().map(|page| {
+        let mut call = vec![submit_page.pallet, submit_page.call];
+        page.page.encode_to(&mut call);
+        // `Some` of the boxed solution, which encodes as the solution itself
+        call.push(1);
+        call.extend_from_slice(&page.solution);
+        PageCalldata { page: page.page, call_data: to_hex(&call) }
+    }).collect();
+
+    SubmissionCalldata { register: to_hex(&register_call), submit_pages }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
+    use crate::multi_block_state_client::test_storage::MockDummyStorage;
+    use crate::multi_block_state_client::{BlockDetails, MockChainClientTrait, MockMultiBlockClientTrait, Phase};
+
+    #[test]
+    fn test_signed_submission() {
+        let score = ElectionScore { minimal_stake: 1, sum_stake: 2, sum_stake_squared: 3 };
+        let pages = vec![
+            EncodedSolutionPage { page: 2, solution: vec![0xaa, 0xbb] },
+            EncodedSolutionPage { page: 3, solution: vec![0xcc] },
+        ];
+        let calldata = signed_submission(CallIndex { pallet: 85, call: 0 }, CallIndex { pallet: 85, call: 1 }, &score, &pages);
+
+        let mut expected_register = vec![85u8, 0];
+        for value in [1u128, 2, 3] {
+            expected_register.extend_from_slice(&value.to_le_bytes());
+        }
+        assert_eq!(calldata.register, to_hex(&expected_register));
+        let submit_pages: Vec<(u32, &str)> = calldata.submit_pages.iter().map(|p| (p.page, p.call_data.as_str())).collect();
+        assert_eq!(submit_pages, vec![(2, "0x55010200000001aabb"), (3, "0x55010300000001cc")]);
+    }
+
+    #[tokio::test]
+    async fn test_require_stored_snapshot() {
+        for (phase, stored) in [(Phase::Signed(10), true), (Phase::Off, false), (Phase::Emergency, false)] {
+            let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+            mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+            mock_client
+                .expect_get_block_details()
+                .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(BlockDetails {
+                    block_hash: None,
+                    phase,
+                    round: 1,
+                    n_pages: 1,
+                    desired_targets: 10,
+                    _block_number: 100,
+                }));
+
+            let result = require_stored_snapshot(&mock_client, None).await;
+            assert_eq!(result.is_ok(), stored, "{:?}", phase);
+            if let Err(e) = result {
+                assert!(e.to_string().starts_with("--emit-calldata needs the snapshot stored by the chain"), "{}", e);
+            }
+        }
+    }
+}
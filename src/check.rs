@@ -0,0 +1,138 @@
+use std::net::SocketAddr;
+
+use sp_core::H256;
+use sp_core::crypto::set_default_ss58_version;
+
+use crate::block_time;
+use crate::raw_state_client::user_agent_headers;
+use crate::simulate::{parse_account, Override};
+use crate::snapshot::check_scan_range;
+use crate::{Action, Args};
+
+/// Validate everything `--check` can without a connection: endpoint schemes, the RPC user agent,
+/// block hashes and times, the manual override file and every address given on the command line.
+pub fn check_args(args: &Args) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    check_endpoint(&args.rpc_endpoint)?;
+    user_agent_headers(&args.rpc_user_agent)?;
+    // Without a connection the SS58 prefix is only known when given; otherwise any known format passes
+    if let Some(format) = args.ss58_prefix.or(args.chain.map(|chain| chain.ss58_address_format())) {
+        set_default_ss58_version(format);
+    }
+
+    match &args.action {
+        Action::Simulate(simulate_args) => {
+            check_block(&simulate_args.block, simulate_args.at_time.as_deref())?;
+            if let Some(path) = simulate_args.manual_override.as_deref() {
+                Override::load(path)?.validate()?;
+            }
+            for account in simulate_args.exclude_voters.iter().chain(&simulate_args.exclude_candidates) {
+                parse_account(account)?;
+            }
+            if let Some(endpoint) = simulate_args.identity_endpoint.as_deref() {
+                check_endpoint(endpoint)?;
+            }
+        }
+        Action::Snapshot(snapshot_args) => check_block(&snapshot_args.block, snapshot_args.at_time.as_deref())?,
+        Action::Backers(backers_args) => {
+            parse_account(&backers_args.stash)?;
+            check_block(&backers_args.block, None)?;
+        }
+        Action::FindSnapshots(find_args) => check_scan_range(find_args.from, find_args.to, find_args.step)?,
+        Action::Submissions(submissions_args) => check_block(&submissions_args.block, None)?,
+        Action::Server { address, extra_endpoints } => {
+            address.parse::<SocketAddr>()
+                .map_err(|e| format!("Invalid server address '{}': {}", address, e))?;
+            for endpoint in extra_endpoints {
+                check_endpoint(endpoint)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// The RPC clients only speak websockets
+fn check_endpoint(endpoint: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if endpoint.starts_with("ws://") || endpoint.starts_with("wss://") {
+        Ok(())
+    } else {
+        Err(format!("Unsupported endpoint '{}': expected a ws:// or wss:// URL", endpoint).into())
+    }
+}
+
+fn check_block(block: &str, at_time: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(time) = at_time {
+        block_time::parse_time(time)?;
+    } else if block != "latest" {
+        block.parse::<H256>().map_err(|e| format!("Invalid block hash '{}': {}", block, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::io::Write;
+
+    const ENDPOINT: &str = "wss://sys.ibp.network/asset-hub-polkadot";
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn check(argv: &[&str]) -> Result<(), String> {
+        let args = Args::try_parse_from(argv).map_err(|e| e.to_string())?;
+        check_args(&args).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_check_args() {
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "--exclude-voter", ALICE]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "backers", ALICE]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "snapshot", "--at-time", "2025-01-31T12:00:00Z"]).is_ok());
+
+        let error = check(&["tool", "-r", "https://rpc.example", "--check", "snapshot"]).unwrap_err();
+        assert!(error.starts_with("Unsupported endpoint 'https://rpc.example'"), "{}", error);
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "backers", "not-an-address"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "snapshot", "-b", "0x1234"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "find-snapshots", "--from", "10", "--to", "5"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "server", "-a", "localhost"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--rpc-user-agent", "dashboard/2.1", "--check", "snapshot"]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--rpc-user-agent", "bad\nagent", "--check", "snapshot"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--rpc-retries", "0", "--rpc-max-delay", "5", "--check", "snapshot"]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--rpc-max-delay", "0", "--check", "snapshot"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "--pretty-addresses", "--identity-endpoint", "wss://sys.ibp.network/people-polkadot"]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "--pretty-addresses", "--identity-endpoint", "https://rpc.example"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "--precision", "12"]).is_ok());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "--precision", "13"]).is_err());
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "snapshot", "--precision", "400"]).is_err());
+    }
+
+    #[test]
+    fn test_emit_calldata_conflicts() {
+        let calldata = ["tool", "-r", ENDPOINT, "--check", "simulate", "--emit-calldata", "calldata.json"];
+        assert!(check(&calldata).is_ok());
+        for extra in [
+            &["-m", "override.json"][..], &["--exclude-voter", ALICE], &["--exclude-candidate", ALICE],
+            &["--desired-validators", "10"], &["--max-nominations", "8"], &["--min-nominator-bond", "1"],
+            &["--min-validator-bond", "1"], &["--exclude-chilled"], &["--unsigned-mode"], &["--max-duration", "60"],
+            &["--sample", "100"], &["--iteration-sweep", "10,20"],
+        ] {
+            let argv: Vec<&str> = calldata.iter().chain(extra).copied().collect();
+            let error = check(&argv).unwrap_err();
+            assert!(error.contains("cannot be used with"), "{:?}: {}", extra, error);
+        }
+    }
+
+    #[test]
+    fn test_check_args_manual_override() {
+        let dir = std::env::temp_dir();
+        let valid = dir.join("check_override_valid.json");
+        let invalid = dir.join("check_override_invalid.json");
+        write!(std::fs::File::create(&valid).unwrap(), r#"{{"voters":[["{ALICE}",100,["{ALICE}"]]],"voters_remove":[],"candidates":[],"candidates_remove":[]}}"#).unwrap();
+        write!(std::fs::File::create(&invalid).unwrap(), r#"{{"voters":[],"voters_remove":["0x12"],"candidates":[],"candidates_remove":[]}}"#).unwrap();
+
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "-m", valid.to_str().unwrap()]).is_ok());
+        let error = check(&["tool", "-r", ENDPOINT, "--check", "simulate", "-m", invalid.to_str().unwrap()]).unwrap_err();
+        assert!(error.starts_with("Invalid hex account '0x12'"), "{}", error);
+        let missing = dir.join("check_override_missing.json");
+        assert!(check(&["tool", "-r", ENDPOINT, "--check", "simulate", "-m", missing.to_str().unwrap()]).is_err());
+    }
+}
@@ -0,0 +1,557 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use futures::future::join_all;
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use sp_core::H256;
+use sp_npos_elections::ElectionScore as NposElectionScore;
+use tracing::{info, warn};
+
+use crate::miner_config::to_ss58;
+use crate::models::{round_float, Chain, CommissionBandOutput, ElectionScore, IterationSweep, IterationSweepStep, QueuedComparison, QueuedValidator, ReduceComparison, ScoreComparison, ScoreDifference, ScoreOutcome, SignedSubmission, SignedSubmissions, SimulationResult, StakeMismatch, Validator, ValidatorOverlap};
+use crate::multi_block_state_client::{ChainClientTrait, MultiBlockClientTrait, StorageTrait};
+use crate::primitives::{AccountId, Balance, EraIndex, Storage};
+use crate::raw_state_client::{RawClientTrait, RpcClient};
+
+/// Fetch the validators already queued on chain for the era following the active one.
+/// Returns the queued era and its validators; the list is empty when the next era
+/// has not been planned yet at the given block.
+pub async fn get_queued_validators<
+    CC: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+    RC: RpcClient + Send + Sync + 'static,
+    RawC: RawClientTrait<RC> + Send + Sync + 'static,
+>(
+    multi_block_state_client: &MBC,
+    raw_state_client: &RawC,
+    block: Option<H256>,
+) -> Result<(EraIndex, Vec<QueuedValidator>), Box<dyn std::error::Error + Send + Sync>> {
+    let storage = multi_block_state_client.get_storage(block).await?;
+    let active_era = multi_block_state_client.get_active_era(&storage)
+        .await?
+        .ok_or("Staking::ActiveEra not found")?;
+    let queued_era = active_era.index + 1;
+
+    let stashes = raw_state_client.get_era_validators(queued_era, block).await?;
+    if stashes.is_empty() {
+        warn!("No validators queued yet for era {} (active era: {})", queued_era, active_era.index);
+        return Ok((queued_era, Vec::new()));
+    }
+    info!("Found {} validators queued for era {}", stashes.len(), queued_era);
+
+    let storage = &storage;
+    let overview_futures: Vec<_> = stashes.into_iter().map(|stash| {
+        async move {
+            let overview = multi_block_state_client.get_era_stakers_overview(storage, queued_era, stash.clone())
+                .await
+                .map_err(|e| format!("Error getting era stakers overview: {}", e))?;
+            Ok::<QueuedValidator, String>(QueuedValidator {
+                stash: to_ss58(&stash),
+                total_stake: overview.as_ref().map_or(0, |o| o.total),
+                own_stake: overview.as_ref().map_or(0, |o| o.own),
+            })
+        }
+    }).collect();
+
+    let queued = join_all(overview_futures)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((queued_era, queued))
+}
+
+/// Relative difference of two stakes in percent of the larger one, so it stays within 0-100.
+fn stake_difference_percent(a: Balance, b: Balance) -> f64 {
+    let larger = a.max(b);
+    if larger == 0 {
+        return 0.0;
+    }
+    a.abs_diff(b) as f64 / larger as f64 * 100.0
+}
+
+/// Compare the simulated active set against the validators queued on chain. Validators in
+/// both sets also have their total stake compared; differences up to `tolerance_percent`
+/// count as within tolerance, the others are listed largest first.
+pub fn compare_with_queued(result: &SimulationResult, era: EraIndex, queued: &[QueuedValidator], tolerance_percent: f64, precision: u32) -> QueuedComparison {
+    let simulated: HashSet<&str> = result.active_validators.iter().map(|v| v.stash.as_str()).collect();
+    let queued_set: HashSet<&str> = queued.iter().map(|v| v.stash.as_str()).collect();
+
+    let queued_stakes: HashMap<&str, Balance> = queued.iter().map(|v| (v.stash.as_str(), v.total_stake)).collect();
+    let mut within_tolerance_count = 0;
+    let mut beyond_tolerance = Vec::new();
+    for validator in &result.active_validators {
+        let Some(&queued_stake) = queued_stakes.get(validator.stash.as_str()) else {
+            continue;
+        };
+        let difference_percent = stake_difference_percent(validator.total_stake, queued_stake);
+        if difference_percent <= tolerance_percent {
+            within_tolerance_count += 1;
+        } else {
+            beyond_tolerance.push(StakeMismatch {
+                stash: validator.stash.clone(),
+                simulated_stake: validator.total_stake,
+                queued_stake,
+                difference_percent: round_float(difference_percent, precision),
+            });
+        }
+    }
+    beyond_tolerance.sort_by(|x, y| y.difference_percent.total_cmp(&x.difference_percent).then(x.stash.cmp(&y.stash)));
+
+    let mut only_simulated: Vec<String> = simulated.difference(&queued_set).map(|s| s.to_string()).collect();
+    let mut only_queued: Vec<String> = queued_set.difference(&simulated).map(|s| s.to_string()).collect();
+    only_simulated.sort();
+    only_queued.sort();
+
+    QueuedComparison {
+        era,
+        simulated_count: simulated.len(),
+        queued_count: queued_set.len(),
+        matching_count: simulated.intersection(&queued_set).count(),
+        only_simulated,
+        only_queued,
+        tolerance_percent,
+        within_tolerance_count,
+        beyond_tolerance_count: beyond_tolerance.len(),
+        beyond_tolerance,
+    }
+}
+
+/// Compare the score of the mined solution with the score of the solution queued on chain
+/// for the round running at the given block.
+pub async fn compare_with_queued_score<
+    CC: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+>(
+    multi_block_state_client: &MBC,
+    block: Option<H256>,
+    score: ElectionScore,
+) -> Result<ScoreComparison, Box<dyn std::error::Error + Send + Sync>> {
+    let storage = multi_block_state_client.get_storage(block).await?;
+    let round = multi_block_state_client.get_round(&storage).await?;
+    let queued = multi_block_state_client.get_queued_solution_score(&storage, round).await?;
+    Ok(score_comparison(round, score, queued))
+}
+
+/// Rank the mined score against the queued one with the chain's `ElectionScore` ordering:
+/// higher minimal stake, then higher sum of stake, then lower sum of stake squared.
+pub fn score_comparison(round: u32, simulated: ElectionScore, queued: Option<NposElectionScore>) -> ScoreComparison {
+    let outcome = match queued.map(|queued| NposElectionScore::from(simulated).cmp(&queued)) {
+        None => ScoreOutcome::NothingQueued,
+        Some(Ordering::Greater) => ScoreOutcome::SimulatedBetter,
+        Some(Ordering::Less) => ScoreOutcome::QueuedBetter,
+        Some(Ordering::Equal) => ScoreOutcome::Equal,
+    };
+    ScoreComparison {
+        round,
+        simulated_score: simulated,
+        queued_score: queued.map(ElectionScore::from),
+        outcome,
+    }
+}
+
+/// List the signed submissions registered for the round running at the given block, along
+/// with the score the verifier has queued so far.
+pub async fn get_signed_submissions<
+    CC: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+>(
+    multi_block_state_client: &MBC,
+    block: Option<H256>,
+) -> Result<SignedSubmissions, Box<dyn std::error::Error + Send + Sync>> {
+    let storage = multi_block_state_client.get_storage(block).await?;
+    let round = multi_block_state_client.get_round(&storage).await?;
+    let scores = multi_block_state_client.get_sorted_scores(&storage, round).await?;
+    let queued = multi_block_state_client.get_queued_solution_score(&storage, round).await?;
+    Ok(rank_submissions(round, scores, queued))
+}
+
+/// Order signed submissions best first with the chain's `ElectionScore` ordering and flag
+/// those whose claimed score beats the queued one.
+pub fn rank_submissions(round: u32, mut scores: Vec<(AccountId, NposElectionScore)>, queued: Option<NposElectionScore>) -> SignedSubmissions {
+    scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+    let submissions = scores.into_iter().enumerate().map(|(index, (submitter, score))| SignedSubmission {
+        rank: index + 1,
+        submitter: to_ss58(&submitter),
+        score: ElectionScore::from(score),
+        beats_queued: queued.map(|queued| score > queued),
+    }).collect();
+    SignedSubmissions {
+        round,
+        queued_score: queued.map(ElectionScore::from),
+        submissions,
+    }
+}
+
+/// Summarise runs of the same election at increasing balancing iterations, reporting for
+/// each step its score and how many elected validators differ from the previous step.
+pub fn iteration_sweep(results: &[SimulationResult]) -> Option<IterationSweep> {
+    let first = results.first()?;
+    let mut previous: Option<HashSet<&str>> = None;
+    let steps = results.iter().map(|result| {
+        let elected: HashSet<&str> = result.active_validators.iter().map(|v| v.stash.as_str()).collect();
+        let changed_validators = previous.as_ref().map_or(0, |previous| elected.difference(previous).count());
+        let step = IterationSweepStep {
+            iterations: result.run_parameters.iterations,
+            score: result.score,
+            elected_count: elected.len(),
+            changed_validators,
+        };
+        previous = Some(elected);
+        step
+    }).collect();
+    Some(IterationSweep { run_parameters: first.run_parameters.clone(), steps })
+}
+
+/// Compare two runs of the same snapshot, mined without and with reduce: which validators
+/// the reduced solution elects differently, and how the score and edge count moved.
+pub fn reduce_comparison(without_reduce: &SimulationResult, with_reduce: &SimulationResult) -> ReduceComparison {
+    let elected = |result: &SimulationResult| -> Vec<String> {
+        result.active_validators.iter().map(|v| v.stash.clone()).collect()
+    };
+    let (before, after) = (elected(without_reduce), elected(with_reduce));
+    let before_set: HashSet<&String> = before.iter().collect();
+    let after_set: HashSet<&String> = after.iter().collect();
+    let only_without_reduce: Vec<String> = before.iter().filter(|s| !after_set.contains(s)).cloned().collect();
+    let only_with_reduce: Vec<String> = after.iter().filter(|s| !before_set.contains(s)).cloned().collect();
+
+    let edges_without_reduce = without_reduce.solution_assignments().edge_count;
+    let edges_with_reduce = with_reduce.solution_assignments().edge_count;
+    let (from, to) = (without_reduce.score, with_reduce.score);
+    ReduceComparison {
+        run_parameters: with_reduce.run_parameters.clone(),
+        score_without_reduce: from,
+        score_with_reduce: to,
+        score_difference: ScoreDifference {
+            minimal_stake: signed_difference(from.minimal_stake, to.minimal_stake),
+            sum_stake: signed_difference(from.sum_stake, to.sum_stake),
+            sum_stake_squared: signed_difference(from.sum_stake_squared, to.sum_stake_squared),
+        },
+        edges_without_reduce,
+        edges_with_reduce,
+        edge_difference: edges_with_reduce as i64 - edges_without_reduce as i64,
+        changed_validators: only_with_reduce.len(),
+        only_without_reduce,
+        only_with_reduce,
+    }
+}
+
+// `to - from`, saturating at the bounds of i128
+fn signed_difference(from: u128, to: u128) -> i128 {
+    let magnitude = i128::try_from(from.abs_diff(to)).unwrap_or(i128::MAX);
+    if to >= from { magnitude } else { -magnitude }
+}
+
+// Upper commission bound of each band after the 0% one; the last band is open ended
+const COMMISSION_BANDS: [(&str, f64); 3] = [("0-5%", 0.05), ("5-10%", 0.10), (">10%", f64::INFINITY)];
+
+/// Group validators into commission bands (0%, up to 5%, up to 10%, above 10%) with the count
+/// and total stake of each. Every band is reported, empty ones included, in increasing order.
+pub fn commission_bands(validators: &[Validator], chain: Chain) -> Vec<CommissionBandOutput> {
+    let mut bands: Vec<(&'static str, Balance, Vec<String>)> = std::iter::once("0%")
+        .chain(COMMISSION_BANDS.iter().map(|(band, _)| *band))
+        .map(|band| (band, 0, Vec::new()))
+        .collect();
+    for validator in validators {
+        let index = if validator.commission <= 0.0 {
+            0
+        } else {
+            1 + COMMISSION_BANDS.iter().position(|(_, up_to)| validator.commission <= *up_to).unwrap_or(COMMISSION_BANDS.len() - 1)
+        };
+        let band = &mut bands[index];
+        band.1 += validator.total_stake;
+        band.2.push(validator.stash.clone());
+    }
+    bands.into_iter().map(|(band, total_stake, validators)| CommissionBandOutput {
+        band,
+        validator_count: validators.len(),
+        total_stake: chain.format_stake(total_stake),
+        validators,
+    }).collect()
+}
+
+/// Jaccard overlap of the backer sets of the `top_k` best-backed elected validators, for every
+/// pair sharing at least one nominator, most similar pairs first. Self votes are not counted.
+pub fn nominator_overlap(validators: &[Validator], top_k: usize, precision: u32) -> Vec<ValidatorOverlap> {
+    let mut top: Vec<&Validator> = validators.iter().collect();
+    top.sort_by_key(|v| std::cmp::Reverse(v.total_stake));
+    top.truncate(top_k);
+    let backers: Vec<HashSet<&str>> = top.iter()
+        .map(|v| v.nominations.iter().map(|n| n.nominator.as_str()).collect())
+        .collect();
+
+    let mut overlaps = Vec::new();
+    for a in 0..top.len() {
+        for b in a + 1..top.len() {
+            let shared = backers[a].intersection(&backers[b]).count();
+            if shared == 0 {
+                continue;
+            }
+            let union = backers[a].len() + backers[b].len() - shared;
+            overlaps.push(ValidatorOverlap {
+                validator_a: top[a].stash.clone(),
+                validator_b: top[b].stash.clone(),
+                shared_nominators: shared,
+                jaccard: round_float(shared as f64 / union as f64, precision),
+            });
+        }
+    }
+    overlaps.sort_by(|x, y| y.jaccard.total_cmp(&x.jaccard).then(y.shared_nominators.cmp(&x.shared_nominators)));
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_block_state_client::test_storage::MockDummyStorage;
+    use sp_core::crypto::Ss58Codec;
+    use mockall::predicate::*;
+    use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
+    use crate::models::{AssignmentEdge, RunParameters, StakingStats, ValidatorNomination, VoterAssignment};
+    use crate::multi_block_state_client::{ActiveEraInfo, MockChainClientTrait, MockMultiBlockClientTrait, PagedExposureMetadata};
+    use crate::raw_state_client::{MockRawClientTrait, MockRpcClient};
+
+    fn validator(stash: &str) -> Validator {
+        Validator {
+            stash: stash.to_string(),
+            self_stake: 0,
+            total_stake: 100,
+            commission: 0.0,
+            commission_parts: 0,
+            blocked: false,
+            nominations_count: 0,
+            nominations: vec![],
+        }
+    }
+
+    fn queued(stash: &str) -> QueuedValidator {
+        QueuedValidator { stash: stash.to_string(), total_stake: 100, own_stake: 0 }
+    }
+
+    fn simulation(iterations: usize, elected: &[&str]) -> SimulationResult {
+        SimulationResult {
+            run_parameters: RunParameters { iterations, max_nominations: 16, desired_validators: 2, ..RunParameters::default() },
+            staking_stats: StakingStats { total_staked: 200, lowest_staked: 100, avg_staked: 100, total_self_stake: 0, total_nominated_stake: 200, ..StakingStats::default() },
+            active_validators: elected.iter().map(|stash| validator(stash)).collect(),
+            score: ElectionScore { minimal_stake: 100, sum_stake: 200, sum_stake_squared: iterations as u128 },
+            ..SimulationResult::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_with_queued() {
+        let result = simulation(0, &["a", "b"]);
+        let comparison = compare_with_queued(&result, 11, &[queued("b"), queued("c")], 0.0, 4);
+        assert_eq!(comparison.era, 11);
+        assert_eq!(comparison.simulated_count, 2);
+        assert_eq!(comparison.queued_count, 2);
+        assert_eq!(comparison.matching_count, 1);
+        assert_eq!(comparison.only_simulated, vec!["a".to_string()]);
+        assert_eq!(comparison.only_queued, vec!["c".to_string()]);
+        assert_eq!(comparison.within_tolerance_count, 1);
+        assert_eq!(comparison.beyond_tolerance_count, 0);
+    }
+
+    #[test]
+    fn test_compare_with_queued_tolerance() {
+        let result = simulation(0, &["a", "b", "c"]);
+        let queued_with_stake = |stash: &str, total_stake: Balance| QueuedValidator { total_stake, ..queued(stash) };
+        let queued = [queued_with_stake("a", 100), queued_with_stake("b", 101), queued_with_stake("c", 120)];
+
+        let comparison = compare_with_queued(&result, 11, &queued, 1.0, 4);
+        assert_eq!(comparison.matching_count, 3);
+        assert_eq!(comparison.within_tolerance_count, 2);
+        assert_eq!(comparison.beyond_tolerance_count, 1);
+        assert_eq!(comparison.beyond_tolerance, vec![StakeMismatch {
+            stash: "c".to_string(),
+            simulated_stake: 100,
+            queued_stake: 120,
+            difference_percent: 16.6667,
+        }]);
+
+        let strict = compare_with_queued(&result, 11, &queued, 0.0, 4);
+        let stashes: Vec<&str> = strict.beyond_tolerance.iter().map(|m| m.stash.as_str()).collect();
+        assert_eq!(stashes, vec!["c", "b"]);
+        assert_eq!(strict.within_tolerance_count, 1);
+    }
+
+    #[test]
+    fn test_score_comparison() {
+        let simulated = ElectionScore { minimal_stake: 100, sum_stake: 200, sum_stake_squared: 300 };
+        let queued = |minimal_stake, sum_stake, sum_stake_squared| Some(NposElectionScore { minimal_stake, sum_stake, sum_stake_squared });
+
+        assert_eq!(score_comparison(3, simulated, queued(90, 500, 1)).outcome, ScoreOutcome::SimulatedBetter);
+        assert_eq!(score_comparison(3, simulated, queued(100, 201, 300)).outcome, ScoreOutcome::QueuedBetter);
+        // A lower sum of stake squared wins once the other fields tie
+        assert_eq!(score_comparison(3, simulated, queued(100, 200, 299)).outcome, ScoreOutcome::QueuedBetter);
+        assert_eq!(score_comparison(3, simulated, queued(100, 200, 301)).outcome, ScoreOutcome::SimulatedBetter);
+        assert_eq!(score_comparison(3, simulated, queued(100, 200, 300)).outcome, ScoreOutcome::Equal);
+
+        let comparison = score_comparison(3, simulated, None);
+        assert_eq!(comparison, ScoreComparison { round: 3, simulated_score: simulated, queued_score: None, outcome: ScoreOutcome::NothingQueued });
+    }
+
+    #[tokio::test]
+    async fn test_compare_with_queued_score() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_round().returning(|_| Ok(4));
+        mock_client.expect_get_queued_solution_score()
+            .with(always(), eq(4))
+            .returning(|_, _| Ok(Some(NposElectionScore { minimal_stake: 50, sum_stake: 200, sum_stake_squared: 300 })));
+
+        let simulated = ElectionScore { minimal_stake: 100, sum_stake: 200, sum_stake_squared: 300 };
+        let comparison = compare_with_queued_score(&mock_client, None, simulated).await.unwrap();
+        assert_eq!(comparison.round, 4);
+        assert_eq!(comparison.queued_score, Some(ElectionScore { minimal_stake: 50, sum_stake: 200, sum_stake_squared: 300 }));
+        assert_eq!(comparison.outcome, ScoreOutcome::SimulatedBetter);
+    }
+
+    #[test]
+    fn test_iteration_sweep() {
+        let results = vec![simulation(10, &["a", "b"]), simulation(20, &["a", "c"]), simulation(30, &["a", "c"])];
+        let sweep = iteration_sweep(&results).unwrap();
+        let summary: Vec<(usize, usize, u128)> = sweep.steps.iter()
+            .map(|step| (step.iterations, step.changed_validators, step.score.sum_stake_squared))
+            .collect();
+        assert_eq!(summary, vec![(10, 0, 10), (20, 1, 20), (30, 0, 30)]);
+        assert!(sweep.steps.iter().all(|step| step.elected_count == 2));
+        assert!(iteration_sweep(&[]).is_none());
+    }
+
+    #[test]
+    fn test_rank_submissions() {
+        let score = |minimal_stake: u128, sum_stake: u128| NposElectionScore { minimal_stake, sum_stake, sum_stake_squared: 0 };
+        let (a, b, c) = (AccountId::from([1u8; 32]), AccountId::from([2u8; 32]), AccountId::from([3u8; 32]));
+        let scores = vec![(a.clone(), score(10, 100)), (b.clone(), score(20, 50)), (c.clone(), score(10, 200))];
+
+        let ranked = rank_submissions(7, scores.clone(), Some(score(10, 150)));
+        assert_eq!(ranked.round, 7);
+        let order: Vec<(usize, String, Option<bool>)> = ranked.submissions.iter()
+            .map(|s| (s.rank, s.submitter.clone(), s.beats_queued))
+            .collect();
+        assert_eq!(order, vec![
+            (1, to_ss58(&b), Some(true)),
+            (2, to_ss58(&c), Some(true)),
+            (3, to_ss58(&a), Some(false)),
+        ]);
+        assert_eq!(ranked.submissions[0].score, ElectionScore { minimal_stake: 20, sum_stake: 50, sum_stake_squared: 0 });
+
+        let ranked = rank_submissions(7, scores, None);
+        assert!(ranked.queued_score.is_none());
+        assert!(ranked.submissions.iter().all(|s| s.beats_queued.is_none()));
+    }
+
+    #[test]
+    fn test_reduce_comparison() {
+        let mut without_reduce = simulation(10, &["a", "b", "c"]);
+        let mut with_reduce = simulation(10, &["a", "c", "d"]);
+        with_reduce.run_parameters.reduce = true;
+        with_reduce.score.sum_stake = 150;
+        let edge = |validator: &str| AssignmentEdge { validator: validator.to_string(), stake: 50 };
+        without_reduce.assignments = vec![VoterAssignment { voter: "x".to_string(), edges: vec![edge("a"), edge("b")] }];
+        with_reduce.assignments = vec![VoterAssignment { voter: "x".to_string(), edges: vec![edge("a")] }];
+
+        let comparison = reduce_comparison(&without_reduce, &with_reduce);
+        assert!(comparison.run_parameters.reduce);
+        assert_eq!(comparison.only_without_reduce, vec!["b".to_string()]);
+        assert_eq!(comparison.only_with_reduce, vec!["d".to_string()]);
+        assert_eq!(comparison.changed_validators, 1);
+        assert_eq!(comparison.score_difference, ScoreDifference { minimal_stake: 0, sum_stake: -50, sum_stake_squared: 0 });
+        assert_eq!((comparison.edges_without_reduce, comparison.edges_with_reduce, comparison.edge_difference), (2, 1, -1));
+        assert_eq!(signed_difference(0, u128::MAX), i128::MAX);
+    }
+
+    #[test]
+    fn test_nominator_overlap() {
+        let backed = |stash: &str, total_stake: u128, nominators: &[&str]| Validator {
+            total_stake,
+            nominations: nominators.iter()
+                .map(|n| ValidatorNomination { nominator: n.to_string(), stake: 10, pool_id: None })
+                .collect(),
+            ..validator(stash)
+        };
+        let validators = vec![
+            backed("a", 300, &["x", "y", "z"]),
+            backed("b", 200, &["x", "y"]),
+            backed("c", 100, &["z", "w"]),
+            backed("d", 50, &["x", "y"]),
+        ];
+
+        let overlaps = nominator_overlap(&validators, 3, 4);
+        let pairs: Vec<(&str, &str, usize, f64)> = overlaps.iter()
+            .map(|o| (o.validator_a.as_str(), o.validator_b.as_str(), o.shared_nominators, o.jaccard))
+            .collect();
+        // d is outside the top 3 and b/c share nobody
+        assert_eq!(pairs, vec![("a", "b", 2, 0.6667), ("a", "c", 1, 0.25)]);
+        assert!(nominator_overlap(&validators, 1, 4).is_empty());
+    }
+
+    #[test]
+    fn test_commission_bands() {
+        let with_commission = |stash: &str, commission: f64| Validator { commission, ..validator(stash) };
+        let validators = vec![
+            with_commission("a", 0.0),
+            with_commission("b", 0.01),
+            with_commission("c", 0.05),
+            with_commission("d", 0.051),
+            with_commission("e", 1.0),
+            with_commission("f", 0.0),
+        ];
+        let bands = commission_bands(&validators, Chain::Substrate);
+        let summary: Vec<(&str, usize, &str, Vec<&str>)> = bands.iter()
+            .map(|b| (b.band, b.validator_count, b.total_stake.as_str(), b.validators.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(summary, vec![
+            ("0%", 2, "200 Planck", vec!["a", "f"]),
+            ("0-5%", 2, "200 Planck", vec!["b", "c"]),
+            ("5-10%", 1, "100 Planck", vec!["d"]),
+            (">10%", 1, "100 Planck", vec!["e"]),
+        ]);
+        assert!(commission_bands(&[], Chain::Substrate).iter().all(|b| b.validator_count == 0));
+    }
+
+    #[tokio::test]
+    async fn test_get_queued_validators() {
+        let stash = AccountId::from([1u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_active_era().returning(|_| Ok(Some(ActiveEraInfo { index: 10, start: None })));
+        mock_client.expect_get_era_stakers_overview()
+            .with(always(), eq(11), eq(stash.clone()))
+            .returning(|_, _, _| Ok(Some(PagedExposureMetadata { total: 500, own: 50, nominator_count: 1, page_count: 1 })));
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let stashes = vec![stash.clone()];
+        raw_client.expect_get_era_validators()
+            .with(eq(11), eq(None))
+            .returning(move |_, _| Ok(stashes.clone()));
+
+        let (era, queued) = get_queued_validators(&mock_client, &raw_client, None).await.unwrap();
+        assert_eq!(era, 11);
+        assert_eq!(queued, vec![QueuedValidator {
+            stash: stash.to_ss58check(),
+            total_stake: 500,
+            own_stake: 50,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_get_queued_validators_not_planned() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_active_era().returning(|_| Ok(Some(ActiveEraInfo { index: 10, start: None })));
+        mock_client.expect_get_era_stakers_overview().never();
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client.expect_get_era_validators().returning(|_, _| Ok(vec![]));
+
+        let (era, queued) = get_queued_validators(&mock_client, &raw_client, None).await.unwrap();
+        assert_eq!(era, 11);
+        assert!(queued.is_empty());
+    }
+}
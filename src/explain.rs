@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::task_local;
+
+use crate::miner_config::to_ss58;
+use crate::models::{ExcludedRole, Exclusion, ExclusionReason};
+use crate::primitives::AccountId;
+
+// Task-local collector of dropped accounts, only present while running under `with_explain`
+task_local! {
+    static EXCLUSIONS: Arc<Mutex<Vec<Exclusion>>>;
+}
+
+/// Run `f` recording every account dropped by the snapshot and simulation filters.
+/// Outside of this scope `record` is a no-op, so explain mode costs nothing when disabled.
+pub async fn with_explain<F, R>(f: F) -> (R, Vec<Exclusion>)
+where
+    F: std::future::Future<Output = R>,
+{
+    let exclusions = Arc::new(Mutex::new(Vec::new()));
+    let result = EXCLUSIONS.scope(exclusions.clone(), f).await;
+    let exclusions = std::mem::take(&mut *exclusions.lock().unwrap());
+    (result, exclusions)
+}
+
+/// Record why an account was dropped from the election input
+pub fn record(account: &AccountId, role: ExcludedRole, reason: ExclusionReason, detail: Option<String>) {
+    let _ = EXCLUSIONS.try_with(|exclusions| {
+        exclusions.lock().unwrap().push(Exclusion {
+            account: to_ss58(account),
+            role,
+            reason,
+            detail,
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::crypto::Ss58Codec;
+
+    #[tokio::test]
+    async fn test_record_only_inside_scope() {
+        let account = AccountId::from([1u8; 32]);
+        record(&account, ExcludedRole::Voter, ExclusionReason::Suppressed, None);
+
+        let (value, exclusions) = with_explain(async {
+            record(&account, ExcludedRole::Candidate, ExclusionReason::ManuallyRemoved, None);
+            7
+        }).await;
+        assert_eq!(value, 7);
+        assert_eq!(exclusions, vec![Exclusion {
+            account: account.to_ss58check(),
+            role: ExcludedRole::Candidate,
+            reason: ExclusionReason::ManuallyRemoved,
+            detail: None,
+        }]);
+    }
+}
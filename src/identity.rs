@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use futures::stream::{self, StreamExt};
+use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use sp_core::H256;
+use tracing::{debug, warn};
+
+use crate::miner_config::to_ss58;
+use crate::multi_block_state_client::{ChainClientTrait, MultiBlockClientTrait, StorageTrait};
+use crate::primitives::{AccountId, Storage};
+
+// Identity reads in flight at once, so annotating thousands of nominators does not flood the node
+const IDENTITY_CONCURRENCY: usize = 64;
+
+/// Fail unless the runtime at `endpoint` has the Identity pallet. On Asset Hub identities live on
+/// the People chain, so without this every lookup would fail and leave all addresses unnamed.
+pub fn require_identity_pallet(pallet_names: &[String], endpoint: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if pallet_names.iter().any(|name| name == "Identity") {
+        return Ok(());
+    }
+    Err(format!(
+        "The runtime at {} has no Identity pallet; pass the People chain endpoint with --identity-endpoint to use --pretty-addresses",
+        endpoint
+    ).into())
+}
+
+/// Display names registered through the Identity pallet for `accounts`, read concurrently.
+/// Accounts without an identity of their own fall back to their parent's name followed by
+/// the sub-account name. Parents shared by several sub accounts are only read once.
+/// At most `IDENTITY_CONCURRENCY` reads run at once. Failed lookups leave the account unnamed.
+pub async fn display_names<
+    CC: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+>(
+    multi_block_state_client: &MBC,
+    block: Option<H256>,
+    accounts: Vec<AccountId>,
+) -> Result<HashMap<AccountId, String>, Box<dyn std::error::Error + Send + Sync>> {
+    let storage = multi_block_state_client.get_storage(block).await?;
+    let mut failures = 0;
+
+    let own_names = stream::iter(accounts).map(|account| {
+        let storage = &storage;
+        async move {
+            let name = multi_block_state_client.get_identity_display(storage, account.clone()).await;
+            (account, name)
+        }
+    }).buffer_unordered(IDENTITY_CONCURRENCY).collect::<Vec<_>>().await;
+
+    let mut names = HashMap::new();
+    let mut unnamed = Vec::new();
+    for (account, name) in own_names {
+        match name {
+            Ok(Some(name)) => { names.insert(account, name); }
+            Ok(None) => unnamed.push(account),
+            Err(e) => {
+                debug!("Identity lookup failed for {}: {}", to_ss58(&account), e);
+                failures += 1;
+            }
+        }
+    }
+
+    let supers = stream::iter(unnamed).map(|account| {
+        let storage = &storage;
+        async move {
+            let super_of = multi_block_state_client.get_super_of(storage, account.clone()).await;
+            (account, super_of)
+        }
+    }).buffer_unordered(IDENTITY_CONCURRENCY).collect::<Vec<_>>().await;
+    let mut subs = Vec::new();
+    for (account, super_of) in supers {
+        match super_of {
+            Ok(Some((parent, sub_name))) => subs.push((account, parent, sub_name.text())),
+            Ok(None) => {}
+            Err(e) => {
+                debug!("Super identity lookup failed for {}: {}", to_ss58(&account), e);
+                failures += 1;
+            }
+        }
+    }
+
+    let parents: HashSet<AccountId> = subs.iter()
+        .map(|(_, parent, _)| parent.clone())
+        .filter(|parent| !names.contains_key(parent))
+        .collect();
+    let parent_names = stream::iter(parents).map(|parent| {
+        let storage = &storage;
+        async move {
+            let name = multi_block_state_client.get_identity_display(storage, parent.clone()).await;
+            (parent, name)
+        }
+    }).buffer_unordered(IDENTITY_CONCURRENCY).collect::<Vec<_>>().await;
+    let mut parent_cache: HashMap<AccountId, String> = HashMap::new();
+    for (parent, name) in parent_names {
+        match name {
+            Ok(Some(name)) => { parent_cache.insert(parent, name); }
+            Ok(None) => {}
+            Err(e) => {
+                debug!("Identity lookup failed for {}: {}", to_ss58(&parent), e);
+                failures += 1;
+            }
+        }
+    }
+
+    for (account, parent, sub_name) in subs {
+        let Some(parent_name) = names.get(&parent).or_else(|| parent_cache.get(&parent)) else {
+            continue;
+        };
+        let name = match sub_name {
+            Some(sub_name) => format!("{}/{}", parent_name, sub_name),
+            None => parent_name.clone(),
+        };
+        names.insert(account, name);
+    }
+
+    if failures > 0 {
+        warn!("{} identity lookups failed, the affected addresses are shown without a name", failures);
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multi_block_state_client::test_storage::MockDummyStorage;
+    use mockall::predicate::*;
+    use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
+    use crate::multi_block_state_client::{IdentityData, MockChainClientTrait, MockMultiBlockClientTrait};
+
+    #[tokio::test]
+    async fn test_display_names() {
+        let named = AccountId::from([1u8; 32]);
+        let sub = AccountId::from([2u8; 32]);
+        let unnamed_sub = AccountId::from([3u8; 32]);
+        let anonymous = AccountId::from([4u8; 32]);
+        let parent = AccountId::from([5u8; 32]);
+
+        let mut client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        client.expect_get_identity_display().with(always(), eq(named.clone())).returning(|_, _| Ok(Some("Validator".to_string())));
+        client.expect_get_identity_display().with(always(), eq(parent.clone())).times(1).returning(|_, _| Ok(Some("Parent".to_string())));
+        client.expect_get_identity_display().returning(|_, _| Ok(None));
+        let parent_value = parent.clone();
+        client.expect_get_super_of().with(always(), eq(sub.clone()))
+            .returning(move |_, _| Ok(Some((parent_value.clone(), IdentityData::Raw(b"node-1".to_vec())))));
+        let parent_value = parent.clone();
+        client.expect_get_super_of().with(always(), eq(unnamed_sub.clone()))
+            .returning(move |_, _| Ok(Some((parent_value.clone(), IdentityData::None))));
+        client.expect_get_super_of().returning(|_, _| Ok(None));
+
+        let names = display_names(&client, None, vec![named.clone(), sub.clone(), unnamed_sub.clone(), anonymous.clone()]).await.unwrap();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[&named], "Validator");
+        assert_eq!(names[&sub], "Parent/node-1");
+        assert_eq!(names[&unnamed_sub], "Parent");
+        assert!(!names.contains_key(&anonymous));
+    }
+
+    #[test]
+    fn test_require_identity_pallet() {
+        let pallets = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        assert!(require_identity_pallet(&pallets(&["System", "Identity"]), "wss://people").is_ok());
+        let error = require_identity_pallet(&pallets(&["System", "Staking"]), "wss://asset-hub").unwrap_err();
+        assert!(error.to_string().starts_with("The runtime at wss://asset-hub has no Identity pallet"), "{}", error);
+    }
+}
@@ -1,18 +1,23 @@
-use tracing::info;
+use tracing::{info, warn};
 use sp_core::{H256};
 use clap::{Parser, Subcommand};
-use sp_core::crypto::set_default_ss58_version;
-use std::fs::File;
+use sp_core::crypto::{set_default_ss58_version, Ss58AddressFormat};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
 use std::io::Write;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use crate::api::routes::root;
 use crate::simulate::{SimulateService, SimulateServiceImpl};
 use crate::snapshot::{SnapshotService, SnapshotServiceImpl};
-use crate::models::{Chain, Algorithm};
-use crate::multi_block_state_client::{MultiBlockClient};
+use crate::models::{BlockTimestamp, Chain, Algorithm, NominatorSort, OutputFormat, OutputOptions, parse_ss58_prefix, parse_tolerance, ScaleFormat, Snapshot, SnapshotFormat, SnapshotOutput, to_msgpack, ValidatorSnapshotOutput, SPLIT_CONFIG_FILE, SPLIT_VALIDATORS_FILE, VersionInfo, VoterSample, DEFAULT_FLOAT_PRECISION, MAX_FLOAT_PRECISION};
+use crate::multi_block_state_client::{ChainClientTrait, MultiBlockClient};
 use crate::primitives::Storage;
-use crate::raw_state_client::RawClientTrait;
+use crate::raw_state_client::{RawClientTrait, RpcClient};
 use crate::subxt_client::Client;
+use crate::miner_config::{to_ss58, ChainContext};
+use sp_version::RuntimeVersion;
 
 mod raw_state_client;
 mod primitives;
@@ -23,6 +28,17 @@ mod api;
 mod subxt_client;
 mod multi_block_state_client;
 mod miner_config;
+mod compare;
+mod block_time;
+mod cache;
+mod calldata;
+mod check;
+mod explain;
+mod budget;
+mod identity;
+mod progress;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
 #[derive(Parser, Debug)]
 pub struct SimulateArgs {
@@ -30,6 +46,10 @@ pub struct SimulateArgs {
     #[arg(short, long, default_value = "latest")]
     pub block: String,
 
+    /// Use the block closest to this RFC 3339 time (e.g. 2025-01-31T12:00:00Z) instead of --block
+    #[arg(long, value_name = "RFC3339", conflicts_with = "block")]
+    pub at_time: Option<String>,
+
     /// Election algorithm to use (seq-phragmen or phragmms)
     #[arg(short, long, default_value = "seq-phragmen")]
     pub algorithm: Algorithm,
@@ -42,6 +62,14 @@ pub struct SimulateArgs {
     #[arg(long)]
     pub reduce: bool,
 
+    /// Run the election once per balancing iteration count (e.g. 10,20,30) and report the score and set changes of each run
+    #[arg(long, value_delimiter = ',', conflicts_with_all = ["iterations", "format", "compare_queued", "compare_score", "explain"])]
+    pub iteration_sweep: Vec<usize>,
+
+    /// Mine the snapshot both without and with reduce and report the validators, score and edge count that change
+    #[arg(long, conflicts_with_all = ["reduce", "iteration_sweep", "format", "compare_queued", "compare_score", "explain", "output_assignments", "emit_calldata", "raw_supports", "per_page_output", "unsigned_mode"])]
+    pub compare_reduce: bool,
+
     /// Desired number of validators to elect (optional, uses chain default if not specified)
     #[arg(long)]
     pub desired_validators: Option<u32>,
@@ -65,6 +93,162 @@ pub struct SimulateArgs {
     /// Manual override JSON file path for voters and candidates
     #[arg(short = 'm', long)]
     pub manual_override: Option<String>,
+
+    /// Remove a voter from the election (SS58 address, repeatable, combined with --manual-override)
+    #[arg(long = "exclude-voter")]
+    pub exclude_voters: Vec<String>,
+
+    /// Remove a candidate from the election (SS58 address, repeatable, combined with --manual-override)
+    #[arg(long = "exclude-candidate")]
+    pub exclude_candidates: Vec<String>,
+
+    /// Compare the simulated active set with the validators already queued for the next era
+    #[arg(long)]
+    pub compare_queued: bool,
+
+    /// Compare the score of the mined solution with the solution already queued on chain for the round
+    #[arg(long)]
+    pub compare_score: bool,
+
+    /// Percent by which a validator's simulated and queued stake may differ and still count as matching
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0, requires = "compare_queued", value_parser = parse_tolerance)]
+    pub tolerance: f64,
+
+    /// Include candidates that were not elected, with the stake that nominated them
+    #[arg(long)]
+    pub include_unelected: bool,
+
+    /// Include nominators whose bonded stake was not fully assigned to elected validators
+    #[arg(long)]
+    pub include_unused_stake: bool,
+
+    /// Include the nominators none of whose targets was elected, whose whole stake is wasted
+    #[arg(long)]
+    pub include_wasted_voters: bool,
+
+    /// Add a labelled breakdown of the election score, with each component in the native token where it applies
+    #[arg(long)]
+    pub explain_score: bool,
+
+    /// Annotate each elected validator with its on-chain identity display name
+    #[arg(long)]
+    pub pretty_addresses: bool,
+
+    /// With --pretty-addresses, also annotate the nominators backing each validator
+    #[arg(long, requires = "pretty_addresses")]
+    pub pretty_nominators: bool,
+
+    /// With --pretty-addresses, read identities at the latest block of this chain (e.g. the People chain) instead of the simulated one
+    #[arg(long, value_name = "URL", requires = "pretty_addresses")]
+    pub identity_endpoint: Option<String>,
+
+    /// Decimal places for commission and ratio fields in the output
+    #[arg(long, default_value_t = DEFAULT_FLOAT_PRECISION, value_parser = clap::value_parser!(u32).range(0..=MAX_FLOAT_PRECISION as i64))]
+    pub precision: u32,
+
+    /// Also output each validator's commission as raw Perbill parts
+    #[arg(long)]
+    pub raw_commission: bool,
+
+    /// Also output each validator's commission as integer basis points (0-10000)
+    #[arg(long)]
+    pub commission_bps: bool,
+
+    /// Keep only the K largest nominators in each validator's nominations list (nominations_count and total_stake still cover all backers)
+    #[arg(long, value_name = "K")]
+    pub top_backers: Option<usize>,
+
+    /// Hide nominations below this stake (in Planck) from each validator's nominations list (nominations_count and total_stake still cover them)
+    #[arg(long, value_name = "PLANCK")]
+    pub min_nomination_display: Option<u128>,
+
+    /// Add a nominator_overlap section with the Jaccard overlap of backer sets between the K best-backed elected validators
+    #[arg(long, value_name = "K")]
+    pub overlap_top: Option<usize>,
+
+    /// Output format: json (full result), csv (active validator table), commission-bands (validators grouped by commission) or msgpack (full result as MessagePack)
+    #[arg(long, default_value = "json")]
+    pub format: OutputFormat,
+
+    /// Emit minified JSON instead of pretty-printed
+    #[arg(long)]
+    pub compact: bool,
+
+    /// List every voter and candidate dropped before the election, with the filter that removed it
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Also write the per-voter assignment edges of the mined (and, with --reduce, reduced) solution to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "iteration_sweep")]
+    pub output_assignments: Option<String>,
+
+    /// Also write each elected validator's support (total and voter weights) exactly as the miner computed it, as integer strings, to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "iteration_sweep")]
+    pub raw_supports: Option<String>,
+
+    /// Also write the winners and supports of each solution page, before the pages are combined, to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "iteration_sweep")]
+    pub per_page_output: Option<String>,
+
+    /// Also write the exact snapshot the election was mined from (after filters, override and sampling) to this file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["iteration_sweep", "compare_reduce"])]
+    pub dump_snapshot: Option<String>,
+
+    /// Write hex call data for a signed submission of the mined solution (register + submit_page per page) to this file
+    #[arg(long, value_name = "PATH", conflicts_with_all = [
+        "iteration_sweep", "sample", "manual_override", "exclude_voters", "exclude_candidates", "desired_validators",
+        "max_nominations", "min_nominator_bond", "min_validator_bond", "exclude_chilled", "unsigned_mode", "max_duration",
+    ])]
+    pub emit_calldata: Option<String>,
+
+    /// Mine a random sample of N voters instead of all of them, for quick approximate runs
+    #[arg(long, value_name = "N")]
+    pub sample: Option<usize>,
+
+    /// Seed for --sample (random when omitted; always reported in run_parameters)
+    #[arg(long, requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// Also append the run, its active validators and their nominations to this SQLite database
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["iteration_sweep", "compare_reduce"])]
+    pub sqlite: Option<String>,
+
+    /// Also print a short summary (validator count, total stake, score) to stdout when writing to a file
+    #[arg(long)]
+    pub tee: bool,
+
+    /// Warn when the combined supports do not add up to the stake of the voters backing them
+    #[arg(long)]
+    pub verify_conservation: bool,
+
+    /// Read the paged snapshot stored under this election round instead of the round the chain reports
+    #[arg(long, conflicts_with = "emit_calldata")]
+    pub round: Option<u32>,
+
+    /// Also append one JSON line of scalar metrics (timestamp, block, stake, validator count, score) to this file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["iteration_sweep", "compare_reduce"])]
+    pub timeseries_append: Option<String>,
+
+    /// Mine like the unsigned-phase fallback miner: only the --miner-pages most significant snapshot pages, always reduced
+    #[arg(long)]
+    pub unsigned_mode: bool,
+
+    /// With --unsigned-mode, the runtime's MinerPages (not exposed in the metadata)
+    #[arg(long, value_name = "PAGES", default_value_t = 1, requires = "unsigned_mode", value_parser = clap::value_parser!(u32).range(1..))]
+    pub miner_pages: u32,
+
+    /// Drop snapshot candidates that chilled (left Staking::Validators) after the snapshot was taken
+    #[arg(long)]
+    pub exclude_chilled: bool,
+
+    /// Include the block's Timestamp::Now (unix millis and RFC 3339) in the JSON output
+    #[arg(long, conflicts_with_all = ["iteration_sweep", "compare_reduce"])]
+    pub block_timestamp: bool,
+
+    /// Cap the run at this many seconds: reading voters stops at three quarters of it and the ones gathered so far are mined, marking the output partial; mining still running at the cap fails the run
+    #[arg(long, value_name = "SECONDS", conflicts_with_all = ["iteration_sweep", "compare_reduce"])]
+    pub max_duration: Option<u64>,
 }
 
 #[derive(Parser, Debug)]
@@ -73,23 +257,147 @@ pub struct SnapshotArgs {
     #[arg(short, long, default_value = "latest")]
     pub block: String,
 
+    /// Use the block closest to this RFC 3339 time (e.g. 2025-01-31T12:00:00Z) instead of --block
+    #[arg(long, value_name = "RFC3339", conflicts_with = "block")]
+    pub at_time: Option<String>,
+
     /// Output file path (if not specified, prints to stdout)
     #[arg(short, long, default_value = "snapshot.json")]
     pub output: String,
+
+    /// Decimal places for commission fields in the output
+    #[arg(long, default_value_t = DEFAULT_FLOAT_PRECISION, value_parser = clap::value_parser!(u32).range(0..=MAX_FLOAT_PRECISION as i64))]
+    pub precision: u32,
+
+    /// Also output each validator's commission as integer basis points (0-10000)
+    #[arg(long)]
+    pub commission_bps: bool,
+
+    /// Order of the nominators in the output: stash (ascending) or stake (descending)
+    #[arg(long, default_value = "stash")]
+    pub sort_by: NominatorSort,
+
+    /// Emit minified JSON instead of pretty-printed
+    #[arg(long)]
+    pub compact: bool,
+
+    /// List every voter and candidate dropped while building the snapshot, with the filter that removed it
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Fail when the block has no election snapshot instead of reconstructing it from staking storage
+    #[arg(long)]
+    pub no_reconstruct: bool,
+
+    /// Only output the candidate validators and the staking config, skipping the nominators
+    #[arg(long, conflicts_with = "sort_by")]
+    pub validators_only: bool,
+
+    /// Log and drop accounts that fail to resolve (e.g. an undecodable ledger) instead of aborting the snapshot
+    #[arg(long)]
+    pub skip_errors: bool,
+
+    /// Emit the SCALE-encoded voter and target pages a miner takes, as hex in JSON or as a binary file
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["validators_only", "explain"])]
+    pub scale_output: Option<ScaleFormat>,
+
+    /// Read the paged snapshot stored under this election round instead of the round the chain reports
+    #[arg(long, conflicts_with = "no_reconstruct")]
+    pub round: Option<u32>,
+
+    /// Include the block's Timestamp::Now (unix millis and RFC 3339) in the output
+    #[arg(long, conflicts_with = "scale_output")]
+    pub block_timestamp: bool,
+
+    /// Treat --output as a directory and split the nominators across numbered files of at most N each
+    #[arg(long, value_name = "N", conflicts_with_all = ["validators_only", "scale_output"], value_parser = clap::value_parser!(u64).range(1..))]
+    pub nominators_per_file: Option<u64>,
+
+    /// Output format: json or msgpack (the same content as MessagePack)
+    #[arg(long, default_value = "json", conflicts_with_all = ["scale_output", "nominators_per_file"])]
+    pub format: SnapshotFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct BackersArgs {
+    /// Validator stash to look up (SS58 or 0x-prefixed hex)
+    pub stash: String,
+
+    /// Block with Snapshot (Signed or Unsigned phase) 
+    #[arg(short, long, default_value = "latest")]
+    pub block: String,
+
+    /// Output file path (if not specified, prints to stdout)
+    #[arg(short, long, default_value = "backers.json")]
+    pub output: String,
+
+    /// Emit minified JSON instead of pretty-printed
+    #[arg(long)]
+    pub compact: bool,
 }
 
+#[derive(Parser, Debug)]
+pub struct SubmissionsArgs {
+    /// Block in the signed phase of an election
+    #[arg(short, long, default_value = "latest")]
+    pub block: String,
+
+    /// Output file path (if not specified, prints to stdout)
+    #[arg(short, long, default_value = "submissions.json")]
+    pub output: String,
+
+    /// Emit minified JSON instead of pretty-printed
+    #[arg(long)]
+    pub compact: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct FindSnapshotsArgs {
+    /// First block number of the range
+    #[arg(long)]
+    pub from: u32,
+
+    /// Last block number of the range (inclusive)
+    #[arg(long)]
+    pub to: u32,
+
+    /// Only check every N-th block of the range
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub step: u32,
+
+    /// Output file path (if not specified, prints to stdout)
+    #[arg(short, long, default_value = "snapshots.json")]
+    pub output: String,
+
+    /// Emit minified JSON instead of pretty-printed
+    #[arg(long)]
+    pub compact: bool,
+}
+
+// Parsed once at startup, so the size of the simulate arguments does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 enum Action {
     /// Simulate the election using the specified algorithm (seq_phragmen or phragmms)
     Simulate(SimulateArgs),
     /// Retrieve actual snapshot containing validator candidates and their voters
     Snapshot(SnapshotArgs),
+    /// List the voters currently nominating a validator, without running an election
+    Backers(BackersArgs),
+    /// List the blocks of a range whose election phase has a snapshot, to find blocks worth simulating
+    FindSnapshots(FindSnapshotsArgs),
+    /// List the signed submissions queued for the current round with their claimed scores, best first
+    Submissions(SubmissionsArgs),
 
     /// Start REST API server
     Server {
         /// Server address to bind to
-        #[arg(short, long, default_value = "127.0.0.1:3000")]
+        #[arg(short, long, env = "BIND_ADDRESS", default_value = "127.0.0.1:3000")]
         address: String,
+
+        /// RPC endpoint of another chain to serve under its own path prefix (e.g. /kusama/simulate); repeatable
+        #[arg(long = "extra-endpoint", env = "EXTRA_RPC_ENDPOINTS", value_delimiter = ',')]
+        extra_endpoints: Vec<String>,
     },
 }
 
@@ -97,24 +405,192 @@ enum Action {
 #[command(version, about, long_about = None)]
 struct Args {
     /// RPC endpoint URL (must be aligned with the chain)
-    #[arg(short, long)]
+    #[arg(short, long, env = "RPC_ENDPOINT")]
     rpc_endpoint: String,
 
+    /// Chain of the endpoint, trusted over the runtime's spec_name, e.g. for forks (detected from the runtime if not specified)
+    #[arg(long, env = "CHAIN")]
+    chain: Option<Chain>,
+
+    /// SS58 prefix for the addresses in the output and the accounts given as input, for chains outside the known ones (detected from the chain if not specified)
+    #[arg(long, env = "SS58_PREFIX", value_parser = parse_ss58_prefix)]
+    ss58_prefix: Option<Ss58AddressFormat>,
+
+    /// Number of parallel websocket connections for the raw RPC reads (storage key listing, block hashes); typed storage reads such as snapshot pages and ledgers share a single connection
+    #[arg(long, env = "RPC_CONNECTIONS", default_value_t = 1)]
+    rpc_connections: usize,
+
+    /// Largest RPC response accepted on every RPC connection, in megabytes
+    #[arg(long, env = "MAX_RESPONSE_SIZE", default_value_t = subxt_client::DEFAULT_MAX_RESPONSE_SIZE_MB)]
+    max_response_size: u32,
+
+    /// User-Agent sent on the RPC connections so providers can identify the tool (default: tool name and version)
+    #[arg(long, env = "RPC_USER_AGENT", default_value = raw_state_client::DEFAULT_USER_AGENT)]
+    rpc_user_agent: String,
+
+    /// Reconnection attempts of the subxt RPC client before it gives up (0 fails on the first error)
+    #[arg(long, env = "RPC_RETRIES", default_value_t = subxt_client::DEFAULT_RETRY_ATTEMPTS)]
+    rpc_retries: usize,
+
+    /// Longest wait between two reconnection attempts, in seconds; the backoff starts at 500ms and doubles
+    #[arg(long, value_name = "SECONDS", env = "RPC_MAX_DELAY", default_value_t = subxt_client::DEFAULT_MAX_RETRY_DELAY_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+    rpc_max_delay: u64,
+
+    /// Name of the multi-block election pallet (default: detected from the runtime metadata)
+    #[arg(long, env = "ELECTION_PALLET")]
+    election_pallet: Option<String>,
+
+    /// Build and mine snapshots of this many pages instead of the chain's Pages, for debugging the paging (at most the chain's Pages)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pages: Option<u32>,
+
+    /// Fail when the chain does not provide one of the miner constants instead of using a default
+    #[arg(long, env = "STRICT_CONSTANTS")]
+    strict_constants: bool,
+
+    /// Log level: error, warn, info, debug or trace (default: info for CLI commands, debug for the server)
+    #[arg(long, env = "LOG_LEVEL")]
+    log_level: Option<tracing::Level>,
+
+    /// Only log warnings and errors, e.g. to pipe `--output -` without progress messages
+    #[arg(long, short = 'q', env = "QUIET", conflicts_with = "log_level")]
+    quiet: bool,
+
+    /// Validate the arguments, override file and addresses, then exit without connecting to the endpoint
+    #[arg(long)]
+    check: bool,
+
     #[command(subcommand)]
     action: Action,
 }
 
-fn write_output<T: serde::Serialize>(data: &T, file_path: String) -> Result<(), Box<dyn std::error::Error>> {
-    let json = serde_json::to_string_pretty(data)?;
+fn write_output<T: serde::Serialize>(data: &T, file_path: String, compact: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let json = if compact {
+        serde_json::to_string(data)?
+    } else {
+        serde_json::to_string_pretty(data)?
+    };
+    write_text(&json, file_path)
+}
+
+fn write_bytes(content: &[u8], file_path: String) -> Result<(), Box<dyn std::error::Error>> {
+    if file_path != "-" {
+        File::create(file_path)?.write_all(content)?;
+    } else {
+        std::io::stdout().write_all(content)?;
+    }
+    Ok(())
+}
+
+fn write_text(content: &str, file_path: String) -> Result<(), Box<dyn std::error::Error>> {
     if file_path != "-" {
         let mut file = File::create(file_path)?;
-        file.write_all(json.as_bytes())?;
+        file.write_all(content.as_bytes())?;
     } else {
-        println!("{}", json);
+        println!("{}", content);
+    }
+    Ok(())
+}
+
+// Snapshot split into `dir`: the validators, numbered nominator files and a config.json listing them
+fn write_split_snapshot(snapshot: &SnapshotOutput, dir: &str, nominators_per_file: usize, compact: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if dir == "-" {
+        return Err("--nominators-per-file writes to a directory, stdout is not supported".into());
+    }
+    std::fs::create_dir_all(dir)?;
+    let path = |name: &str| Path::new(dir).join(name).to_string_lossy().into_owned();
+    write_output(&snapshot.validators, path(SPLIT_VALIDATORS_FILE), compact)?;
+    let files = snapshot.nominator_files(nominators_per_file);
+    for (name, nominators) in &files {
+        write_output(nominators, path(name), compact)?;
     }
+    info!("Wrote {} nominators in {} files to {}", snapshot.nominators.len(), files.len(), dir);
+    let index = snapshot.split_index(files.into_iter().map(|(name, _)| name).collect());
+    write_output(&index, path(SPLIT_CONFIG_FILE), compact)
+}
+
+// Append `line` to the file, creating it on the first run
+fn append_line(line: &str, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = OpenOptions::new().create(true).append(true).open(file_path)?;
+    writeln!(file, "{}", line)?;
     Ok(())
 }
 
+// Block from --at-time when given, otherwise the --block hash ("latest" meaning None)
+async fn resolve_block<C: RpcClient + Send + Sync + 'static, R: RawClientTrait<C>>(block: &str, at_time: Option<&str>, raw_client: &R) -> Result<Option<H256>, Box<dyn std::error::Error>> {
+    if let Some(time) = at_time {
+        let target = block_time::parse_time(time).map_err(|e| e.to_string())?;
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_millis() as u64;
+        let hash = block_time::find_block_at_time(raw_client, target, now).await.map_err(|e| e.to_string())?;
+        return Ok(Some(hash));
+    }
+    if block == "latest" {
+        Ok(None)
+    } else {
+        Ok(Some(block.parse()?))
+    }
+}
+
+// Chain behind an endpoint, from the spec name of its runtime
+fn detect_chain(spec_name: &str) -> Result<Chain, Box<dyn std::error::Error>> {
+    match spec_name {
+        "polkadot" => Ok(Chain::Polkadot),
+        "kusama" => Ok(Chain::Kusama),
+        "substrate" => Ok(Chain::Substrate),
+        "statemint" => Ok(Chain::Polkadot),
+        "statemine" => Ok(Chain::Kusama),
+        _ => Err("Unsupported chain".into()),
+    }
+}
+
+// Chain given with --chain, which is trusted over the runtime's spec_name so forks and local
+// nodes with an unknown spec_name can be used; otherwise the chain detected from the spec_name
+fn resolve_chain(spec_name: &str, configured: Option<Chain>) -> Result<Chain, Box<dyn std::error::Error>> {
+    let Some(chain) = configured else {
+        return detect_chain(spec_name)
+            .map_err(|e| format!("{} (spec_name {}), pass --chain to use the endpoint anyway", e, spec_name).into());
+    };
+    match detect_chain(spec_name) {
+        Ok(detected) if detected != chain => warn!("Endpoint reports {:?} (spec_name {}), using {:?} as given by --chain", detected, spec_name, chain),
+        Ok(_) => {}
+        Err(_) => info!("Unknown spec_name {}, using {:?} as given by --chain", spec_name, chain),
+    }
+    Ok(chain)
+}
+
+// Versions reported by GET /version for a chain
+async fn server_version<C: RpcClient + Send + Sync + 'static, R: RawClientTrait<C>>(raw_client: &R, runtime_version: &RuntimeVersion) -> VersionInfo {
+    // A runtime without the metadata versions API still serves everything else
+    let metadata_version = match raw_client.get_metadata_versions(None).await {
+        Ok(versions) => versions.into_iter().max(),
+        Err(e) => {
+            warn!("Could not read the runtime metadata versions: {}", e);
+            None
+        }
+    };
+    VersionInfo {
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        spec_name: runtime_version.spec_name.to_string(),
+        spec_version: runtime_version.spec_version,
+        metadata_version,
+    }
+}
+
+// Server routes of one chain, backed by services built for its MinerConfig
+fn chain_router<C: RpcClient + Send + Sync + 'static, R: RawClientTrait<C> + Send + Sync + 'static>(
+    subxt_client: &Client,
+    raw_client: R,
+    context: ChainContext,
+    version: VersionInfo,
+) -> axum::Router {
+    let chain = context.chain;
+    with_miner_config!(chain, {
+        let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
+        let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), Arc::new(raw_client)));
+        let simulate_service = Arc::new(SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone()));
+        root::chain_routes(simulate_service, snapshot_service, context, version)
+    })
+}
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -122,35 +598,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Use INFO level for CLI commands, DEBUG level for server
     let args = Args::parse();
     
-    let log_level = if matches!(args.action, Action::Server { .. }) {
+    let log_level = args.log_level.unwrap_or(if args.quiet {
+        tracing::Level::WARN
+    } else if matches!(args.action, Action::Server { .. }) {
         tracing::Level::DEBUG
     } else {
         tracing::Level::INFO
-    };
+    });
     
+    // Logs go to stderr so they never mix with JSON printed to stdout
     tracing_subscriber::fmt()
         .with_max_level(log_level)
         .with_target(false)
+        .with_writer(std::io::stderr)
         .init();
 
-    let raw_client = raw_state_client::RawClient::new(&args.rpc_endpoint).await?;
-    let subxt_client = subxt_client::Client::new(&args.rpc_endpoint, None).await?;
+    if args.check {
+        check::check_args(&args).map_err(|e| e.to_string())?;
+        info!("Arguments are valid; not connecting to {}", args.rpc_endpoint);
+        return Ok(());
+    }
+
+    let raw_client = raw_state_client::RawClient::new(&args.rpc_endpoint, args.rpc_connections, args.max_response_size, &args.rpc_user_agent).await?;
+    let subxt_client = subxt_client::Client::new(&args.rpc_endpoint, args.rpc_retries, Duration::from_secs(args.rpc_max_delay), args.max_response_size, &args.rpc_user_agent).await?;
     
     let runtime_version = raw_client.get_runtime_version().await?;
-    let chain = match runtime_version.spec_name.to_string().as_str() {
-        "polkadot" => Chain::Polkadot,
-        "kusama" => Chain::Kusama,
-        "substrate" => Chain::Substrate,
-        "statemint" => Chain::Polkadot,
-        "statemine" => Chain::Kusama,
-        _ => return Err("Unsupported chain".into()),
-    };
+    let chain = resolve_chain(&runtime_version.spec_name, args.chain)?;
 
-    set_default_ss58_version(chain.ss58_address_format());
+    let ss58_format = args.ss58_prefix.unwrap_or(chain.ss58_address_format());
+    set_default_ss58_version(ss58_format);
 
     // Fetch all constants from chain API
-    let miner_constants = miner_config::fetch_constants(&subxt_client).await?;
-    info!("Fetched constants: pages={}, max_winners_per_page={}, max_backers_per_winner={}, voter_snapshot_per_block={}, target_snapshot_per_block={}, max_length={}",
+    let miner_constants = miner_config::fetch_constants(&subxt_client, args.election_pallet.as_deref(), args.strict_constants).await?
+        .with_pages(args.pages)?;
+    info!("Fetched constants: election_pallet={}, pages={}, max_winners_per_page={}, max_backers_per_winner={}, voter_snapshot_per_block={}, target_snapshot_per_block={}, max_length={}",
+        miner_constants.election_pallet,
         miner_constants.pages,
         miner_constants.max_winners_per_page,
         miner_constants.max_backers_per_winner,
@@ -164,11 +646,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     match args.action {
         Action::Simulate(simulate_args) => {
-            let block: Option<H256> = if simulate_args.block == "latest" {
-                None
+            if simulate_args.emit_calldata.is_some() && args.pages.is_some() {
+                return Err("--emit-calldata submits for the chain's Pages and cannot be combined with --pages".into());
+            }
+            let block = resolve_block(&simulate_args.block, simulate_args.at_time.as_deref(), &raw_client).await?;
+            // A stored run records the block it was mined at, so "latest" is pinned to a hash up front
+            #[cfg(feature = "sqlite")]
+            let sqlite_block = match simulate_args.sqlite {
+                Some(_) => {
+                    let number = raw_client.get_block_number(block).await.map_err(|e| e.to_string())?;
+                    let hash = match block {
+                        Some(hash) => hash,
+                        None => raw_client.get_block_hash(number).await.map_err(|e| e.to_string())?
+                            .ok_or_else(|| format!("Block {} not found", number))?,
+                    };
+                    Some((hash, number))
+                }
+                None => None,
+            };
+            #[cfg(feature = "sqlite")]
+            let block = sqlite_block.map(|(hash, _)| hash).or(block);
+            if simulate_args.emit_calldata.is_some() {
+                with_miner_config!(chain, {
+                    let multi_block_client = MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone());
+                    calldata::require_stored_snapshot(&multi_block_client, block).await
+                }).map_err(|e| e.to_string())?;
+            }
+            // Checked before mining so a missing Identity pallet does not cost a whole run
+            let identity_client = if simulate_args.pretty_addresses {
+                let (client, endpoint) = match simulate_args.identity_endpoint.as_deref() {
+                    Some(endpoint) => (subxt_client::Client::new(endpoint, args.rpc_retries, Duration::from_secs(args.rpc_max_delay), args.max_response_size, &args.rpc_user_agent).await?, endpoint),
+                    None => (subxt_client.clone(), args.rpc_endpoint.as_str()),
+                };
+                identity::require_identity_pallet(&client.pallet_names(), endpoint).map_err(|e| e.to_string())?;
+                Some(client)
             } else {
-                Some(simulate_args.block.parse().unwrap())
+                None
             };
+            // The simulated block only exists on the simulated chain
+            let identity_block = if simulate_args.identity_endpoint.is_some() { None } else { block };
 
             let output = simulate_args.output.clone();
             info!("Running election simulation with {:?} algorithm...", simulate_args.algorithm);
@@ -178,66 +694,365 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let max_nominations = simulate_args.max_nominations;
             miner_config::set_election_config(algorithm, iterations, max_nominations);
             let apply_reduce = simulate_args.reduce;
-            let manual_override = if let Some(path) = simulate_args.manual_override.clone() {
-                let file = std::fs::read(&path)
-                    .map_err(|e| format!("Failed to read manual override file '{}': {}", path, e))?;
-                let override_data: simulate::Override = serde_json::from_slice(&file)
-                    .map_err(|e| format!("Failed to parse manual override JSON: {}", e))?;
-                Some(override_data)
-            } else {
-                None
+            let manual_override = match simulate_args.manual_override.as_deref() {
+                Some(path) => Some(simulate::Override::load(path).map_err(|e| e.to_string())?),
+                None => None,
             };
+            let manual_override = simulate::Override::with_exclusions(
+                manual_override,
+                simulate_args.exclude_voters.clone(),
+                simulate_args.exclude_candidates.clone(),
+            );
             let min_nominator_bond = simulate_args.min_nominator_bond;
             let min_validator_bond = simulate_args.min_validator_bond;
+            let compare_queued = simulate_args.compare_queued;
+            let compare_score = simulate_args.compare_score;
+            let explain_mode = simulate_args.explain;
+            let sample = simulate_args.sample.map(|size| VoterSample {
+                size,
+                seed: simulate_args.seed.unwrap_or_else(rand::random),
+            });
+
+            if !simulate_args.iteration_sweep.is_empty() {
+                let iterations = simulate_args.iteration_sweep.clone();
+                let sweep_results = with_miner_config!(chain, {
+                    let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
+                    let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), Arc::new(raw_client)).with_round(simulate_args.round));
+                    let simulate_service = SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone())
+                        .with_verify_conservation(simulate_args.verify_conservation)
+                        .with_unsigned_pages(simulate_args.unsigned_mode.then_some(simulate_args.miner_pages))
+                        .with_exclude_chilled(simulate_args.exclude_chilled);
+                    simulate_service.simulate_sweep(block, iterations, desired_validators, apply_reduce, manual_override, min_nominator_bond, min_validator_bond, sample).await
+                });
+                let sweep_results = sweep_results.map_err(|e| format!("Error in iteration sweep -> {}", e))?;
+                let sweep = compare::iteration_sweep(&sweep_results).ok_or("Iteration sweep produced no results")?;
+                for step in &sweep.steps {
+                    info!("{} iterations: score {:?}, {} validators changed", step.iterations, step.score, step.changed_validators);
+                }
+                write_output(&sweep, output, simulate_args.compact)?;
+                return Ok(());
+            }
+            if simulate_args.compare_reduce {
+                let runs = with_miner_config!(chain, {
+                    let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
+                    let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), Arc::new(raw_client)).with_round(simulate_args.round));
+                    let simulate_service = SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone())
+                        .with_verify_conservation(simulate_args.verify_conservation)
+                        .with_unsigned_pages(simulate_args.unsigned_mode.then_some(simulate_args.miner_pages))
+                        .with_exclude_chilled(simulate_args.exclude_chilled);
+                    simulate_service.simulate_reduce_pair(block, desired_validators, manual_override, min_nominator_bond, min_validator_bond, sample).await
+                });
+                let (without_reduce, with_reduce) = runs.map_err(|e| format!("Error in reduce comparison -> {}", e))?;
+                let comparison = compare::reduce_comparison(&without_reduce, &with_reduce);
+                info!(
+                    "Reduce changed {} validators and {} edges ({} -> {}), score difference {:?}",
+                    comparison.changed_validators, comparison.edge_difference, comparison.edges_without_reduce,
+                    comparison.edges_with_reduce, comparison.score_difference
+                );
+                write_output(&comparison, output, simulate_args.compact)?;
+                return Ok(());
+            }
+
+            // Read before the election takes the raw client
+            let block_time = if simulate_args.timeseries_append.is_some() || simulate_args.block_timestamp {
+                let number = raw_client.get_block_number(block).await.map_err(|e| e.to_string())?;
+                let timestamp = raw_client.get_timestamp(block).await.map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("Timestamp::Now not found at block {}", number))?;
+                Some((number, timestamp))
+            } else {
+                None
+            };
             
+            let dump_snapshot = simulate_args.dump_snapshot.clone().map(|path| {
+                let snapshot_options = OutputOptions { precision: simulate_args.precision, ..OutputOptions::default() };
+                let compact = simulate_args.compact;
+                Box::new(move |snapshot: &Snapshot| -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                    write_output(&snapshot.to_output(chain, &snapshot_options), path.clone(), compact).map_err(|e| e.to_string())?;
+                    info!("Wrote the election input snapshot to {}", path);
+                    Ok(())
+                }) as simulate::SnapshotSink
+            });
             let election_result = with_miner_config!(chain, {
                 let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
                 let raw_client_arc = Arc::new(raw_client);             
-                let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), raw_client_arc.clone()));
-                let simulate_service = SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone());               
+                let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), raw_client_arc.clone()).with_round(simulate_args.round));
+                let simulate_service = SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone())
+                    .with_verify_conservation(simulate_args.verify_conservation)
+                    .with_unsigned_pages(simulate_args.unsigned_mode.then_some(simulate_args.miner_pages))
+                    .with_exclude_chilled(simulate_args.exclude_chilled)
+                    .with_dump_snapshot(dump_snapshot);
                 
-                simulate_service.simulate(block, desired_validators, apply_reduce, manual_override, min_nominator_bond, min_validator_bond).await
+                let simulation = budget::with_max_duration(
+                    simulate_args.max_duration.map(Duration::from_secs),
+                    simulate_service.simulate(block, desired_validators, apply_reduce, manual_override, min_nominator_bond, min_validator_bond, sample),
+                );
+                let ((mut result, cuts), exclusions) = if explain_mode {
+                    let (result, exclusions) = explain::with_explain(simulation).await;
+                    (result, Some(exclusions))
+                } else {
+                    (simulation.await, None)
+                };
+                if compare_queued && let Ok(simulation) = result.as_mut() {
+                    match compare::get_queued_validators(multi_block_client.as_ref(), raw_client_arc.as_ref(), block).await {
+                        Ok((era, queued)) => {
+                            let comparison = compare::compare_with_queued(simulation, era, &queued, simulate_args.tolerance, simulate_args.precision);
+                            if comparison.beyond_tolerance_count > 0 {
+                                warn!("{} of {} matching validators differ in stake beyond {}%", comparison.beyond_tolerance_count, comparison.matching_count, comparison.tolerance_percent);
+                            }
+                            simulation.queued_comparison = Some(comparison);
+                        }
+                        Err(e) => result = Err(format!("Error fetching queued validators -> {}", e).into()),
+                    }
+                }
+                let mut score_comparison = None;
+                if compare_score && let Ok(simulation) = result.as_ref() {
+                    match compare::compare_with_queued_score(multi_block_client.as_ref(), block, simulation.score).await {
+                        Ok(comparison) => {
+                            info!("Round {} score comparison with the queued solution: {:?}", comparison.round, comparison.outcome);
+                            score_comparison = Some(comparison);
+                        }
+                        Err(e) => result = Err(format!("Error fetching queued solution score -> {}", e).into()),
+                    }
+                }
+                let mut display_names = None;
+                if let Some(identity_client) = identity_client.as_ref() && let Ok(simulation) = result.as_ref() {
+                    let mut addresses: HashSet<&str> = simulation.active_validators.iter().map(|v| v.stash.as_str()).collect();
+                    if simulate_args.pretty_nominators {
+                        addresses.extend(simulation.active_validators.iter()
+                            .flat_map(|v| v.nominations.iter())
+                            .map(|n| n.nominator.as_str()));
+                    }
+                    let accounts = addresses.into_iter()
+                        .map(simulate::parse_account)
+                        .collect::<Result<Vec<_>, _>>();
+                    match accounts {
+                        Ok(accounts) => match identity::display_names(&MultiBlockClient::<Client, MinerConfig, Storage>::new(identity_client.clone()), identity_block, accounts).await {
+                            Ok(names) => {
+                                info!("Found identity display names for {} addresses", names.len());
+                                display_names = Some(names.into_iter().map(|(account, name)| (to_ss58(&account), name)).collect::<HashMap<_, _>>());
+                            }
+                            Err(e) => result = Err(format!("Error fetching identities -> {}", e).into()),
+                        },
+                        Err(e) => result = Err(e),
+                    }
+                }
+                result.map(|result| (result, exclusions, score_comparison, display_names, cuts))
             });
             if election_result.is_err() {  
                 return Err(format!("Error in election simulation -> {}", election_result.err().unwrap()).into());
             }
-            let result = election_result.unwrap();
-            let output_result = result.to_output(chain);
-            write_output(&output_result, output)?;
+            let (result, exclusions, score_comparison, display_names, cuts) = election_result.unwrap();
+            #[cfg(feature = "sqlite")]
+            if let (Some(path), Some((block_hash, block_number))) = (simulate_args.sqlite.as_deref(), sqlite_block) {
+                let run_id = sqlite::write_simulation(path, chain, block_hash, block_number, &result)
+                    .map_err(|e| e.to_string())?;
+                info!("Stored run {} in {}", run_id, path);
+            }
+            if let (Some(path), Some((number, timestamp))) = (simulate_args.timeseries_append.as_deref(), block_time) {
+                append_line(&serde_json::to_string(&result.timeseries_point(chain, number, timestamp))?, path)?;
+                info!("Appended block {} metrics to {}", number, path);
+            }
+            if let Some(path) = simulate_args.emit_calldata.clone() {
+                let metadata = subxt_client.chain_api().metadata();
+                let (register, submit_page) = calldata::signed_submission_indices(&metadata).map_err(|e| e.to_string())?;
+                let submission = calldata::signed_submission(register, submit_page, &result.score, &result.solution_pages);
+                info!("Signed submission call data: register + {} pages", submission.submit_pages.len());
+                write_output(&submission, path, simulate_args.compact)?;
+            }
+            if let Some(path) = simulate_args.output_assignments.clone() {
+                write_output(&result.solution_assignments(), path, simulate_args.compact)?;
+            }
+            if let Some(path) = simulate_args.raw_supports.clone() {
+                write_output(&result.raw_supports(), path, simulate_args.compact)?;
+            }
+            if let Some(path) = simulate_args.per_page_output.clone() {
+                write_output(&result.paged_supports(), path, simulate_args.compact)?;
+            }
+            let output_options = OutputOptions {
+                precision: simulate_args.precision,
+                raw_commission: simulate_args.raw_commission,
+                commission_bps: simulate_args.commission_bps,
+                include_unelected: simulate_args.include_unelected,
+                include_unused_stake: simulate_args.include_unused_stake,
+                include_wasted_voters: simulate_args.include_wasted_voters,
+                top_backers: simulate_args.top_backers,
+                min_nomination_display: simulate_args.min_nomination_display.unwrap_or(0),
+                explain_score: simulate_args.explain_score,
+                ..OutputOptions::default()
+            };
+            let mut output_result = result.to_output(chain, &output_options);
+            output_result.exclusions = exclusions;
+            output_result.score_comparison = score_comparison;
+            output_result.partial = (!cuts.is_empty()).then_some(cuts);
+            if let Some(names) = display_names {
+                output_result.annotate_display_names(&names, simulate_args.pretty_nominators);
+            }
+            if simulate_args.block_timestamp {
+                output_result.block_timestamp = block_time.map(|(_, timestamp)| BlockTimestamp::from_millis(timestamp));
+            }
+            output_result.nominator_overlap = simulate_args.overlap_top
+                .map(|top_k| compare::nominator_overlap(&result.active_validators, top_k, simulate_args.precision));
+            match simulate_args.format {
+                OutputFormat::Json => write_output(&output_result, output.clone(), simulate_args.compact)?,
+                OutputFormat::Csv => write_text(&output_result.to_csv(), output.clone())?,
+                OutputFormat::CommissionBands => write_output(&compare::commission_bands(&result.active_validators, chain), output.clone(), simulate_args.compact)?,
+                OutputFormat::Msgpack => write_bytes(&to_msgpack(&output_result)?, output.clone())?,
+            }
+            if simulate_args.tee && output != "-" {
+                println!("{}", output_result.summary());
+            }
         }
         Action::Snapshot(snapshot_args) => {
-            let block: Option<H256> = if snapshot_args.block == "latest" {
-                None
+            let block = resolve_block(&snapshot_args.block, snapshot_args.at_time.as_deref(), &raw_client).await?;
+            // Read before the snapshot service takes the raw client
+            let block_timestamp = if snapshot_args.block_timestamp {
+                let timestamp = raw_client.get_timestamp(block).await.map_err(|e| e.to_string())?
+                    .ok_or("Timestamp::Now not found at the snapshot block")?;
+                Some(BlockTimestamp::from_millis(timestamp))
             } else {
-                Some(snapshot_args.block.parse().unwrap())
+                None
             };
 
             info!("Taking snapshot...");
+            if let Some(format) = snapshot_args.scale_output {
+                let encoded = with_miner_config!(chain, {
+                    let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
+                    let snapshot_service = SnapshotServiceImpl::new(multi_block_client.clone(), Arc::new(raw_client))
+                        .with_skip_errors(snapshot_args.skip_errors)
+                        .with_round(snapshot_args.round);
+                    if snapshot_args.no_reconstruct && let Err(e) = snapshot::require_snapshot(multi_block_client.as_ref(), block).await {
+                        Err(e)
+                    } else {
+                        snapshot::scale_snapshot(multi_block_client.as_ref(), &snapshot_service, block, snapshot_args.round).await
+                    }
+                }).map_err(|e| format!("Error generating snapshot -> {}", e))?;
+                info!("Encoded {} voter pages ({} bytes) and the target page ({} bytes) of round {}",
+                    encoded.pages, encoded.voter_pages.len(), encoded.targets.len(), encoded.round);
+                match format {
+                    ScaleFormat::Hex => write_output(&encoded.to_output(), snapshot_args.output, snapshot_args.compact)?,
+                    ScaleFormat::Binary => write_bytes(&encoded.to_bytes(), snapshot_args.output)?,
+                }
+                return Ok(());
+            }
             let snapshot = with_miner_config!(chain, {
-                let multi_block_client = MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone());
-                let snapshot_service = SnapshotServiceImpl::new(Arc::new(multi_block_client), Arc::new(raw_client));
-                snapshot_service.build(block).await
+                let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
+                let snapshot_service = SnapshotServiceImpl::new(multi_block_client.clone(), Arc::new(raw_client))
+                    .with_skip_errors(snapshot_args.skip_errors)
+                    .with_round(snapshot_args.round);
+                if snapshot_args.no_reconstruct && let Err(e) = snapshot::require_snapshot(multi_block_client.as_ref(), block).await {
+                    Err(e)
+                } else if snapshot_args.explain && snapshot_args.validators_only {
+                    let (snapshot, exclusions) = explain::with_explain(snapshot_service.build_validators(block)).await;
+                    snapshot.map(|snapshot| (snapshot, Some(exclusions)))
+                } else if snapshot_args.explain {
+                    let (snapshot, exclusions) = explain::with_explain(snapshot_service.build(block)).await;
+                    snapshot.map(|snapshot| (snapshot, Some(exclusions)))
+                } else if snapshot_args.validators_only {
+                    snapshot_service.build_validators(block).await.map(|snapshot| (snapshot, None))
+                } else {
+                    snapshot_service.build(block).await.map(|snapshot| (snapshot, None))
+                }
             });
             if snapshot.is_err() {
                 return Err(format!("Error generating snapshot -> {}", snapshot.err().unwrap()).into());
             }
-            let snapshot = snapshot.unwrap();
-            let output_snapshot = snapshot.to_output(chain);
-            write_output(&output_snapshot, snapshot_args.output)?;
+            let (snapshot, exclusions) = snapshot.unwrap();
+            if let Some(slots) = snapshot.config.nominator_slots {
+                match (slots.cap, slots.remaining) {
+                    (Some(cap), Some(remaining)) => info!("Nominators: {} of {} ({} slots remaining)", slots.count, cap, remaining),
+                    _ => info!("Nominators: {} (no cap)", slots.count),
+                }
+            }
+            let output_options = OutputOptions {
+                precision: snapshot_args.precision,
+                commission_bps: snapshot_args.commission_bps,
+                nominator_sort: snapshot_args.sort_by,
+                ..OutputOptions::default()
+            };
+            let mut output_snapshot = snapshot.to_output(chain, &output_options);
+            output_snapshot.exclusions = exclusions;
+            output_snapshot.block_timestamp = block_timestamp;
+            if snapshot_args.validators_only {
+                let output_validators = ValidatorSnapshotOutput {
+                    validators: output_snapshot.validators,
+                    config: output_snapshot.config,
+                    reconstruction: output_snapshot.reconstruction,
+                    block_timestamp: output_snapshot.block_timestamp,
+                    exclusions: output_snapshot.exclusions,
+                };
+                match snapshot_args.format {
+                    SnapshotFormat::Json => write_output(&output_validators, snapshot_args.output, snapshot_args.compact)?,
+                    SnapshotFormat::Msgpack => write_bytes(&to_msgpack(&output_validators)?, snapshot_args.output)?,
+                }
+            } else if let Some(per_file) = snapshot_args.nominators_per_file {
+                write_split_snapshot(&output_snapshot, &snapshot_args.output, per_file as usize, snapshot_args.compact)?;
+            } else {
+                match snapshot_args.format {
+                    SnapshotFormat::Json => write_output(&output_snapshot, snapshot_args.output, snapshot_args.compact)?,
+                    SnapshotFormat::Msgpack => write_bytes(&to_msgpack(&output_snapshot)?, snapshot_args.output)?,
+                }
+            }
+        }
+        Action::Backers(backers_args) => {
+            let block = resolve_block(&backers_args.block, None, &raw_client).await?;
+            let stash = simulate::parse_account(&backers_args.stash).map_err(|e| e.to_string())?;
+
+            info!("Looking up backers of {}...", backers_args.stash);
+            let backers = with_miner_config!(chain, {
+                let multi_block_client = MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone());
+                let snapshot_service = SnapshotServiceImpl::new(Arc::new(multi_block_client), Arc::new(raw_client));
+                snapshot_service.get_backers(block, stash).await
+            });
+            if backers.is_err() {
+                return Err(format!("Error getting backers -> {}", backers.err().unwrap()).into());
+            }
+            let output_backers = backers.unwrap().to_output(chain);
+            write_output(&output_backers, backers_args.output, backers_args.compact)?;
+        }
+        Action::FindSnapshots(find_args) => {
+            info!("Scanning blocks {} to {} every {} blocks...", find_args.from, find_args.to, find_args.step);
+            let blocks = with_miner_config!(chain, {
+                let multi_block_client = MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone());
+                snapshot::find_snapshots(&multi_block_client, &raw_client, find_args.from, find_args.to, find_args.step).await
+            });
+            let blocks = blocks.map_err(|e| format!("Error finding snapshots -> {}", e))?;
+            write_output(&blocks, find_args.output, find_args.compact)?;
         }
-        Action::Server { address } => {
+        Action::Submissions(submissions_args) => {
+            let block = resolve_block(&submissions_args.block, None, &raw_client).await?;
+            let submissions = with_miner_config!(chain, {
+                let multi_block_client = MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone());
+                compare::get_signed_submissions(&multi_block_client, block).await
+            });
+            let submissions = submissions.map_err(|e| format!("Error getting signed submissions -> {}", e))?;
+            info!("{} signed submissions queued for round {}", submissions.submissions.len(), submissions.round);
+            write_output(&submissions, submissions_args.output, submissions_args.compact)?;
+        }
+        Action::Server { address, extra_endpoints } => {
             info!("Starting server on {}", address);
             let listener = tokio::net::TcpListener::bind(address).await?;
-            with_miner_config!(chain, {
-                let multi_block_client = Arc::new(MultiBlockClient::<Client, MinerConfig, Storage>::new(subxt_client.clone()));
-                let raw_client_arc = Arc::new(raw_client);
-                let snapshot_service = Arc::new(SnapshotServiceImpl::new(multi_block_client.clone(), raw_client_arc.clone()));
-                let simulate_service = Arc::new(SimulateServiceImpl::new(multi_block_client.clone(), snapshot_service.clone()));
-                let router = root::routes(simulate_service, snapshot_service, chain);
-                axum::serve(listener, router)
-                    .await
-                    .unwrap_or_else(|e| panic!("Error starting server: {}", e));
-            });
+            let version = server_version(&raw_client, &runtime_version).await;
+            let context = ChainContext::new(chain, miner_constants).with_ss58_format(ss58_format);
+            let mut chains = vec![(chain, chain_router(&subxt_client, raw_client, context, version))];
+            for endpoint in extra_endpoints {
+                let raw_client = raw_state_client::RawClient::new(&endpoint, args.rpc_connections, args.max_response_size, &args.rpc_user_agent).await?;
+                let subxt_client = subxt_client::Client::new(&endpoint, args.rpc_retries, Duration::from_secs(args.rpc_max_delay), args.max_response_size, &args.rpc_user_agent).await?;
+                let runtime_version = raw_client.get_runtime_version().await?;
+                let extra_chain = detect_chain(&runtime_version.spec_name)
+                    .map_err(|e| format!("{} at {}", e, endpoint))?;
+                if chains.iter().any(|(served, _)| *served == extra_chain) {
+                    return Err(format!("{} reports {:?}, which is already served", endpoint, extra_chain).into());
+                }
+                let constants = miner_config::fetch_constants(&subxt_client, args.election_pallet.as_deref(), args.strict_constants).await?
+                    .with_pages(args.pages)
+                    .map_err(|e| format!("{} at {}", e, endpoint))?;
+                info!("Serving {:?} from {} under /{}", extra_chain, endpoint, extra_chain.name());
+                let version = server_version(&raw_client, &runtime_version).await;
+                chains.push((extra_chain, chain_router(&subxt_client, raw_client, ChainContext::new(extra_chain, constants), version)));
+            }
+            axum::serve(listener, root::routes(chains))
+                .await
+                .unwrap_or_else(|e| panic!("Error starting server: {}", e));
         }
     }
     Ok(())
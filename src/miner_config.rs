@@ -7,12 +7,17 @@ use frame_election_provider_support::{self, SequentialPhragmen, PhragMMS};
 use sp_runtime::{PerU16, Percent, Perbill};
 use serde::Deserialize;
 use parity_scale_codec::{Decode};
-use sp_npos_elections;
+use sp_core::crypto::{default_ss58_version, Ss58AddressFormat, Ss58Codec};
+use tracing::{info, warn};
 
 /// Constants fetched from chain
 #[derive(Debug, Clone)]
 pub struct MinerConstants {
+	// Name the runtime gives the multi-block election pallet
+	pub election_pallet: String,
 	pub pages: u32,
+	// Pages of the snapshots the chain stores; `pages` is lower when overridden with `with_pages`
+	pub chain_pages: u32,
 	pub max_winners_per_page: u32,
 	pub max_backers_per_winner: u32,
 	pub voter_snapshot_per_block: u32,
@@ -42,39 +47,85 @@ pub struct PerDispatchClass {
 	pub mandatory: u32,
 }
 
-/// Helper function to fetch constants from chain API
+// Constant read from the chain, or `default` when the runtime does not provide it. With `strict`
+// a missing constant is an error instead. Where each value came from is logged either way.
+async fn constant_or_default<C: ChainClientTrait, T: serde::de::DeserializeOwned + std::fmt::Debug + 'static>(
+	client: &C,
+	pallet: &str,
+	constant_name: &str,
+	default: T,
+	strict: bool,
+) -> Result<T, Box<dyn std::error::Error>> {
+	match client.fetch_constant::<T>(pallet, constant_name).await {
+		Ok(value) => {
+			info!("{}::{} = {:?} (chain)", pallet, constant_name, value);
+			Ok(value)
+		}
+		Err(e) if strict => Err(format!("{}::{} not provided by the chain: {}", pallet, constant_name, e).into()),
+		Err(e) => {
+			warn!("{}::{} not provided by the chain ({}), using default {:?}", pallet, constant_name, e, default);
+			Ok(default)
+		}
+	}
+}
+
+/// Names runtimes are known to give the multi-block election pallet, in the order they are looked up
+pub const ELECTION_PALLET_NAMES: [&str; 2] = ["MultiBlockElection", "ElectionProviderMultiBlock"];
+
+/// Pick the election pallet among the pallets of the runtime metadata: `configured` when given,
+/// otherwise the first of `ELECTION_PALLET_NAMES` the runtime has
+pub fn resolve_election_pallet(pallets: &[String], configured: Option<&str>) -> Result<String, String> {
+	let found = match configured {
+		Some(name) => pallets.iter().find(|pallet| pallet.as_str() == name),
+		None => ELECTION_PALLET_NAMES.iter()
+			.find_map(|name| pallets.iter().find(|pallet| pallet.as_str() == *name)),
+	};
+	match (found, configured) {
+		(Some(pallet), _) => Ok(pallet.clone()),
+		(None, Some(name)) => Err(format!(
+			"Election pallet {} not found in the runtime metadata, pallets found: {}",
+			name, pallets.join(", "),
+		)),
+		(None, None) => Err(format!(
+			"None of the election pallets {} found in the runtime metadata, pass the right one with --election-pallet. Pallets found: {}",
+			ELECTION_PALLET_NAMES.join(", "), pallets.join(", "),
+		)),
+	}
+}
+
+/// Helper function to fetch constants from chain API.
+/// The election pallet is `election_pallet` when given, or detected from the metadata.
+/// With `strict`, every constant must come from the chain instead of falling back to a default.
 pub async fn fetch_constants<C: ChainClientTrait>(
 	client: &C,
+	election_pallet: Option<&str>,
+	strict: bool,
 ) -> Result<MinerConstants, Box<dyn std::error::Error>> {
+	let election_pallet = resolve_election_pallet(&client.pallet_names(), election_pallet)?;
+	let verifier_pallet = format!("{}Verifier", election_pallet);
 	let pages = client
-		.fetch_constant::<u32>("MultiBlockElection", "Pages")
+		.fetch_constant::<u32>(&election_pallet, "Pages")
 		.await?;
-	let max_winners_per_page = client
-		.fetch_constant::<u32>("MultiBlockElectionVerifier", "MaxWinnersPerPage")
-		.await
-		.unwrap_or(256);
-	let max_backers_per_winner = client
-		.fetch_constant::<u32>("MultiBlockElectionVerifier", "MaxBackersPerWinner")
-		.await
-		.unwrap_or(u32::MAX);
-	let voter_snapshot_per_block = client
-		.fetch_constant::<u32>("MultiBlockElection", "VoterSnapshotPerBlock")
-		.await
-		.unwrap_or(100);
-	let target_snapshot_per_block = client
-		.fetch_constant::<u32>("MultiBlockElection", "TargetSnapshotPerBlock")
-		.await
-		.unwrap_or(100);
-
-	let block_length = client
-		.fetch_constant::<BlockLength>("System", "BlockLength")
-		.await
-		.unwrap_or(BlockLength { max: PerDispatchClass { normal: 1, operational: 2, mandatory: 3 } });
+	info!("{}::Pages = {} (chain)", election_pallet, pages);
+	let max_winners_per_page = constant_or_default(client, &verifier_pallet, "MaxWinnersPerPage", 256u32, strict).await?;
+	let max_backers_per_winner = constant_or_default(client, &verifier_pallet, "MaxBackersPerWinner", u32::MAX, strict).await?;
+	let voter_snapshot_per_block = constant_or_default(client, &election_pallet, "VoterSnapshotPerBlock", 100u32, strict).await?;
+	let target_snapshot_per_block = constant_or_default(client, &election_pallet, "TargetSnapshotPerBlock", 100u32, strict).await?;
+
+	let block_length = constant_or_default(
+		client,
+		"System",
+		"BlockLength",
+		BlockLength { max: PerDispatchClass { normal: 1, operational: 2, mandatory: 3 } },
+		strict,
+	).await?;
 
 	let max_length = Percent::from_percent(75) * block_length.total();
 
 	Ok(MinerConstants {
+		election_pallet,
 		pages,
+		chain_pages: pages,
 		max_winners_per_page,
 		max_backers_per_winner,
 		voter_snapshot_per_block,
@@ -83,8 +134,25 @@ pub async fn fetch_constants<C: ChainClientTrait>(
 	})
 }
 
+impl MinerConstants {
+	/// Build and mine snapshots of `pages` pages instead of the chain's, for debugging the paging.
+	/// Stored snapshots are then read from their most significant pages
+	pub fn with_pages(mut self, pages: Option<u32>) -> Result<Self, String> {
+		let Some(pages) = pages else {
+			return Ok(self);
+		};
+		if pages == 0 || pages > self.chain_pages {
+			return Err(format!("--pages must be between 1 and {}::Pages of the chain ({}), got {}", self.election_pallet, self.chain_pages, pages));
+		}
+		warn!("Using {} of the chain's {} election pages", pages, self.chain_pages);
+		self.pages = pages;
+		Ok(self)
+	}
+}
+
 // Runtime configuration holder - stores values fetched from chain
 use std::sync::{OnceLock, Mutex};
+use tokio::task::LocalKey;
 use tokio::task_local;
 
 static RUNTIME_CONFIG: OnceLock<MinerConstants> = OnceLock::new();
@@ -94,6 +162,43 @@ task_local! {
 	static MAX_VOTES_PER_VOTER: u32;
 }
 
+// Chain served by the current request when one server handles several chains; outside of a
+// `ChainContext::scope` the constants set at startup and the default SS58 version apply
+#[derive(Debug, Clone)]
+pub struct ChainContext {
+	pub chain: Chain,
+	pub constants: MinerConstants,
+	pub ss58_format: Ss58AddressFormat,
+}
+task_local! {
+	static CHAIN_CONTEXT: ChainContext;
+}
+
+impl ChainContext {
+	pub fn new(chain: Chain, constants: MinerConstants) -> Self {
+		ChainContext { chain, constants, ss58_format: chain.ss58_address_format() }
+	}
+
+	pub fn with_ss58_format(self, ss58_format: Ss58AddressFormat) -> Self {
+		ChainContext { ss58_format, ..self }
+	}
+
+	/// Run a future with the miner constants, default max votes and address format of this chain.
+	pub async fn scope<F, R>(self, f: F) -> R
+	where
+		F: std::future::Future<Output = R>,
+	{
+		CHAIN_CONTEXT.scope(self, f).await
+	}
+}
+
+/// SS58 address of an account in the format of the chain being served
+pub fn to_ss58(account: &AccountId) -> String {
+	let format = CHAIN_CONTEXT.try_with(|context| context.ss58_format)
+		.unwrap_or_else(|_| default_ss58_version());
+	account.to_ss58check_with_version(format)
+}
+
 // Global fallback for max_votes_per_voter - set at startup based on chain
 static MAX_VOTES_PER_VOTER_FALLBACK: Mutex<u32> = Mutex::new(16);
 
@@ -122,12 +227,21 @@ pub fn set_runtime_constants(constants: MinerConstants, chain: Chain) {
 
 /// Set max_votes_per_voter based on chain
 fn set_max_votes_per_voter(chain: Chain) {
-	let max_votes = match chain {
+	*MAX_VOTES_PER_VOTER_FALLBACK.lock().unwrap() = chain_max_votes_per_voter(chain);
+}
+
+fn chain_max_votes_per_voter(chain: Chain) -> u32 {
+	match chain {
 		Chain::Polkadot => 16,
 		Chain::Kusama => 24,
 		Chain::Substrate => 16,
-	};
-	*MAX_VOTES_PER_VOTER_FALLBACK.lock().unwrap() = max_votes;
+	}
+}
+
+// Default max_votes_per_voter of the chain being served, or the one set at startup
+fn default_max_votes_per_voter() -> u32 {
+	CHAIN_CONTEXT.try_with(|context| chain_max_votes_per_voter(context.chain))
+		.unwrap_or_else(|_| *MAX_VOTES_PER_VOTER_FALLBACK.lock().unwrap())
 }
 
 #[cfg(test)]
@@ -135,21 +249,33 @@ use std::sync::Once;
 #[cfg(test)]
 static INIT: Once = Once::new();
 
+#[cfg(test)]
+fn test_constants() -> MinerConstants {
+	MinerConstants {
+		election_pallet: ELECTION_PALLET_NAMES[0].to_string(),
+		pages: 1,
+		chain_pages: 1,
+		max_winners_per_page: 1,
+		max_backers_per_winner: 1,
+		voter_snapshot_per_block: 2,
+		target_snapshot_per_block: 2,
+		max_length: 100000000,
+	}
+}
+
 #[cfg(test)]
 pub fn initialize_runtime_constants() {
 	INIT.call_once(|| {
 		// Ignore error if constants are already set (e.g., by another test)
-		let _ = set_runtime_constants(MinerConstants {
-			pages: 1,
-			max_winners_per_page: 1,
-			max_backers_per_winner: 1,
-			voter_snapshot_per_block: 2,
-			target_snapshot_per_block: 2,
-			max_length: 100000000,
-		}, Chain::Polkadot);
+		set_runtime_constants(test_constants(), Chain::Polkadot);
 	});
 }
 
+#[cfg(test)]
+pub fn test_chain_context() -> ChainContext {
+	ChainContext::new(Chain::Polkadot, test_constants())
+}
+
 /// Set election algorithm, balancing iterations, and optional max_votes_per_voter override
 /// 
 /// Note: For concurrent API requests, use `with_election_config` instead
@@ -172,18 +298,77 @@ pub async fn with_election_config<F, R>(algorithm: Algorithm, iterations: usize,
 where
 	F: std::future::Future<Output = R>,
 {
-	let max_votes = max_votes_per_voter.unwrap_or_else(|| *MAX_VOTES_PER_VOTER_FALLBACK.lock().unwrap());
+	let max_votes = max_votes_per_voter.unwrap_or_else(default_max_votes_per_voter);
 	ELECTION_CONFIG.scope(ElectionConfig {
 		algorithm,
 		iterations,
 	}, MAX_VOTES_PER_VOTER.scope(max_votes, f)).await
 }
 
+/// Miner settings of the current task, to mine on a thread of its own with the same constants,
+/// algorithm and max votes
+pub struct MinerScope {
+	chain: Option<ChainContext>,
+	election: Option<ElectionConfig>,
+	max_votes: Option<u32>,
+}
+
+impl MinerScope {
+	pub fn current() -> Self {
+		MinerScope {
+			chain: CHAIN_CONTEXT.try_with(Clone::clone).ok(),
+			election: ELECTION_CONFIG.try_with(Clone::clone).ok(),
+			max_votes: MAX_VOTES_PER_VOTER.try_with(|max_votes| *max_votes).ok(),
+		}
+	}
+
+	/// Run the blocking `f` with the captured settings
+	pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
+		sync_scope(&CHAIN_CONTEXT, self.chain, || {
+			sync_scope(&ELECTION_CONFIG, self.election, || sync_scope(&MAX_VOTES_PER_VOTER, self.max_votes, f))
+		})
+	}
+}
+
+fn sync_scope<T: 'static, R>(key: &'static LocalKey<T>, value: Option<T>, f: impl FnOnce() -> R) -> R {
+	match value {
+		Some(value) => key.sync_scope(value, f),
+		None => f(),
+	}
+}
+
 /// Get the runtime miner constants
 pub fn get_runtime_constants() -> &'static MinerConstants {
 	RUNTIME_CONFIG.get().expect("Runtime constants not set - call set_runtime_constants first")
 }
 
+// Constant of the chain being served, falling back to the constants set at startup
+fn runtime_constant(constant: fn(&MinerConstants) -> u32) -> u32 {
+	CHAIN_CONTEXT.try_with(|context| constant(&context.constants))
+		.unwrap_or_else(|_| constant(get_runtime_constants()))
+}
+
+/// Pages of the snapshots stored by the chain, which may be more than `Pages` under `--pages`.
+/// 0 until the constants are fetched
+pub fn chain_pages() -> u32 {
+	CHAIN_CONTEXT.try_with(|context| context.constants.chain_pages)
+		.unwrap_or_else(|_| RUNTIME_CONFIG.get().map_or(0, |constants| constants.chain_pages))
+}
+
+/// Multi-block election pallet of the chain being served, falling back to the one detected at startup
+pub fn election_pallet() -> String {
+	CHAIN_CONTEXT.try_with(|context| context.constants.election_pallet.clone())
+		.unwrap_or_else(|_| RUNTIME_CONFIG.get()
+			.map(|constants| constants.election_pallet.clone())
+			.unwrap_or_else(|| ELECTION_PALLET_NAMES[0].to_string()))
+}
+
+/// Companion pallet of the election, e.g. `Verifier` or `Signed`, which runtimes name after the
+/// election pallet itself
+pub fn election_sub_pallet(suffix: &str) -> String {
+	format!("{}{}", election_pallet(), suffix)
+}
+
 // Simple type aliases for constants 
 pub struct Pages;
 pub struct MaxWinnersPerPage;
@@ -207,48 +392,51 @@ pub fn get_current_algorithm() -> Algorithm {
 // Implement Get for constants
 impl sp_core::Get<u32> for Pages {
 	fn get() -> u32 { 
-		get_runtime_constants().pages
+		runtime_constant(|constants| constants.pages)
 	}
 }
 
 impl sp_core::Get<u32> for MaxWinnersPerPage {
 	fn get() -> u32 { 
-		get_runtime_constants().max_winners_per_page
+		runtime_constant(|constants| constants.max_winners_per_page)
 	}
 }
 
 impl sp_core::Get<u32> for MaxBackersPerWinner {
 	fn get() -> u32 { 
-		get_runtime_constants().max_backers_per_winner
+		runtime_constant(|constants| constants.max_backers_per_winner)
 	}
 }
 
 impl sp_core::Get<u32> for VoterSnapshotPerBlock {
 	fn get() -> u32 { 
-		get_runtime_constants().voter_snapshot_per_block
+		runtime_constant(|constants| constants.voter_snapshot_per_block)
 	}
 }
 
 impl sp_core::Get<u32> for TargetSnapshotPerBlock {
 	fn get() -> u32 { 
-		get_runtime_constants().target_snapshot_per_block
+		runtime_constant(|constants| constants.target_snapshot_per_block)
 	}
 }
 
 impl sp_core::Get<u32> for MaxLength {
 	fn get() -> u32 { 
-		get_runtime_constants().max_length
+		runtime_constant(|constants| constants.max_length)
 	}
 }
 
 impl sp_core::Get<u32> for MaxVotesPerVoter {
 	fn get() -> u32 {
-		// Try task-local first (for API requests), fall back to global (for CLI)
+		// Try task-local first (for API requests), fall back to the chain default
 		MAX_VOTES_PER_VOTER.try_with(|v| *v)
-			.unwrap_or_else(|_| *MAX_VOTES_PER_VOTER_FALLBACK.lock().unwrap())
+			.unwrap_or_else(|_| default_max_votes_per_voter())
 	}
 }
 
+// Shared by both algorithms of DynamicSolver. Sequential Phragmén balances once on the final
+// result, while PhragMMS balances after every elected winner, so the iterations also shape
+// which candidates PhragMMS picks in later rounds
 impl sp_core::Get<Option<sp_npos_elections::BalancingConfig>> for BalancingIterations {
 	fn get() -> Option<sp_npos_elections::BalancingConfig> {
 		// Try task-local first (for API requests), fall back to global (for CLI)
@@ -309,6 +497,33 @@ impl frame_election_provider_support::NposSolver for DynamicSolver {
 	}
 }
 
+// Solution voter indices address a voter within its page and target indices the target snapshot,
+// so the largest page and the target count must fit the index types of the chain's solution
+pub fn check_solution_index_width<MC: multi_block::unsigned::miner::MinerConfig>(
+	largest_voter_page: usize,
+	targets: usize,
+) -> Result<(), String> {
+	use frame_election_provider_support::NposSolution;
+	type VoterIndex<MC> = <<MC as multi_block::unsigned::miner::MinerConfig>::Solution as NposSolution>::VoterIndex;
+	type TargetIndex<MC> = <<MC as multi_block::unsigned::miner::MinerConfig>::Solution as NposSolution>::TargetIndex;
+
+	if largest_voter_page > 0 && VoterIndex::<MC>::try_from(largest_voter_page - 1).is_err() {
+		return Err(format!(
+			"too many voters for {} solution type on this config: a page holds {} voters",
+			std::any::type_name::<VoterIndex<MC>>(),
+			largest_voter_page,
+		));
+	}
+	if targets > 0 && TargetIndex::<MC>::try_from(targets - 1).is_err() {
+		return Err(format!(
+			"too many targets for {} solution type on this config: the snapshot holds {} targets",
+			std::any::type_name::<TargetIndex<MC>>(),
+			targets,
+		));
+	}
+	Ok(())
+}
+
 pub mod polkadot {
 	use super::*;
 
@@ -360,7 +575,7 @@ pub mod kusama {
 	impl multi_block::unsigned::miner::MinerConfig for MinerConfig {
 		type AccountId = AccountId;
 		type Solution = NposSolution24;
-		type Solver = SequentialPhragmen<AccountId, Perbill, BalancingIterations>;
+		type Solver = DynamicSolver;
 		type Pages = Pages;
 		type MaxVotesPerVoter = MaxVotesPerVoter;
 		type MaxWinnersPerPage = MaxWinnersPerPage;
@@ -392,7 +607,7 @@ pub mod substrate {
     impl multi_block::unsigned::miner::MinerConfig for MinerConfig {
         type AccountId = AccountId;
         type Solution = NposSolution16;
-        type Solver = SequentialPhragmen<AccountId, Perbill, BalancingIterations>;
+        type Solver = DynamicSolver;
         type Pages = Pages;
         type MaxVotesPerVoter = MaxVotesPerVoter;
         type MaxWinnersPerPage = MaxWinnersPerPage;
@@ -405,6 +620,28 @@ pub mod substrate {
     }
 }
 
+/// Simple macro to select the appropriate MinerConfig based on chain
+/// Usage: with_miner_config!(chain, { code that uses MinerConfig })
+#[macro_export]
+macro_rules! with_miner_config {
+	($chain:expr, $code:block) => {
+		match $chain {
+			$crate::models::Chain::Polkadot => {
+				use $crate::miner_config::polkadot::MinerConfig;
+				$code
+			},
+			$crate::models::Chain::Kusama => {
+				use $crate::miner_config::kusama::MinerConfig;
+				$code
+			},
+            $crate::models::Chain::Substrate => {
+                use $crate::miner_config::substrate::MinerConfig;
+                $code
+            },
+		}
+	};
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -418,6 +655,7 @@ mod tests {
 	#[tokio::test]
 	async fn fetch_constants_test() {
 		let mut client = MockChainClientTrait::new();
+		client.expect_pallet_names().returning(|| vec!["System".to_string(), "MultiBlockElection".to_string()]);
 		client.expect_fetch_constant::<u32>()
 			.with(eq("MultiBlockElection"), eq("Pages"))
 			.returning(|_, _| Ok(1u32));
@@ -436,7 +674,7 @@ mod tests {
 		client.expect_fetch_constant::<BlockLength>()
 			.with(eq("System"), eq("BlockLength"))
 			.returning(|_, _| Ok(BlockLength { max: PerDispatchClass { normal: 1, operational: 2, mandatory: 3 } }));
-		let constants = fetch_constants(&client).await;
+		let constants = fetch_constants(&client, None, true).await;
 		assert!(constants.is_ok());
 		let constants = constants.unwrap();
 		assert_eq!(constants.pages, 1);
@@ -445,6 +683,53 @@ mod tests {
 		assert_eq!(constants.voter_snapshot_per_block, 1);
 		assert_eq!(constants.target_snapshot_per_block, 1);
 		assert_eq!(constants.max_length, 4);
+		assert_eq!(constants.election_pallet, "MultiBlockElection");
+	}
+
+	#[test]
+	fn resolve_election_pallet_test() {
+		let pallets = vec!["System".to_string(), "ElectionProviderMultiBlock".to_string(), "Staking".to_string()];
+		assert_eq!(resolve_election_pallet(&pallets, None).unwrap(), "ElectionProviderMultiBlock");
+		assert_eq!(resolve_election_pallet(&pallets, Some("ElectionProviderMultiBlock")).unwrap(), "ElectionProviderMultiBlock");
+
+		let err = resolve_election_pallet(&pallets, Some("MultiBlockElection")).unwrap_err();
+		assert_eq!(err, "Election pallet MultiBlockElection not found in the runtime metadata, pallets found: System, ElectionProviderMultiBlock, Staking");
+		let err = resolve_election_pallet(&pallets[..1], None).unwrap_err();
+		assert!(err.contains("MultiBlockElection, ElectionProviderMultiBlock"));
+		assert!(err.ends_with("Pallets found: System"));
+	}
+
+	#[tokio::test]
+	async fn fetch_constants_missing_test() {
+		let mut client = MockChainClientTrait::new();
+		client.expect_pallet_names().returning(|| vec!["MultiBlockElection".to_string()]);
+		client.expect_fetch_constant::<u32>()
+			.with(eq("MultiBlockElection"), eq("Pages"))
+			.returning(|_, _| Ok(4u32));
+		client.expect_fetch_constant::<u32>()
+			.with(eq("MultiBlockElectionVerifier"), eq("MaxWinnersPerPage"))
+			.returning(|_, _| Err("constant not found".into()));
+		client.expect_fetch_constant::<u32>()
+			.returning(|_, _| Ok(1u32));
+		client.expect_fetch_constant::<BlockLength>()
+			.returning(|_, _| Ok(BlockLength { max: PerDispatchClass { normal: 1, operational: 2, mandatory: 3 } }));
+
+		let constants = fetch_constants(&client, None, false).await.unwrap();
+		assert_eq!(constants.max_winners_per_page, 256);
+		let err = fetch_constants(&client, None, true).await.unwrap_err();
+		assert!(err.to_string().contains("MultiBlockElectionVerifier::MaxWinnersPerPage not provided by the chain"));
+	}
+
+	#[test]
+	fn test_check_solution_index_width() {
+		let voters = u16::MAX as usize + 2;
+		let err = check_solution_index_width::<substrate::MinerConfig>(voters, 10).unwrap_err();
+		assert!(err.contains("too many voters for u16 solution type"));
+		assert!(check_solution_index_width::<substrate::MinerConfig>(u16::MAX as usize + 1, 10).is_ok());
+		assert!(check_solution_index_width::<polkadot::MinerConfig>(voters, 10).is_ok());
+
+		let err = check_solution_index_width::<polkadot::MinerConfig>(10, u16::MAX as usize + 2).unwrap_err();
+		assert!(err.contains("too many targets for u16 solution type"));
 	}
 
 	#[test]
@@ -455,6 +740,23 @@ mod tests {
 		assert_eq!(bl.total(), 60);
 	}
 
+	#[tokio::test]
+	async fn test_with_pages() {
+		let constants = MinerConstants { pages: 32, chain_pages: 32, ..test_constants() };
+		assert_eq!(constants.clone().with_pages(None).unwrap().pages, 32);
+		assert!(constants.clone().with_pages(Some(0)).is_err());
+		assert_eq!(
+			constants.clone().with_pages(Some(33)).err().unwrap(),
+			"--pages must be between 1 and MultiBlockElection::Pages of the chain (32), got 33"
+		);
+
+		let constants = constants.with_pages(Some(4)).unwrap();
+		let (pages, chain_pages) = ChainContext::new(Chain::Polkadot, constants)
+			.scope(async { (Pages::get(), chain_pages()) })
+			.await;
+		assert_eq!((pages, chain_pages), (4, 32));
+	}
+
 	#[serial]
 	 fn test_set_election_config_and_get_current_algorithm() {
 		initialize_runtime_constants();
@@ -479,6 +781,44 @@ mod tests {
 		assert_eq!(alg.1, 20);
 	}
 
+	#[tokio::test]
+	#[serial]
+	async fn test_miner_scope_on_another_thread() {
+		initialize_runtime_constants();
+		let constants = MinerConstants { pages: 32, chain_pages: 32, ..test_constants() };
+		let scope = ChainContext::new(Chain::Kusama, constants)
+			.scope(with_election_config(Algorithm::Phragmms, 3, Some(20), async { MinerScope::current() }))
+			.await;
+		let settings = std::thread::spawn(move || scope.run(|| (Pages::get(), get_current_algorithm(), MaxVotesPerVoter::get())))
+			.join()
+			.unwrap();
+		assert_eq!(settings, (32, Algorithm::Phragmms, 20));
+	}
+
+	#[tokio::test]
+	#[serial]
+	async fn test_chain_context_scope() {
+		initialize_runtime_constants();
+		let constants = MinerConstants { pages: 32, chain_pages: 32, election_pallet: "ElectionProviderMultiBlock".to_string(), ..test_constants() };
+		let context = ChainContext::new(Chain::Kusama, constants);
+		let account = AccountId::from([1u8; 32]);
+		let (pages, max_votes, address, verifier) = context.scope(async {
+			(Pages::get(), MaxVotesPerVoter::get(), to_ss58(&account), election_sub_pallet("Verifier"))
+		}).await;
+		assert_eq!(pages, 32);
+		assert_eq!(max_votes, 24);
+		assert_eq!(address, account.to_ss58check_with_version(Ss58AddressFormat::custom(2)));
+		assert_eq!(verifier, "ElectionProviderMultiBlockVerifier");
+		// Outside of the scope the startup constants apply again
+		assert_eq!(Pages::get(), 1);
+		assert_eq!(election_pallet(), "MultiBlockElection");
+
+		// An explicit max nominations still wins over the chain default
+		let context = ChainContext::new(Chain::Kusama, test_constants());
+		let max_votes = context.scope(with_election_config(Algorithm::SeqPhragmen, 0, Some(20), async { MaxVotesPerVoter::get() })).await;
+		assert_eq!(max_votes, 20);
+	}
+
 	#[test]
 	fn test_dynamic_solver_seq_phragmen() {
 		initialize_runtime_constants();
@@ -506,26 +846,54 @@ mod tests {
 		// Restore default so other tests
 		set_election_config(Algorithm::SeqPhragmen, 0, Some(16));
 	}
-}
 
-/// Simple macro to select the appropriate MinerConfig based on chain
-/// Usage: with_miner_config!(chain, { code that uses MinerConfig })
-#[macro_export]
-macro_rules! with_miner_config {
-	($chain:expr, $code:block) => {
-		match $chain {
-			$crate::models::Chain::Polkadot => {
-				use $crate::miner_config::polkadot::MinerConfig;
-				$code
-			},
-			$crate::models::Chain::Kusama => {
-				use $crate::miner_config::kusama::MinerConfig;
-				$code
-			},
-            $crate::models::Chain::Substrate => {
-                use $crate::miner_config::substrate::MinerConfig;
-                $code
-            },
+	#[test]
+	#[serial]
+	fn test_dynamic_solver_phragmms_balancing_iterations() {
+		initialize_runtime_constants();
+		let a = AccountId::from([1u8; 32]);
+		let b = AccountId::from([2u8; 32]);
+		let c = AccountId::from([3u8; 32]);
+		let voters = vec![
+			(AccountId::from([4u8; 32]), 1_000_000u64, vec![a.clone(), b.clone()]),
+			(AccountId::from([5u8; 32]), 1_000_000u64, vec![a.clone()]),
+			(AccountId::from([6u8; 32]), 1u64, vec![c.clone()]),
+		];
+		let targets = vec![a.clone(), b.clone(), c.clone()];
+		let min_backing = |result: &frame_election_provider_support::ElectionResult<AccountId, Perbill>| {
+			result.winners.iter().map(|(_, stake)| *stake).min().unwrap()
+		};
+
+		// Without balancing the split voter keeps part of its stake on the first winner
+		set_election_config(Algorithm::Phragmms, 0, None);
+		let unbalanced = DynamicSolver::solve(2, targets.clone(), voters.clone()).unwrap();
+
+		set_election_config(Algorithm::Phragmms, 10, None);
+		let balanced = DynamicSolver::solve(2, targets, voters).unwrap();
+
+		let mut winners: Vec<AccountId> = balanced.winners.iter().map(|(who, _)| who.clone()).collect();
+		winners.sort();
+		assert_eq!(winners, vec![a, b]);
+		assert_ne!(unbalanced.assignments, balanced.assignments);
+		assert!(min_backing(&balanced) > min_backing(&unbalanced));
+
+		set_election_config(Algorithm::SeqPhragmen, 0, Some(16));
+	}
+
+	#[test]
+	fn test_all_chains_use_dynamic_solver() {
+		use std::any::TypeId;
+
+		fn solver_of<MC: multi_block::unsigned::miner::MinerConfig>() -> TypeId
+		where
+			MC::Solver: 'static,
+		{
+			TypeId::of::<MC::Solver>()
 		}
-	};
+
+		// --algorithm only takes effect on chains whose miner dispatches through DynamicSolver
+		assert_eq!(solver_of::<polkadot::MinerConfig>(), TypeId::of::<DynamicSolver>());
+		assert_eq!(solver_of::<kusama::MinerConfig>(), TypeId::of::<DynamicSolver>());
+		assert_eq!(solver_of::<substrate::MinerConfig>(), TypeId::of::<DynamicSolver>());
+	}
 }
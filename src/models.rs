@@ -1,22 +1,149 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use sp_core::crypto::{Ss58AddressFormat};
+use sp_npos_elections::ElectionScore as NposElectionScore;
 
-use crate::primitives::{Balance};
+use crate::primitives::{Balance, EraIndex};
 
-#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub enum Chain {
     Polkadot,  // SS58 version 0
     Kusama,    // SS58 version 2
     Substrate, // SS58 version 42
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, Deserialize, Serialize)]
 pub enum Algorithm {
+    #[default]
     SeqPhragmen,
     Phragmms,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    // Active validators grouped by commission band, with the count and total stake of each
+    #[serde(rename = "commission_bands")]
+    CommissionBands,
+    // The full result as MessagePack
+    Msgpack,
+}
+
+// Encoding of `snapshot --scale-output`: hex strings in JSON, or the raw bytes of both parts back to back
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ScaleFormat {
+    Hex,
+    Binary,
+}
+
+// Encoding of the regular snapshot output
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum SnapshotFormat {
+    Json,
+    Msgpack,
+}
+
+/// MessagePack encoding of an output, with structs as maps keyed by field name so it decodes
+/// without a schema like the JSON. u128 values are 16 byte big-endian binaries, since
+/// MessagePack integers stop at 64 bits.
+pub fn to_msgpack<T: Serialize>(data: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(data)
+}
+
+// Final ordering of the nominators in the snapshot output
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NominatorSort {
+    #[default]
+    Stash,
+    Stake,
+}
+
+// Decimal places kept for commission and ratio fields in the output
+pub const DEFAULT_FLOAT_PRECISION: u32 = 4;
+// Beyond 12 places the Perbill noise shows again and `10^precision` loses exactness in an f64
+pub const MAX_FLOAT_PRECISION: u32 = 12;
+
+// Presentation settings applied when converting results to their output form
+#[derive(Debug, Clone, Copy)]
+pub struct OutputOptions {
+    pub precision: u32,
+    pub raw_commission: bool,
+    // Add each validator's commission as integer basis points
+    pub commission_bps: bool,
+    pub include_unelected: bool,
+    pub include_unused_stake: bool,
+    pub include_wasted_voters: bool,
+    // Keep only the largest backers of each validator in its nominations list
+    pub top_backers: Option<usize>,
+    // Hide nominations below this stake from the nominations list, in Planck
+    pub min_nomination_display: Balance,
+    pub nominator_sort: NominatorSort,
+    // Add a labelled breakdown of the election score
+    pub explain_score: bool,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            precision: DEFAULT_FLOAT_PRECISION,
+            raw_commission: false,
+            commission_bps: false,
+            include_unelected: false,
+            include_unused_stake: false,
+            include_wasted_voters: false,
+            top_backers: None,
+            min_nomination_display: 0,
+            nominator_sort: NominatorSort::default(),
+            explain_score: false,
+        }
+    }
+}
+
+// Round to the given number of decimal places, removing the noise left by Perbill conversions
+pub fn round_float(value: f64, precision: u32) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
+// Commission in basis points (0-10000), truncated from its Perbill parts
+pub fn perbill_parts_to_basis_points(parts: u32) -> u32 {
+    parts / 100_000
+}
+
+// SS58 prefix given on the command line; prefixes are 14 bits and 46/47 are reserved
+pub fn parse_ss58_prefix(value: &str) -> Result<Ss58AddressFormat, String> {
+    let prefix: u16 = value.parse().map_err(|_| format!("invalid SS58 prefix {}", value))?;
+    let format = Ss58AddressFormat::custom(prefix);
+    if prefix > 16_383 || format.is_reserved() {
+        return Err(format!("SS58 prefix {} is reserved or out of range (0-16383)", prefix));
+    }
+    Ok(format)
+}
+
+// Stake tolerance in percent given on the command line
+pub fn parse_tolerance(value: &str) -> Result<f64, String> {
+    let tolerance: f64 = value.parse().map_err(|_| format!("invalid tolerance {}", value))?;
+    if !(0.0..=100.0).contains(&tolerance) {
+        return Err(format!("tolerance {} is out of range (0-100 percent)", tolerance));
+    }
+    Ok(tolerance)
+}
+
 impl Chain {
+    // Name used on the command line and as the server path prefix
+    pub fn name(&self) -> &'static str {
+        match self {
+            Chain::Polkadot => "polkadot",
+            Chain::Kusama => "kusama",
+            Chain::Substrate => "substrate",
+        }
+    }
+
     pub fn ss58_address_format(&self) -> Ss58AddressFormat {
         match self {
             Chain::Polkadot => Ss58AddressFormat::custom(0),
@@ -49,6 +176,7 @@ impl Chain {
 pub struct ValidatorNomination {
     pub nominator: String,
     pub stake: Balance,
+    pub pool_id: Option<u32>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -57,40 +185,105 @@ pub struct Validator {
     pub self_stake: Balance,
     pub total_stake: Balance,
     pub commission: f64,
+    // Perbill parts of `commission`, so the integer outputs never go through the float
+    pub commission_parts: u32,
     pub blocked: bool,
     pub nominations_count: usize,
     pub nominations: Vec<ValidatorNomination>,
 }
 
+impl Validator {
+    // Stake backing the validator from others per unit of its own, `None` without self-stake
+    pub fn nominator_to_self_ratio(&self) -> Option<f64> {
+        (self.self_stake > 0).then(|| self.total_stake.saturating_sub(self.self_stake) as f64 / self.self_stake as f64)
+    }
+}
+
 #[derive(Debug, Serialize, PartialEq)]
 pub struct ValidatorNominationOutput {
     pub nominator: String,
     pub stake: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
 pub struct ValidatorOutput {
     pub stash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
     pub self_stake: String,
     pub total_stake: String,
+    // null when the validator has no self-stake
+    pub nominator_to_self_ratio: Option<f64>,
     pub commission: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_perbill: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_bps: Option<u32>,
     pub blocked: bool,
     pub nominations_count: usize,
     pub nominations: Vec<ValidatorNominationOutput>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
 pub struct StakingConfig {
     pub desired_validators: u32,
     pub max_nominations: u32,
     pub min_nominator_bond: u128,
     pub min_validator_bond: u128,
+    // Staking::MinimumActiveStake, absent on runtimes that do not store it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_active_stake: Option<u128>,
+    // Only read for the snapshot command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nominator_slots: Option<NominatorSlots>,
+}
+
+// Staking::CounterForNominators against Staking::MaxNominatorsCount. `cap` and `remaining` are
+// null when the chain does not cap the number of nominators
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct NominatorSlots {
+    pub count: u32,
+    pub cap: Option<u32>,
+    pub remaining: Option<u32>,
+}
+
+impl NominatorSlots {
+    pub fn new(count: u32, cap: Option<u32>) -> Self {
+        NominatorSlots { count, cap, remaining: cap.map(|cap| cap.saturating_sub(count)) }
+    }
+}
+
+// What rebuilding the snapshot from staking storage had to cut or skip; all zero when the
+// chain's own snapshot was read without errors, and then left out of the output
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct ReconstructionCounts {
+    // Voters nominating more than MaxVotesPerVoter targets, kept with their first ones
+    pub truncated_voters: usize,
+    // Nominations cut from those voters
+    pub dropped_nominations: usize,
+    // Voters and validators left out by --skip-errors because they failed to resolve
+    pub skipped_accounts: usize,
+}
+
+impl ReconstructionCounts {
+    pub fn is_empty(&self) -> bool {
+        *self == ReconstructionCounts::default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct SnapshotValidator {
     pub stash: String,
     pub commission: f64,
+    // Perbill parts of `commission`, only read to derive `commission_bps`
+    #[serde(skip)]
+    pub commission_parts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_bps: Option<u32>,
     pub blocked: bool,
 }
 
@@ -99,6 +292,7 @@ pub struct SnapshotNominator {
     pub stash: String,
     pub stake: Balance,
     pub nominations: Vec<String>,
+    pub pool_id: Option<u32>,
 }
 
 #[derive(Debug, Serialize, PartialEq)]
@@ -106,6 +300,8 @@ pub struct SnapshotNominatorOutput {
     pub stash: String,
     pub stake: String,
     pub nominations: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_id: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -113,6 +309,7 @@ pub struct Snapshot {
     pub validators: Vec<SnapshotValidator>,
     pub nominators: Vec<SnapshotNominator>,
     pub config: StakingConfig,
+    pub reconstruction: ReconstructionCounts,
 }
 
 // Output snapshot with formatted stake strings
@@ -121,25 +318,220 @@ pub struct SnapshotOutput {
     pub validators: Vec<SnapshotValidator>,
     pub nominators: Vec<SnapshotNominatorOutput>,
     pub config: StakingConfig,
+    #[serde(skip_serializing_if = "ReconstructionCounts::is_empty")]
+    pub reconstruction: ReconstructionCounts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<BlockTimestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<Vec<Exclusion>>,
+}
+
+// `config.json` of a snapshot split by `--nominators-per-file`, listing the other files
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SplitSnapshotIndex<'a> {
+    pub config: &'a StakingConfig,
+    #[serde(skip_serializing_if = "ReconstructionCounts::is_empty")]
+    pub reconstruction: ReconstructionCounts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<&'a BlockTimestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<&'a Vec<Exclusion>>,
+    pub validators_file: &'static str,
+    pub nominators_count: usize,
+    pub nominator_files: Vec<String>,
+}
+
+pub const SPLIT_VALIDATORS_FILE: &str = "validators.json";
+pub const SPLIT_CONFIG_FILE: &str = "config.json";
+
+impl SnapshotOutput {
+    // Nominators in consecutive chunks of at most `per_file`, each with its numbered file name
+    pub fn nominator_files(&self, per_file: usize) -> Vec<(String, &[SnapshotNominatorOutput])> {
+        self.nominators.chunks(per_file.max(1))
+            .enumerate()
+            .map(|(index, chunk)| (format!("nominators-{:03}.json", index), chunk))
+            .collect()
+    }
+
+    pub fn split_index(&self, nominator_files: Vec<String>) -> SplitSnapshotIndex<'_> {
+        SplitSnapshotIndex {
+            config: &self.config,
+            reconstruction: self.reconstruction,
+            block_timestamp: self.block_timestamp.as_ref(),
+            exclusions: self.exclusions.as_ref(),
+            validators_file: SPLIT_VALIDATORS_FILE,
+            nominators_count: self.nominators.len(),
+            nominator_files,
+        }
+    }
+}
+
+// Output of `snapshot --validators-only`, which never reads the voters
+#[derive(Debug, Serialize)]
+pub struct ValidatorSnapshotOutput {
+    pub validators: Vec<SnapshotValidator>,
+    pub config: StakingConfig,
+    #[serde(skip_serializing_if = "ReconstructionCounts::is_empty")]
+    pub reconstruction: ReconstructionCounts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<BlockTimestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<Vec<Exclusion>>,
+}
+
+// Snapshot pages SCALE encoded for a miner: `AllVoterPagesOf` and the `TargetPageOf` page
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleSnapshot {
+    pub round: u32,
+    pub pages: u32,
+    pub voter_pages: Vec<u8>,
+    pub targets: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ScaleSnapshotOutput {
+    pub round: u32,
+    pub pages: u32,
+    pub voter_pages: String,
+    pub targets: String,
+}
+
+impl ScaleSnapshot {
+    pub fn to_output(&self) -> ScaleSnapshotOutput {
+        ScaleSnapshotOutput {
+            round: self.round,
+            pages: self.pages,
+            voter_pages: format!("0x{}", hex::encode(&self.voter_pages)),
+            targets: format!("0x{}", hex::encode(&self.targets)),
+        }
+    }
+
+    // Voter pages followed by the target page, decodable in one pass as `(AllVoterPagesOf, TargetPageOf)`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.voter_pages.as_slice(), self.targets.as_slice()].concat()
+    }
 }
 
 impl Snapshot {
-    pub fn to_output(&self, chain: Chain) -> SnapshotOutput {
+    pub fn to_output(&self, chain: Chain, options: &OutputOptions) -> SnapshotOutput {
+        let mut nominators: Vec<&SnapshotNominator> = self.nominators.iter().collect();
+        match options.nominator_sort {
+            NominatorSort::Stash => nominators.sort_by(|a, b| a.stash.cmp(&b.stash)),
+            NominatorSort::Stake => nominators.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.stash.cmp(&b.stash))),
+        }
         SnapshotOutput {
-            validators: self.validators.clone(),
-            nominators: self.nominators.iter().map(|n| {
+            validators: self.validators.iter().map(|v| {
+                SnapshotValidator {
+                    commission: round_float(v.commission, options.precision),
+                    commission_bps: options.commission_bps.then(|| perbill_parts_to_basis_points(v.commission_parts)),
+                    ..v.clone()
+                }
+            }).collect(),
+            nominators: nominators.into_iter().map(|n| {
                 SnapshotNominatorOutput {
                     stash: n.stash.clone(),
                     stake: chain.format_stake(n.stake),
                     nominations: n.nominations.clone(),
+                    pool_id: n.pool_id,
                 }
             }).collect(),
             config: self.config.clone(),
+            reconstruction: self.reconstruction,
+            block_timestamp: None,
+            exclusions: None,
+        }
+    }
+}
+
+// Versions reported by GET /version, read once when the server starts
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct VersionInfo {
+    pub tool_version: String,
+    pub spec_name: String,
+    pub spec_version: u32,
+    // Newest metadata version offered by the runtime
+    pub metadata_version: Option<u32>,
+}
+
+// Block whose election phase carries a snapshot, as listed by find-snapshots
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnapshotBlock {
+    pub number: u32,
+    pub hash: String,
+    pub phase: String,
+    pub round: u32,
+}
+
+// Active era at a block, used to correlate simulations with eras
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct EraInfo {
+    pub index: EraIndex,
+    // Milliseconds since the Unix epoch, unset until the first block of the era
+    pub start: Option<u64>,
+    // Session the era started at, from Staking::BondedEras
+    pub start_session_index: Option<u32>,
+}
+
+// Timestamp::Now of the block an output was computed at, for `--block-timestamp`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BlockTimestamp {
+    pub unix_millis: u64,
+    pub rfc3339: String,
+}
+
+impl BlockTimestamp {
+    pub fn from_millis(unix_millis: u64) -> Self {
+        let rfc3339 = i64::try_from(unix_millis).ok()
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .map_or_else(String::new, |time| time.to_rfc3339());
+        BlockTimestamp { unix_millis, rfc3339 }
+    }
+}
+
+impl EraInfo {
+    // Era start as RFC 3339 for logs, or "unknown start" before the era's first block sets it
+    pub fn start_description(&self) -> String {
+        self.start
+            .and_then(|start| i64::try_from(start).ok())
+            .and_then(DateTime::<Utc>::from_timestamp_millis)
+            .map_or_else(|| "unknown start".to_string(), |start| start.to_rfc3339())
+    }
+}
+
+// Voters currently nominating a single validator, largest stake first
+#[derive(Debug, PartialEq)]
+pub struct ValidatorBackers {
+    pub validator: String,
+    pub backers: Vec<ValidatorNomination>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ValidatorBackersOutput {
+    pub validator: String,
+    pub backers_count: usize,
+    pub total_stake: String,
+    pub backers: Vec<ValidatorNominationOutput>,
+}
+
+impl ValidatorBackers {
+    pub fn to_output(&self, chain: Chain) -> ValidatorBackersOutput {
+        ValidatorBackersOutput {
+            validator: self.validator.clone(),
+            backers_count: self.backers.len(),
+            total_stake: chain.format_stake(self.backers.iter().map(|b| b.stake).sum()),
+            backers: self.backers.iter().map(|b| {
+                ValidatorNominationOutput {
+                    nominator: b.nominator.clone(),
+                    stake: chain.format_stake(b.stake),
+                    pool_id: b.pool_id,
+                    display_name: None,
+                }
+            }).collect(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct RunParameters {
     pub algorithm: Algorithm,
     pub iterations: usize,
@@ -148,63 +540,773 @@ pub struct RunParameters {
     pub min_nominator_bond: u128,
     pub min_validator_bond: u128,
     pub desired_validators: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample: Option<VoterSample>,
+    // Most significant snapshot pages mined when reproducing the unsigned-phase miner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsigned_pages: Option<u32>,
 }
 
-#[derive(Debug)]
+// Random subset of the voters kept for a quick, approximate simulation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct VoterSample {
+    pub size: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Default)]
 pub struct SimulationResult {
     pub run_parameters: RunParameters,
     pub staking_stats: StakingStats,
     pub active_validators: Vec<Validator>,
+    pub solution_length: SolutionLength,
+    pub score: ElectionScore,
+    pub unelected_candidates: Vec<UnelectedCandidate>,
+    pub unused_stake: Vec<NominatorUnusedStake>,
+    pub wasted_voters: Vec<WastedVoter>,
+    pub queued_comparison: Option<QueuedComparison>,
+    pub assignments: Vec<VoterAssignment>,
+    pub supports: Vec<RawSupport>,
+    pub page_supports: Vec<PageSupports>,
+    pub solution_pages: Vec<EncodedSolutionPage>,
+    pub reconstruction: ReconstructionCounts,
+    // Conditions that make the result degenerate or surprising, also logged when detected
+    pub warnings: Vec<String>,
 }
 
-#[derive(Debug)]
+// SCALE-encoded solution of one snapshot page, as submitted on chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedSolutionPage {
+    pub page: u32,
+    pub solution: Vec<u8>,
+}
+
+// Stake a voter puts behind one validator in the mined solution, in Planck
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AssignmentEdge {
+    pub validator: String,
+    pub stake: Balance,
+}
+
+// Edges of one voter exactly as encoded in the mined (and possibly reduced) solution
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct VoterAssignment {
+    pub voter: String,
+    pub edges: Vec<AssignmentEdge>,
+}
+
+// Combined support of one winner exactly as the feasibility check computed it. Amounts are
+// integer strings in Planck so JSON readers keep full precision
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RawSupport {
+    pub validator: String,
+    pub total: String,
+    pub voters: Vec<(String, String)>,
+}
+
+// Election mined from a posted electorate by POST /elect, without any chain state
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SyntheticElection {
+    pub score: ElectionScore,
+    pub winners: Vec<RawSupport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RawSupports<'a> {
+    pub run_parameters: &'a RunParameters,
+    pub supports: &'a [RawSupport],
+}
+
+// Winners of one solution page and their supports from that page alone, before the pages are
+// combined into `supports`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PageSupports {
+    pub page: u32,
+    pub winner_count: usize,
+    pub winners: Vec<RawSupport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PagedSupports<'a> {
+    pub run_parameters: &'a RunParameters,
+    pub pages: &'a [PageSupports],
+}
+
+#[derive(Debug, Serialize)]
+pub struct SolutionAssignments<'a> {
+    pub run_parameters: &'a RunParameters,
+    pub voter_count: usize,
+    pub edge_count: usize,
+    pub assignments: &'a [VoterAssignment],
+}
+
+// Flat scalar metrics of one run, appended as a JSON line for time-series dashboards.
+// Balances stay plain numbers in Planck so they can be graphed without parsing
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TimeseriesPoint {
+    pub chain: &'static str,
+    // Timestamp::Now of the simulated block, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub block: u32,
+    pub total_stake: Balance,
+    pub validator_count: usize,
+    pub min_active_stake: Balance,
+    pub score_minimal_stake: u128,
+    pub score_sum_stake: u128,
+    pub score_sum_stake_squared: u128,
+}
+
+impl SimulationResult {
+    pub fn solution_assignments(&self) -> SolutionAssignments<'_> {
+        SolutionAssignments {
+            run_parameters: &self.run_parameters,
+            voter_count: self.assignments.len(),
+            edge_count: self.assignments.iter().map(|a| a.edges.len()).sum(),
+            assignments: &self.assignments,
+        }
+    }
+
+    pub fn raw_supports(&self) -> RawSupports<'_> {
+        RawSupports {
+            run_parameters: &self.run_parameters,
+            supports: &self.supports,
+        }
+    }
+
+    pub fn paged_supports(&self) -> PagedSupports<'_> {
+        PagedSupports {
+            run_parameters: &self.run_parameters,
+            pages: &self.page_supports,
+        }
+    }
+
+    pub fn timeseries_point(&self, chain: Chain, block: u32, timestamp: u64) -> TimeseriesPoint {
+        TimeseriesPoint {
+            chain: chain.name(),
+            timestamp,
+            block,
+            total_stake: self.staking_stats.total_staked,
+            validator_count: self.active_validators.len(),
+            min_active_stake: self.staking_stats.minimum_active_stake,
+            score_minimal_stake: self.score.minimal_stake,
+            score_sum_stake: self.score.sum_stake,
+            score_sum_stake_squared: self.score.sum_stake_squared,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExcludedRole {
+    Voter,
+    Candidate,
+}
+
+// Filter that removed an account from the election input
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    // Stash has no controller or bonded ledger
+    NotBonded,
+    // Ledger exists but has no active stake
+    NoActiveStake,
+    // Active stake below the minimum nominator or validator bond
+    BelowMinBond,
+    // Nominations suppressed on chain
+    Suppressed,
+    // Neither nominating nor validating (e.g. chilled)
+    NotNominating,
+    // Voter kept, but nominations beyond MaxVotesPerVoter were dropped
+    TruncatedTargets,
+    // Voter beyond the snapshot capacity of VoterSnapshotPerBlock * Pages
+    SnapshotCapacity,
+    // Account already taken as a voter earlier in the reconstructed snapshot
+    DuplicateVoter,
+    // Removed by the manual override or an exclude flag
+    ManuallyRemoved,
+    // Snapshot candidate no longer in `Staking::Validators` at the block, i.e. chilled since the snapshot
+    Chilled,
+}
+
+// Account dropped from the election input and the reason, reported in explain mode
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Exclusion {
+    pub account: String,
+    pub role: ExcludedRole,
+    pub reason: ExclusionReason,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnelectedReason {
+    // No voter in the election nominated the candidate
+    NoSupport,
+    // Nominated, but the backing was not enough to make the cut
+    InsufficientSupport,
+}
+
+// Candidate present in the election targets that did not make it into the active set
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnelectedCandidate {
+    pub stash: String,
+    pub backing_stake: Balance,
+    pub backers_count: usize,
+    pub reason: UnelectedReason,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct UnelectedCandidateOutput {
+    pub stash: String,
+    pub backing_stake: String,
+    pub backers_count: usize,
+    pub reason: UnelectedReason,
+}
+
+// Nominator whose bonded stake was not fully assigned to the winners
+#[derive(Debug, Clone, PartialEq)]
+pub struct NominatorUnusedStake {
+    pub nominator: String,
+    pub bonded_stake: Balance,
+    pub assigned_stake: Balance,
+    pub unused_stake: Balance,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct NominatorUnusedStakeOutput {
+    pub nominator: String,
+    pub bonded_stake: String,
+    pub assigned_stake: String,
+    pub unused_stake: String,
+}
+
+// Nominator none of whose targets was elected, so its whole stake is wasted
+#[derive(Debug, Clone, PartialEq)]
+pub struct WastedVoter {
+    pub nominator: String,
+    pub stake: Balance,
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WastedVoterOutput {
+    pub nominator: String,
+    pub stake: String,
+    pub targets: Vec<String>,
+}
+
+// Encoded size of the mined paged solution measured against the miner MaxLength
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct SolutionLength {
+    pub encoded_length: u32,
+    pub max_length: u32,
+    pub usage_percent: f64,
+}
+
+impl SolutionLength {
+    pub fn new(encoded_length: u32, max_length: u32) -> Self {
+        let usage_percent = if max_length == 0 {
+            0.0
+        } else {
+            encoded_length as f64 * 100.0 / max_length as f64
+        };
+        SolutionLength { encoded_length, max_length, usage_percent }
+    }
+}
+
+// Score of the mined solution; the chain prefers a higher minimal stake, then a higher
+// sum of stake, then a lower sum of stake squared
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+pub struct ElectionScore {
+    pub minimal_stake: u128,
+    pub sum_stake: u128,
+    pub sum_stake_squared: u128,
+}
+
+impl From<NposElectionScore> for ElectionScore {
+    fn from(score: NposElectionScore) -> Self {
+        ElectionScore { minimal_stake: score.minimal_stake, sum_stake: score.sum_stake, sum_stake_squared: score.sum_stake_squared }
+    }
+}
+
+// One labelled component of the score breakdown
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScoreComponent {
+    pub name: &'static str,
+    pub value: u128,
+    // In the native token; sum_stake_squared is in Planck squared and has no token value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native: Option<String>,
+    pub interpretation: &'static str,
+}
+
+// Score breakdown of `--explain-score`, in the order the chain compares the components
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScoreExplanation {
+    pub components: Vec<ScoreComponent>,
+    // Spread of the winners' backing, derived from sum_stake and sum_stake_squared
+    pub backing_std_dev: String,
+    pub comparison: &'static str,
+}
+
+impl ElectionScore {
+    pub fn explain(&self, chain: Chain, winners: usize) -> ScoreExplanation {
+        let backing_std_dev = if winners == 0 {
+            0.0
+        } else {
+            let mean = self.sum_stake as f64 / winners as f64;
+            (self.sum_stake_squared as f64 / winners as f64 - mean * mean).max(0.0).sqrt()
+        };
+        ScoreExplanation {
+            components: vec![
+                ScoreComponent {
+                    name: "minimal_stake",
+                    value: self.minimal_stake,
+                    native: Some(chain.format_stake(self.minimal_stake)),
+                    interpretation: "Backing of the least-backed winner. Higher is better: it is the stake needed to outbid the weakest seat",
+                },
+                ScoreComponent {
+                    name: "sum_stake",
+                    value: self.sum_stake,
+                    native: Some(chain.format_stake(self.sum_stake)),
+                    interpretation: "Total backing of all winners. Higher is better: more of the nominated stake secures the active set",
+                },
+                ScoreComponent {
+                    name: "sum_stake_squared",
+                    value: self.sum_stake_squared,
+                    native: None,
+                    interpretation: "Sum of each winner's backing squared, in Planck squared. Lower is better: for the same sum_stake it is lowest when the backing is spread evenly",
+                },
+            ],
+            backing_std_dev: chain.format_stake(backing_std_dev.round() as Balance),
+            comparison: "Scores are compared component by component: a higher minimal_stake wins, then a higher sum_stake, then a lower sum_stake_squared",
+        }
+    }
+}
+
+impl From<ElectionScore> for NposElectionScore {
+    fn from(score: ElectionScore) -> Self {
+        NposElectionScore { minimal_stake: score.minimal_stake, sum_stake: score.sum_stake, sum_stake_squared: score.sum_stake_squared }
+    }
+}
+
+// Which of the mined and the queued solution the chain's score ordering prefers
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreOutcome {
+    SimulatedBetter,
+    QueuedBetter,
+    Equal,
+    // No solution has been verified and queued for the round yet
+    NothingQueued,
+}
+
+// Score of the mined solution against the solution queued on chain for the same round
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScoreComparison {
+    pub round: u32,
+    pub simulated_score: ElectionScore,
+    pub queued_score: Option<ElectionScore>,
+    pub outcome: ScoreOutcome,
+}
+
+// Solution registered in the signed phase; rank 1 is the best claimed score
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SignedSubmission {
+    pub rank: usize,
+    pub submitter: String,
+    pub score: ElectionScore,
+    // Whether the claimed score beats the solution already queued by the verifier, if any
+    pub beats_queued: Option<bool>,
+}
+
+// Signed submissions of a round, best first by the chain's score ordering
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SignedSubmissions {
+    pub round: u32,
+    pub queued_score: Option<ElectionScore>,
+    pub submissions: Vec<SignedSubmission>,
+}
+
+// One run of an iteration sweep: the score reached and how much the active set moved
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct IterationSweepStep {
+    pub iterations: usize,
+    pub score: ElectionScore,
+    pub elected_count: usize,
+    // Validators elected in this step that were not elected in the previous one
+    pub changed_validators: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IterationSweep {
+    pub run_parameters: RunParameters,
+    pub steps: Vec<IterationSweepStep>,
+}
+
+// Signed per-component change of the election score, as with reduce minus without reduce
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct ScoreDifference {
+    pub minimal_stake: i128,
+    pub sum_stake: i128,
+    pub sum_stake_squared: i128,
+}
+
+// The same snapshot mined without and with reduce, and what reducing the solution changed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReduceComparison {
+    pub run_parameters: RunParameters,
+    pub score_without_reduce: ElectionScore,
+    pub score_with_reduce: ElectionScore,
+    pub score_difference: ScoreDifference,
+    pub edges_without_reduce: usize,
+    pub edges_with_reduce: usize,
+    pub edge_difference: i64,
+    pub only_without_reduce: Vec<String>,
+    pub only_with_reduce: Vec<String>,
+    pub changed_validators: usize,
+}
+
+// Active validators whose commission falls in (above, up_to], or exactly 0% for the first band
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CommissionBandOutput {
+    pub band: &'static str,
+    pub validator_count: usize,
+    pub total_stake: String,
+    pub validators: Vec<String>,
+}
+
+// Nominators shared by two elected validators; jaccard is shared / union of their backer sets
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ValidatorOverlap {
+    pub validator_a: String,
+    pub validator_b: String,
+    pub shared_nominators: usize,
+    pub jaccard: f64,
+}
+
+// Validator already selected on chain for the upcoming era
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedValidator {
+    pub stash: String,
+    pub total_stake: Balance,
+    pub own_stake: Balance,
+}
+
+// Validator in both sets whose simulated stake differs from the queued one beyond the tolerance
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StakeMismatch {
+    pub stash: String,
+    pub simulated_stake: Balance,
+    pub queued_stake: Balance,
+    pub difference_percent: f64,
+}
+
+// Simulated validator set compared against the set queued on chain for the next era
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct QueuedComparison {
+    pub era: EraIndex,
+    pub simulated_count: usize,
+    pub queued_count: usize,
+    pub matching_count: usize,
+    pub only_simulated: Vec<String>,
+    pub only_queued: Vec<String>,
+    // Matching validators split by whether their total stake agrees within tolerance_percent
+    pub tolerance_percent: f64,
+    pub within_tolerance_count: usize,
+    pub beyond_tolerance_count: usize,
+    pub beyond_tolerance: Vec<StakeMismatch>,
+}
+
+#[derive(Debug, Default)]
 pub struct StakingStats {
     pub total_staked: Balance,
     pub lowest_staked: Balance,
     pub avg_staked: Balance,
+    pub total_self_stake: Balance,
+    pub total_nominated_stake: Balance,
+    // Smallest bonded stake among nominators backing at least one elected validator
+    pub min_active_nominator_stake: Balance,
+    // On-chain Staking::MinimumActiveStake, or min_active_nominator_stake when the chain does not store it
+    pub minimum_active_stake: Balance,
+    // Gini coefficients (0 for an even spread) of the winners' total stake and of each
+    // nominator's stake assigned across the winners
+    pub validator_stake_gini: f64,
+    pub nominator_stake_gini: f64,
+    // Nominator stake left unassigned by the election, summed over all nominators
+    pub total_unused_stake: Balance,
+    // Distribution of the winners' total stake and of each nominator's assigned stake
+    pub validator_stake_percentiles: StakePercentiles,
+    pub nominator_stake_percentiles: StakePercentiles,
+}
+
+// Nearest-rank percentiles of a stake distribution
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StakePercentiles {
+    pub p50: Balance,
+    pub p90: Balance,
+    pub p99: Balance,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StakePercentilesOutput {
+    pub p50: String,
+    pub p90: String,
+    pub p99: String,
+}
+
+impl StakePercentiles {
+    fn to_output(self, chain: Chain) -> StakePercentilesOutput {
+        StakePercentilesOutput {
+            p50: chain.format_stake(self.p50),
+            p90: chain.format_stake(self.p90),
+            p99: chain.format_stake(self.p99),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct StakingStatsOutput {
     pub total_staked: String,
     pub lowest_staked: String,
+    pub stake_to_get_elected: String,
     pub avg_staked: String,
+    pub total_self_stake: String,
+    pub total_nominated_stake: String,
+    pub min_active_nominator_stake: String,
+    pub minimum_active_stake: String,
+    pub validator_stake_gini: f64,
+    pub nominator_stake_gini: f64,
+    pub total_unused_stake: String,
+    pub validator_stake_percentiles: StakePercentilesOutput,
+    pub nominator_stake_percentiles: StakePercentilesOutput,
 }
 
 // Output simulation with formatted stake strings
 #[derive(Debug, Serialize)]
 pub struct SimulationResultOutput {
     pub run_parameters: RunParameters,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_timestamp: Option<BlockTimestamp>,
     pub staking_stats: StakingStatsOutput,
     pub active_validators: Vec<ValidatorOutput>,
+    pub solution_length: SolutionLength,
+    pub score: ElectionScore,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unelected_candidates: Option<Vec<UnelectedCandidateOutput>>,
+    // Stashes of the unelected candidates nobody nominated, not even themselves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zero_support: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_stake: Option<Vec<NominatorUnusedStakeOutput>>,
+    // Nominators backing no elected validator; the list is only included on request
+    pub wasted_voters_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wasted_voters: Option<Vec<WastedVoterOutput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusions: Option<Vec<Exclusion>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queued_comparison: Option<QueuedComparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nominator_overlap: Option<Vec<ValidatorOverlap>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_comparison: Option<ScoreComparison>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_explanation: Option<ScoreExplanation>,
+    #[serde(skip_serializing_if = "ReconstructionCounts::is_empty")]
+    pub reconstruction: ReconstructionCounts,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    // What --max-duration cut short, only present on results mined from partial data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<Vec<String>>,
+}
+
+// Nominations of at least `min_stake`, largest first and cut to `limit`; the original order is
+// kept when there is no limit
+fn displayed_nominations(nominations: &[ValidatorNomination], limit: Option<usize>, min_stake: Balance) -> Vec<&ValidatorNomination> {
+    let mut nominations: Vec<&ValidatorNomination> = nominations.iter().filter(|n| n.stake >= min_stake).collect();
+    if let Some(limit) = limit {
+        nominations.sort_by_key(|n| std::cmp::Reverse(n.stake));
+        nominations.truncate(limit);
+    }
+    nominations
 }
 
 impl SimulationResult {
-    pub fn to_output(&self, chain: Chain) -> SimulationResultOutput {
+    // Total backing a candidate needs to displace the lowest elected validator: one Planck above
+    // its stake, or just the validator bond when seats were left empty. Approximate, as a new
+    // winner also changes how its nominators' stake is spread
+    pub fn stake_to_get_elected(&self) -> Balance {
+        if self.active_validators.len() < self.run_parameters.desired_validators as usize {
+            return self.run_parameters.min_validator_bond.max(1);
+        }
+        self.staking_stats.lowest_staked.saturating_add(1)
+    }
+
+    pub fn to_output(&self, chain: Chain, options: &OutputOptions) -> SimulationResultOutput {
         SimulationResultOutput {
             run_parameters: self.run_parameters.clone(),
+            block_timestamp: None,
             staking_stats: StakingStatsOutput {
                 total_staked: chain.format_stake(self.staking_stats.total_staked),
                 lowest_staked: chain.format_stake(self.staking_stats.lowest_staked),
+                stake_to_get_elected: chain.format_stake(self.stake_to_get_elected()),
                 avg_staked: chain.format_stake(self.staking_stats.avg_staked),
+                total_self_stake: chain.format_stake(self.staking_stats.total_self_stake),
+                total_nominated_stake: chain.format_stake(self.staking_stats.total_nominated_stake),
+                min_active_nominator_stake: chain.format_stake(self.staking_stats.min_active_nominator_stake),
+                minimum_active_stake: chain.format_stake(self.staking_stats.minimum_active_stake),
+                validator_stake_gini: round_float(self.staking_stats.validator_stake_gini, options.precision),
+                nominator_stake_gini: round_float(self.staking_stats.nominator_stake_gini, options.precision),
+                total_unused_stake: chain.format_stake(self.staking_stats.total_unused_stake),
+                validator_stake_percentiles: self.staking_stats.validator_stake_percentiles.to_output(chain),
+                nominator_stake_percentiles: self.staking_stats.nominator_stake_percentiles.to_output(chain),
             },
             active_validators: self.active_validators.iter().map(|v| {
                 ValidatorOutput {
                     stash: v.stash.clone(),
+                    display_name: None,
                     self_stake: chain.format_stake(v.self_stake),
                     total_stake: chain.format_stake(v.total_stake),
-                    commission: v.commission,
+                    nominator_to_self_ratio: v.nominator_to_self_ratio().map(|ratio| round_float(ratio, options.precision)),
+                    commission: round_float(v.commission, options.precision),
+                    commission_perbill: options.raw_commission.then_some(v.commission_parts),
+                    commission_bps: options.commission_bps.then(|| perbill_parts_to_basis_points(v.commission_parts)),
                     blocked: v.blocked,
                     nominations_count: v.nominations_count,
-                    nominations: v.nominations.iter().map(|n| {
+                    nominations: displayed_nominations(&v.nominations, options.top_backers, options.min_nomination_display).into_iter().map(|n| {
                         ValidatorNominationOutput {
                             nominator: n.nominator.clone(),
                             stake: chain.format_stake(n.stake),
+                            pool_id: n.pool_id,
+                            display_name: None,
                         }
                     }).collect(),
                 }
             }).collect(),
+            solution_length: SolutionLength {
+                usage_percent: round_float(self.solution_length.usage_percent, options.precision),
+                ..self.solution_length.clone()
+            },
+            score: self.score,
+            unelected_candidates: options.include_unelected.then(|| self.unelected_candidates.iter().map(|c| {
+                UnelectedCandidateOutput {
+                    stash: c.stash.clone(),
+                    backing_stake: chain.format_stake(c.backing_stake),
+                    backers_count: c.backers_count,
+                    reason: c.reason,
+                }
+            }).collect()),
+            zero_support: options.include_unelected.then(|| self.unelected_candidates.iter()
+                .filter(|c| c.reason == UnelectedReason::NoSupport)
+                .map(|c| c.stash.clone())
+                .collect()),
+            unused_stake: options.include_unused_stake.then(|| self.unused_stake.iter().map(|n| {
+                NominatorUnusedStakeOutput {
+                    nominator: n.nominator.clone(),
+                    bonded_stake: chain.format_stake(n.bonded_stake),
+                    assigned_stake: chain.format_stake(n.assigned_stake),
+                    unused_stake: chain.format_stake(n.unused_stake),
+                }
+            }).collect()),
+            wasted_voters_count: self.wasted_voters.len(),
+            wasted_voters: options.include_wasted_voters.then(|| self.wasted_voters.iter().map(|v| {
+                WastedVoterOutput {
+                    nominator: v.nominator.clone(),
+                    stake: chain.format_stake(v.stake),
+                    targets: v.targets.clone(),
+                }
+            }).collect()),
+            exclusions: None,
+            queued_comparison: self.queued_comparison.clone(),
+            nominator_overlap: None,
+            score_comparison: None,
+            score_explanation: options.explain_score.then(|| self.score.explain(chain, self.active_validators.len())),
+            reconstruction: self.reconstruction,
+            warnings: self.warnings.clone(),
+            partial: None,
+        }
+    }
+}
+
+// Column order of the validator table exported as CSV
+const VALIDATOR_CSV_HEADER: &str = "stash,self_stake,total_stake,commission,blocked,nominations_count";
+
+// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl SimulationResultOutput {
+    // Attach identity display names, keyed by SS58 address, to the validators and optionally their nominators
+    pub fn annotate_display_names(&mut self, names: &HashMap<String, String>, include_nominators: bool) {
+        for validator in self.active_validators.iter_mut() {
+            validator.display_name = names.get(&validator.stash).cloned();
+            if include_nominators {
+                for nomination in validator.nominations.iter_mut() {
+                    nomination.display_name = names.get(&nomination.nominator).cloned();
+                }
+            }
+        }
+    }
+
+    // Validator table as CSV, one row per active validator, shared by the CLI and the API
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(VALIDATOR_CSV_HEADER);
+        csv.push('\n');
+        for v in &self.active_validators {
+            csv.push_str(&[
+                csv_field(&v.stash),
+                csv_field(&v.self_stake),
+                csv_field(&v.total_stake),
+                v.commission.to_string(),
+                v.blocked.to_string(),
+                v.nominations_count.to_string(),
+            ].join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    // Short human readable overview printed by --tee next to the full output file
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Active validators: {}\nTotal stake: {}\nValidator stake p50/p90/p99: {} / {} / {}\nNominator stake p50/p90/p99: {} / {} / {}\nScore: minimal_stake {}, sum_stake {}, sum_stake_squared {}",
+            self.active_validators.len(),
+            self.staking_stats.total_staked,
+            self.staking_stats.validator_stake_percentiles.p50,
+            self.staking_stats.validator_stake_percentiles.p90,
+            self.staking_stats.validator_stake_percentiles.p99,
+            self.staking_stats.nominator_stake_percentiles.p50,
+            self.staking_stats.nominator_stake_percentiles.p90,
+            self.staking_stats.nominator_stake_percentiles.p99,
+            self.score.minimal_stake,
+            self.score.sum_stake,
+            self.score.sum_stake_squared,
+        );
+        if let Some(comparison) = &self.queued_comparison {
+            summary.push_str(&format!(
+                "\nQueued era {}: {} of {} matching, {} beyond {}% stake tolerance",
+                comparison.era,
+                comparison.matching_count,
+                comparison.queued_count,
+                comparison.beyond_tolerance_count,
+                comparison.tolerance_percent,
+            ));
+        }
+        for warning in &self.warnings {
+            summary.push_str(&format!("\nWarning: {}", warning));
+        }
+        for cut in self.partial.iter().flatten() {
+            summary.push_str(&format!("\nPartial: {}", cut));
         }
+        summary
     }
 }
 
@@ -212,6 +1314,14 @@ impl SimulationResult {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_chain_name() {
+        use clap::ValueEnum;
+        for chain in Chain::value_variants() {
+            assert_eq!(chain.to_possible_value().unwrap().get_name(), chain.name());
+        }
+    }
+
     #[test]
     fn test_chain_ss58_address_format() {
         assert_eq!(Chain::Polkadot.ss58_address_format(), Ss58AddressFormat::custom(0));
@@ -219,6 +1329,26 @@ mod tests {
         assert_eq!(Chain::Substrate.ss58_address_format(), Ss58AddressFormat::custom(42));
     }
 
+    #[test]
+    fn test_parse_ss58_prefix() {
+        assert_eq!(parse_ss58_prefix("5"), Ok(Ss58AddressFormat::custom(5)));
+        assert_eq!(parse_ss58_prefix("16383"), Ok(Ss58AddressFormat::custom(16_383)));
+        assert!(parse_ss58_prefix("16384").is_err());
+        assert!(parse_ss58_prefix("46").is_err());
+        assert!(parse_ss58_prefix("-1").is_err());
+        assert!(parse_ss58_prefix("astar").is_err());
+    }
+
+    #[test]
+    fn test_parse_tolerance() {
+        assert_eq!(parse_tolerance("0"), Ok(0.0));
+        assert_eq!(parse_tolerance("0.5"), Ok(0.5));
+        assert!(parse_tolerance("-1").is_err());
+        assert!(parse_tolerance("101").is_err());
+        assert!(parse_tolerance("NaN").is_err());
+        assert!(parse_tolerance("half").is_err());
+    }
+
     #[test]
     fn test_chain_format_stake() {
         assert!(Chain::Polkadot.format_stake(10_000_000_000).starts_with("1 DOT"));
@@ -230,21 +1360,64 @@ mod tests {
     fn test_snapshot_to_output_polkadot() {
         let snapshot = Snapshot {
             validators: vec![],
-            nominators: vec![SnapshotNominator {stash: "x".to_string(), stake: 10_000_000_000, nominations: vec![]}],
-            config: StakingConfig {desired_validators: 1, max_nominations: 16, min_nominator_bond: 0, min_validator_bond: 0},
+            nominators: vec![SnapshotNominator {stash: "x".to_string(), stake: 10_000_000_000, nominations: vec![], pool_id: None}],
+            config: StakingConfig { desired_validators: 1, max_nominations: 16, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
         };
-        let out = snapshot.to_output(Chain::Polkadot);
+        let out = snapshot.to_output(Chain::Polkadot, &OutputOptions::default());
         assert_eq!(out.nominators[0].stake, "1 DOT");
     }
 
+    #[test]
+    fn test_snapshot_to_output_nominator_sort() {
+        let nominator = |stash: &str, stake: Balance| SnapshotNominator { stash: stash.to_string(), stake, nominations: vec![], pool_id: None };
+        let snapshot = Snapshot {
+            validators: vec![],
+            nominators: vec![nominator("c", 10), nominator("a", 5), nominator("b", 10)],
+            config: StakingConfig { desired_validators: 1, max_nominations: 16, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
+        };
+        let order = |sort: NominatorSort| -> Vec<String> {
+            let options = OutputOptions { nominator_sort: sort, ..OutputOptions::default() };
+            snapshot.to_output(Chain::Substrate, &options).nominators.into_iter().map(|n| n.stash).collect()
+        };
+        assert_eq!(order(NominatorSort::Stash), vec!["a", "b", "c"]);
+        assert_eq!(order(NominatorSort::Stake), vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn test_snapshot_nominator_files() {
+        let nominator = |stash: &str| SnapshotNominator { stash: stash.to_string(), stake: 1, nominations: vec![], pool_id: None };
+        let snapshot = Snapshot {
+            validators: vec![],
+            nominators: vec![nominator("a"), nominator("b"), nominator("c")],
+            config: StakingConfig { desired_validators: 1, max_nominations: 16, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
+        };
+        let out = snapshot.to_output(Chain::Substrate, &OutputOptions::default());
+        let files = out.nominator_files(2);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, "nominators-000.json");
+        assert_eq!(files[0].1.iter().map(|n| n.stash.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(files[1].0, "nominators-001.json");
+        assert_eq!(files[1].1.len(), 1);
+        assert_eq!(out.nominator_files(3).len(), 1);
+
+        let index = out.split_index(files.into_iter().map(|(name, _)| name).collect());
+        assert_eq!(index.nominators_count, 3);
+        assert_eq!(index.validators_file, "validators.json");
+        assert_eq!(index.nominator_files, vec!["nominators-000.json", "nominators-001.json"]);
+    }
+
     #[test]
     fn test_snapshot_to_output_kusama() {
         let s = Snapshot {
             validators: vec![],
-            nominators: vec![SnapshotNominator { stash: "x".to_string(), stake: 1_000_000_000_000, nominations: vec![] }],
-            config: StakingConfig { desired_validators: 1, max_nominations: 24, min_nominator_bond: 0, min_validator_bond: 0 },
+            nominators: vec![SnapshotNominator { stash: "x".to_string(), stake: 1_000_000_000_000, nominations: vec![], pool_id: None }],
+            config: StakingConfig { desired_validators: 1, max_nominations: 24, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
         };
-        let out = s.to_output(Chain::Kusama);
+        let out = s.to_output(Chain::Kusama, &OutputOptions::default());
         assert!(out.nominators[0].stake.starts_with("1 KSM"));
     }
 
@@ -252,42 +1425,289 @@ mod tests {
     fn test_snapshot_to_output_substrate() {
         let snapshot = Snapshot {
             validators: vec![],
-            nominators: vec![SnapshotNominator { stash: "x".to_string(), stake: 999, nominations: vec![] }],
-            config: StakingConfig { desired_validators: 1, max_nominations: 16, min_nominator_bond: 0, min_validator_bond: 0 },
+            nominators: vec![SnapshotNominator { stash: "x".to_string(), stake: 999, nominations: vec![], pool_id: None }],
+            config: StakingConfig { desired_validators: 1, max_nominations: 16, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
         };
-        let out = snapshot.to_output(Chain::Substrate);
+        let out = snapshot.to_output(Chain::Substrate, &OutputOptions::default());
         assert_eq!(out.nominators[0].stake, "999 Planck");
     }
 
-    #[test]
-    fn test_simulation_result_to_output_all_chains() {
-        let result = SimulationResult {
-            run_parameters: RunParameters {
-                algorithm: Algorithm::SeqPhragmen,
-                iterations: 0,
-                reduce: false,
-                max_nominations: 16,
-                min_nominator_bond: 0,
-                min_validator_bond: 0,
-                desired_validators: 1,
-            },
-            staking_stats: StakingStats { total_staked: 1_000_000_000_000, lowest_staked: 100, avg_staked: 500 },
+    // A run electing the single validator "x", backed by 100 self stake and 900 nominated
+    fn single_validator_result() -> SimulationResult {
+        SimulationResult {
+            run_parameters: RunParameters { max_nominations: 16, desired_validators: 1, ..RunParameters::default() },
+            staking_stats: StakingStats { total_staked: 1_000_000_000_000, lowest_staked: 100, avg_staked: 500, total_self_stake: 100, total_nominated_stake: 900, min_active_nominator_stake: 200, minimum_active_stake: 200, ..StakingStats::default() },
             active_validators: vec![Validator {
                 stash: "x".to_string(),
                 self_stake: 100,
                 total_stake: 1000,
                 commission: 0.0,
+                commission_parts: 0,
                 blocked: false,
                 nominations_count: 0,
                 nominations: vec![],
             }],
-        };
-        let out_dot = result.to_output(Chain::Polkadot);
+            solution_length: SolutionLength::new(100, 1000),
+            ..SimulationResult::default()
+        }
+    }
+
+    #[test]
+    fn test_simulation_result_to_output_all_chains() {
+        let result = single_validator_result();
+        let out_dot = result.to_output(Chain::Polkadot, &OutputOptions::default());
         assert!(out_dot.staking_stats.total_staked.starts_with("100 DOT"));
-        let out_ksm = result.to_output(Chain::Kusama);
+        let out_ksm = result.to_output(Chain::Kusama, &OutputOptions::default());
         assert!(out_ksm.staking_stats.total_staked.starts_with("1 KSM"));
-        let out_sub = result.to_output(Chain::Substrate);
+        let out_sub = result.to_output(Chain::Substrate, &OutputOptions::default());
         assert_eq!(out_sub.staking_stats.total_staked, "1000000000000 Planck");
     }
-}
 
+    #[test]
+    fn test_stake_to_get_elected() {
+        let result = single_validator_result();
+        let output = result.to_output(Chain::Substrate, &OutputOptions::default());
+        assert_eq!(output.staking_stats.stake_to_get_elected, "101 Planck");
+
+        // With a seat left empty any backed candidate above the validator bond gets in
+        let open_seat = SimulationResult { run_parameters: RunParameters { desired_validators: 2, min_validator_bond: 50, ..result.run_parameters.clone() }, ..result };
+        assert_eq!(open_seat.stake_to_get_elected(), 50);
+    }
+
+    #[test]
+    fn test_nominator_to_self_ratio() {
+        let output = single_validator_result().to_output(Chain::Substrate, &OutputOptions::default());
+        assert_eq!(output.active_validators[0].nominator_to_self_ratio, Some(9.0));
+        let unbonded = Validator { stash: "y".to_string(), self_stake: 0, total_stake: 1000, commission: 0.0, commission_parts: 0, blocked: false, nominations_count: 1, nominations: vec![] };
+        assert_eq!(unbonded.nominator_to_self_ratio(), None);
+    }
+
+    #[test]
+    fn test_zero_support() {
+        let unelected = |stash: &str, backers_count: usize, reason: UnelectedReason| UnelectedCandidate {
+            stash: stash.to_string(), backing_stake: 10 * backers_count as u128, backers_count, reason,
+        };
+        let result = SimulationResult {
+            unelected_candidates: vec![unelected("y", 2, UnelectedReason::InsufficientSupport), unelected("z", 0, UnelectedReason::NoSupport)],
+            ..single_validator_result()
+        };
+        // Candidates nobody nominated are also listed on their own
+        let output = result.to_output(Chain::Substrate, &OutputOptions { include_unelected: true, ..OutputOptions::default() });
+        assert_eq!(output.unelected_candidates.map(|candidates| candidates.len()), Some(2));
+        assert_eq!(output.zero_support, Some(vec!["z".to_string()]));
+        assert_eq!(result.to_output(Chain::Substrate, &OutputOptions::default()).zero_support, None);
+    }
+
+    #[test]
+    fn test_simulation_result_to_output_reconstruction() {
+        let output = serde_json::to_value(single_validator_result().to_output(Chain::Substrate, &OutputOptions::default())).unwrap();
+        assert!(output.get("reconstruction").is_none());
+
+        let reconstruction = ReconstructionCounts { truncated_voters: 1, dropped_nominations: 2, skipped_accounts: 3 };
+        let result = SimulationResult { reconstruction, ..single_validator_result() };
+        let output = serde_json::to_value(result.to_output(Chain::Substrate, &OutputOptions::default())).unwrap();
+        assert_eq!(output["reconstruction"], serde_json::json!({ "truncated_voters": 1, "dropped_nominations": 2, "skipped_accounts": 3 }));
+    }
+
+    #[test]
+    fn test_timeseries_record() {
+        let point = serde_json::to_string(&single_validator_result().timeseries_point(Chain::Kusama, 42, 1_700_000_000_000)).unwrap();
+        assert_eq!(point, r#"{"chain":"kusama","timestamp":1700000000000,"block":42,"total_stake":1000000000000,"validator_count":1,"min_active_stake":200,"score_minimal_stake":0,"score_sum_stake":0,"score_sum_stake_squared":0}"#);
+    }
+
+    #[test]
+    fn test_simulation_result_to_output_top_backers() {
+        let nomination = |nominator: &str, stake: Balance| ValidatorNomination {
+            nominator: nominator.to_string(),
+            stake,
+            pool_id: None,
+        };
+        let result = SimulationResult {
+            run_parameters: RunParameters { max_nominations: 16, desired_validators: 1, ..RunParameters::default() },
+            staking_stats: StakingStats { total_staked: 1000, lowest_staked: 1000, avg_staked: 1000, total_self_stake: 100, total_nominated_stake: 900, min_active_nominator_stake: 200, minimum_active_stake: 200, ..StakingStats::default() },
+            active_validators: vec![Validator {
+                stash: "x".to_string(),
+                self_stake: 100,
+                total_stake: 1000,
+                commission: 0.0,
+                commission_parts: 0,
+                blocked: false,
+                nominations_count: 3,
+                nominations: vec![nomination("a", 200), nomination("b", 500), nomination("c", 200)],
+            }],
+            solution_length: SolutionLength::new(100, 1000),
+            ..SimulationResult::default()
+        };
+        let options = OutputOptions { top_backers: Some(2), ..OutputOptions::default() };
+        let output = result.to_output(Chain::Substrate, &options);
+        let validator = &output.active_validators[0];
+        assert!(validator.nominations.len() <= 2);
+        assert_eq!(validator.nominations_count, 3);
+        assert_eq!(validator.total_stake, "1000 Planck");
+        let nominators: Vec<&str> = validator.nominations.iter().map(|n| n.nominator.as_str()).collect();
+        assert_eq!(nominators, vec!["b", "a"]);
+
+        let options = OutputOptions { min_nomination_display: 300, ..OutputOptions::default() };
+        let output = result.to_output(Chain::Substrate, &options);
+        let validator = &output.active_validators[0];
+        let nominators: Vec<&str> = validator.nominations.iter().map(|n| n.nominator.as_str()).collect();
+        assert_eq!(nominators, vec!["b"]);
+        assert_eq!(validator.nominations_count, 3);
+        assert_eq!(validator.total_stake, "1000 Planck");
+
+        let mut output = result.to_output(Chain::Substrate, &OutputOptions::default());
+        assert_eq!(output.active_validators[0].nominations.len(), 3);
+
+        let names = HashMap::from([("x".to_string(), "Validator X".to_string()), ("b".to_string(), "Nominator B".to_string())]);
+        output.annotate_display_names(&names, false);
+        assert_eq!(output.active_validators[0].display_name.as_deref(), Some("Validator X"));
+        assert!(output.active_validators[0].nominations.iter().all(|n| n.display_name.is_none()));
+        output.annotate_display_names(&names, true);
+        let named: Vec<Option<&str>> = output.active_validators[0].nominations.iter().map(|n| n.display_name.as_deref()).collect();
+        assert_eq!(named, vec![None, Some("Nominator B"), None]);
+    }
+
+    #[test]
+    fn test_simulation_result_msgpack_round_trip() {
+        // MessagePack keeps the field names and u128 values above u64
+        #[derive(Deserialize)]
+        struct DecodedValidator { stash: String, total_stake: String, nominations_count: usize }
+        #[derive(Deserialize)]
+        struct DecodedScore { minimal_stake: u128, sum_stake_squared: u128 }
+        #[derive(Deserialize)]
+        struct Decoded { active_validators: Vec<DecodedValidator>, score: DecodedScore }
+        let score = ElectionScore { minimal_stake: 1000, sum_stake: 1000, sum_stake_squared: u128::from(u64::MAX) * 3 };
+        let output = SimulationResult { score, ..single_validator_result() }.to_output(Chain::Substrate, &OutputOptions::default());
+        let decoded: Decoded = rmp_serde::from_slice(&to_msgpack(&output).unwrap()).unwrap();
+        assert_eq!(decoded.active_validators.len(), 1);
+        assert_eq!(decoded.active_validators[0].stash, "x");
+        assert_eq!(decoded.active_validators[0].total_stake, "1000 Planck");
+        assert_eq!(decoded.active_validators[0].nominations_count, 0);
+        assert_eq!((decoded.score.minimal_stake, decoded.score.sum_stake_squared), (1000, u128::from(u64::MAX) * 3));
+
+        #[derive(Deserialize)]
+        struct DecodedNominator { stash: String, stake: String, nominations: Vec<String> }
+        #[derive(Deserialize)]
+        struct DecodedConfig { desired_validators: u32, min_nominator_bond: u128 }
+        #[derive(Deserialize)]
+        struct DecodedSnapshotValidator { stash: String, commission: f64, blocked: bool }
+        #[derive(Deserialize)]
+        struct DecodedSnapshot { validators: Vec<DecodedSnapshotValidator>, nominators: Vec<DecodedNominator>, config: DecodedConfig }
+        let snapshot = Snapshot {
+            validators: vec![SnapshotValidator { stash: "x".to_string(), commission: 0.05, commission_parts: 50_000_000, commission_bps: None, blocked: false }],
+            nominators: vec![SnapshotNominator { stash: "a".to_string(), stake: 10_000_000_000, nominations: vec!["x".to_string()], pool_id: None }],
+            config: StakingConfig { desired_validators: 1, max_nominations: 16, min_nominator_bond: u128::from(u64::MAX) * 3, ..StakingConfig::default() },
+            reconstruction: ReconstructionCounts::default(),
+        };
+        let output = snapshot.to_output(Chain::Polkadot, &OutputOptions::default());
+        let decoded: DecodedSnapshot = rmp_serde::from_slice(&to_msgpack(&output).unwrap()).unwrap();
+        assert_eq!(decoded.validators.len(), 1);
+        assert_eq!((decoded.validators[0].stash.as_str(), decoded.validators[0].commission, decoded.validators[0].blocked), ("x", 0.05, false));
+        assert_eq!(decoded.nominators.len(), 1);
+        assert_eq!((decoded.nominators[0].stash.as_str(), decoded.nominators[0].stake.as_str()), ("a", "1 DOT"));
+        assert_eq!(decoded.nominators[0].nominations, vec!["x".to_string()]);
+        assert_eq!((decoded.config.desired_validators, decoded.config.min_nominator_bond), (1, u128::from(u64::MAX) * 3));
+    }
+
+    #[test]
+    fn test_era_start_description() {
+        let era = EraInfo { index: 10, start: Some(1_700_000_000_000), start_session_index: None };
+        assert_eq!(era.start_description(), "2023-11-14T22:13:20+00:00");
+        let era = EraInfo { start: None, ..era };
+        assert_eq!(era.start_description(), "unknown start");
+        let era = EraInfo { start: Some(u64::MAX), ..era };
+        assert_eq!(era.start_description(), "unknown start");
+    }
+
+    #[test]
+    fn test_block_timestamp_from_millis() {
+        let timestamp = BlockTimestamp::from_millis(1_700_000_000_000);
+        assert_eq!(timestamp.rfc3339, "2023-11-14T22:13:20+00:00");
+        assert_eq!(serde_json::to_string(&timestamp).unwrap(), r#"{"unix_millis":1700000000000,"rfc3339":"2023-11-14T22:13:20+00:00"}"#);
+    }
+
+    #[test]
+    fn test_election_score_explain() {
+        // Winners backed by 100 and 300 Planck
+        let score = ElectionScore { minimal_stake: 100, sum_stake: 400, sum_stake_squared: 100_000 };
+        let explanation = score.explain(Chain::Substrate, 2);
+        let names: Vec<&str> = explanation.components.iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["minimal_stake", "sum_stake", "sum_stake_squared"]);
+        assert_eq!(explanation.components[0].native.as_deref(), Some("100 Planck"));
+        assert_eq!(explanation.components[1].native.as_deref(), Some("400 Planck"));
+        assert_eq!(explanation.components[2].native, None);
+        assert_eq!(explanation.backing_std_dev, "100 Planck");
+        assert_eq!(ElectionScore::default().explain(Chain::Substrate, 0).backing_std_dev, "0 Planck");
+    }
+
+    #[test]
+    fn test_solution_length_usage_percent() {
+        let length = SolutionLength::new(450, 1800);
+        assert_eq!(length.usage_percent, 25.0);
+        assert_eq!(SolutionLength::new(10, 0).usage_percent, 0.0);
+    }
+
+    #[test]
+    fn test_simulation_result_to_csv() {
+        let output = SimulationResultOutput {
+            run_parameters: RunParameters { max_nominations: 16, desired_validators: 1, ..RunParameters::default() },
+            block_timestamp: None,
+            staking_stats: StakingStatsOutput { total_staked: "1 DOT".to_string(), lowest_staked: "1 DOT".to_string(), stake_to_get_elected: "1 DOT".to_string(), avg_staked: "1 DOT".to_string(), total_self_stake: "0 DOT".to_string(), total_nominated_stake: "1 DOT".to_string(), min_active_nominator_stake: "1 DOT".to_string(), minimum_active_stake: "1 DOT".to_string(), validator_stake_gini: 0.0, nominator_stake_gini: 0.0, total_unused_stake: "0 DOT".to_string(), validator_stake_percentiles: StakePercentilesOutput { p50: "1 DOT".to_string(), p90: "1 DOT".to_string(), p99: "1 DOT".to_string() }, nominator_stake_percentiles: StakePercentilesOutput { p50: "1 DOT".to_string(), p90: "1 DOT".to_string(), p99: "1 DOT".to_string() } },
+            active_validators: vec![ValidatorOutput {
+                stash: "x".to_string(),
+                display_name: None,
+                self_stake: "1,5 DOT".to_string(),
+                total_stake: "1 DOT".to_string(),
+                nominator_to_self_ratio: None,
+                commission: 0.05,
+                commission_perbill: None,
+                commission_bps: None,
+                blocked: false,
+                nominations_count: 2,
+                nominations: vec![],
+            }],
+            solution_length: SolutionLength::new(0, 0),
+            score: ElectionScore::default(),
+            unelected_candidates: None,
+            zero_support: None,
+            unused_stake: None,
+            wasted_voters_count: 0,
+            wasted_voters: None,
+            exclusions: None,
+            queued_comparison: None,
+            nominator_overlap: None,
+            score_comparison: None,
+            score_explanation: None,
+            reconstruction: ReconstructionCounts::default(),
+            warnings: vec!["requested 2 validators".to_string()],
+            partial: None,
+        };
+        assert_eq!(output.to_csv(), "stash,self_stake,total_stake,commission,blocked,nominations_count\nx,\"1,5 DOT\",1 DOT,0.05,false,2\n");
+        assert_eq!(output.summary(), "Active validators: 1\nTotal stake: 1 DOT\nValidator stake p50/p90/p99: 1 DOT / 1 DOT / 1 DOT\nNominator stake p50/p90/p99: 1 DOT / 1 DOT / 1 DOT\nScore: minimal_stake 0, sum_stake 0, sum_stake_squared 0\nWarning: requested 2 validators");
+    }
+
+    #[test]
+    fn test_scale_snapshot_output() {
+        let snapshot = ScaleSnapshot { round: 2, pages: 1, voter_pages: vec![4, 0], targets: vec![0] };
+        assert_eq!(snapshot.to_output(), ScaleSnapshotOutput {
+            round: 2,
+            pages: 1,
+            voter_pages: "0x0400".to_string(),
+            targets: "0x00".to_string(),
+        });
+        assert_eq!(snapshot.to_bytes(), vec![4, 0, 0]);
+    }
+
+    #[test]
+    fn test_commission_precision() {
+        let parts = sp_runtime::Perbill::from_parts(75_000_001).deconstruct();
+        let commission = parts as f64 / 1_000_000_000.0;
+        assert_eq!(round_float(commission, DEFAULT_FLOAT_PRECISION), 0.075);
+        assert_eq!(round_float(commission, 2), 0.08);
+        assert_eq!(perbill_parts_to_basis_points(parts), 750);
+        assert_eq!(perbill_parts_to_basis_points(sp_runtime::Perbill::one().deconstruct()), 10_000);
+        // Parts just below a basis point boundary are truncated rather than rounded up
+        assert_eq!(perbill_parts_to_basis_points(sp_runtime::Perbill::from_parts(99_999_999).deconstruct()), 999);
+    }
+}
@@ -2,32 +2,37 @@ use mockall::automock;
 use subxt::utils::Yes;
 use subxt::storage::Address;
 use crate::{primitives::Storage, subxt_client::Client};
-use crate::raw_state_client::{NominationsLight, StakingLedger};
+use crate::raw_state_client::{rpc_error_message, NominationsLight, StakingLedger};
+use crate::miner_config::{election_pallet, election_sub_pallet};
 use pallet_staking::ValidatorPrefs;
 use parity_scale_codec::{Decode, Encode};
-use parity_scale_codec as codec;
+use serde::{Deserialize, Serialize};
 use frame_support::BoundedVec;
 use frame_election_provider_support::Voter;
 use pallet_election_provider_multi_block::{unsigned::miner::MinerConfig};
 use sp_core::Get;
-use subxt::dynamic::Value;
+use sp_npos_elections::ElectionScore;
+use subxt::dynamic::{At, Value};
 
-use crate::primitives::{AccountId, Hash};
+use crate::primitives::{AccountId, Balance, EraIndex, Hash};
 use subxt::ext::{scale_value};
+use subxt::ext::scale_value::ValueDef;
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
+use futures::future::join_all;
 
 // Trait for chain client operations to enable dependency injection for testing
 #[automock]
 #[async_trait::async_trait]
 pub trait ChainClientTrait: Send + Sync {
     async fn get_storage(&self, block: Option<Hash>) -> Result<Storage, Box<dyn std::error::Error + Send + Sync>>;
-    async fn fetch_constant<T: serde::de::DeserializeOwned>(
+    // Names of the pallets in the runtime metadata
+    fn pallet_names(&self) -> Vec<String>;
+    async fn fetch_constant<T: serde::de::DeserializeOwned + 'static>(
         &self,
         pallet: &str,
         constant_name: &str,
-    ) -> Result<T, Box<dyn std::error::Error>>
-    where
-        T: 'static;
+    ) -> Result<T, Box<dyn std::error::Error>>;
 }
 
 // Implementation of ChainClientTrait for Client
@@ -41,6 +46,10 @@ impl ChainClientTrait for Client {
         }
     }
 
+    fn pallet_names(&self) -> Vec<String> {
+        self.chain_api().metadata().pallets().map(|pallet| pallet.name().to_string()).collect()
+    }
+
     async fn fetch_constant<T: serde::de::DeserializeOwned>(
         &self,
         pallet: &str,
@@ -78,7 +87,7 @@ impl StorageTrait for Storage {
     where
         Addr: Address<IsFetchable = Yes> + Sync + 'static,
     {
-        let val = Storage::fetch(self, address).await?;
+        let val = Storage::fetch(self, address).await.map_err(|e| rpc_error_message(&e))?;
         Ok(val)
     }
 
@@ -95,7 +104,8 @@ impl StorageTrait for Storage {
 }
 
 /// Phase enum matching the structure from pallet_election_provider_multi_block
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode, Serialize, Deserialize)]
+#[serde(from = "PhaseJson", into = "PhaseJson")]
 pub enum Phase {
 	/// Nothing is happening, but it might.
 	Off,
@@ -115,6 +125,50 @@ pub enum Phase {
 	Emergency,
 }
 
+// JSON view of `Phase` naming what the inner value counts, e.g. {"type":"Signed","blocks_left":42}
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum PhaseJson {
+	Off,
+	Signed { blocks_left: u32 },
+	SignedValidation { blocks_left: u32 },
+	Unsigned { blocks_left: u32 },
+	Snapshot { pages_left: u32 },
+	Done,
+	Export { last_page: u32 },
+	Emergency,
+}
+
+impl From<Phase> for PhaseJson {
+	fn from(phase: Phase) -> Self {
+		match phase {
+			Phase::Off => PhaseJson::Off,
+			Phase::Signed(blocks_left) => PhaseJson::Signed { blocks_left },
+			Phase::SignedValidation(blocks_left) => PhaseJson::SignedValidation { blocks_left },
+			Phase::Unsigned(blocks_left) => PhaseJson::Unsigned { blocks_left },
+			Phase::Snapshot(pages_left) => PhaseJson::Snapshot { pages_left },
+			Phase::Done => PhaseJson::Done,
+			Phase::Export(last_page) => PhaseJson::Export { last_page },
+			Phase::Emergency => PhaseJson::Emergency,
+		}
+	}
+}
+
+impl From<PhaseJson> for Phase {
+	fn from(phase: PhaseJson) -> Self {
+		match phase {
+			PhaseJson::Off => Phase::Off,
+			PhaseJson::Signed { blocks_left } => Phase::Signed(blocks_left),
+			PhaseJson::SignedValidation { blocks_left } => Phase::SignedValidation(blocks_left),
+			PhaseJson::Unsigned { blocks_left } => Phase::Unsigned(blocks_left),
+			PhaseJson::Snapshot { pages_left } => Phase::Snapshot(pages_left),
+			PhaseJson::Done => Phase::Done,
+			PhaseJson::Export { last_page } => Phase::Export(last_page),
+			PhaseJson::Emergency => Phase::Emergency,
+		}
+	}
+}
+
 impl Phase {
 	/// Check if snapshots are available in this phase.
 	/// 
@@ -143,6 +197,18 @@ impl Phase {
 	}
 }
 
+/// Decode a raw storage value, naming the pallet, item and target type on failure
+/// so that layout changes after a runtime upgrade point at the read that broke.
+pub fn decode_storage<T: Decode>(
+    mut bytes: &[u8],
+    pallet: &str,
+    item: &str,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    T::decode(&mut bytes).map_err(|e| {
+        format!("failed to decode {pallet}::{item} as {}: {e}", std::any::type_name::<T>()).into()
+    })
+}
+
 // Generic voter type for use with MinerConfig
 pub type VoterData<MC> = Voter<AccountId, <MC as MinerConfig>::MaxVotesPerVoter>;
 
@@ -173,28 +239,66 @@ pub struct ListNode {
     pub prev: Option<AccountId>,
     pub next: Option<AccountId>,
 }
+
+/// Value of `Staking::ActiveEra`
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+pub struct ActiveEraInfo {
+    pub index: EraIndex,
+    /// Moment of start expressed as millisecond from `$UNIX_EPOCH`
+    pub start: Option<u64>,
+}
+
+/// Value of `Staking::ErasStakersOverview`, the exposure summary of a validator for an era
+#[derive(Debug, Clone, PartialEq, Eq, Decode, Encode)]
+pub struct PagedExposureMetadata {
+    #[codec(compact)]
+    pub total: Balance,
+    #[codec(compact)]
+    pub own: Balance,
+    pub nominator_count: u32,
+    pub page_count: u32,
+}
 #[automock]
 #[async_trait::async_trait]
 pub trait MultiBlockClientTrait<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync + 'static, S: StorageTrait + From<Storage> + 'static> {
     async fn get_storage(&self, block: Option<Hash>) -> Result<S, Box<dyn std::error::Error + Send + Sync>>;
-    async fn get_block_details(&self, storage: &S, block: Option<Hash>) -> Result<BlockDetails, Box<dyn std::error::Error + Send + Sync>> where S: Clone + 'static;
+    async fn get_block_details(&self, storage: &S, block: Option<Hash>) -> Result<BlockDetails, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_phase(&self, storage: &S) -> Result<Phase, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_round(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_desired_targets(&self, storage: &S, round: u32) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_queued_solution_score(&self, storage: &S, round: u32) -> Result<Option<ElectionScore>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_sorted_scores(&self, storage: &S, round: u32) -> Result<Vec<(AccountId, ElectionScore)>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_block_number(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_min_nominator_bond(&self, storage: &S) -> Result<u128, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_min_validator_bond(&self, storage: &S) -> Result<u128, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_minimum_active_stake(&self, storage: &S) -> Result<Option<u128>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_total_issuance(&self, storage: &S) -> Result<u128, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_nominator_count(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_max_nominators_count(&self, storage: &S) -> Result<Option<u32>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_staking_validator_count(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
     async fn fetch_paged_voter_snapshot(&self, storage: &S, round: u32, page: u32) -> Result<VoterSnapshotPage<MC>, Box<dyn std::error::Error + Send + Sync>>;
     async fn fetch_paged_target_snapshot(&self, storage: &S, round: u32, page: u32) -> Result<TargetSnapshotPage<MC>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_validator_prefs(&self, storage: &S, validator: AccountId) -> Result<ValidatorPrefs, Box<dyn std::error::Error + Send + Sync>>;
+    async fn is_validator(&self, storage: &S, validator: AccountId) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_nominator(&self, storage: &S, nominator: AccountId) -> Result<Option<NominationsLight<AccountId>>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_controller_from_stash(&self, storage: &S, stash: AccountId) -> Result<Option<AccountId>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_stash_from_controller(&self, storage: &S, controller: AccountId) -> Result<Option<AccountId>, Box<dyn std::error::Error + Send + Sync>>;
     async fn ledger(&self, storage: &S, account: AccountId) -> Result<Option<StakingLedger>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn ledgers_batch(&self, storage: &S, stashes: Vec<AccountId>) -> Result<LedgerResults, Box<dyn std::error::Error + Send + Sync>>;
     async fn list_bags(&self, storage: &S, index: u64) -> Result<Option<ListBag>, Box<dyn std::error::Error + Send + Sync>>;
     async fn list_nodes(&self, storage: &S, account: AccountId) -> Result<Option<ListNode>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_active_era(&self, storage: &S) -> Result<Option<ActiveEraInfo>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_bonded_eras(&self, storage: &S) -> Result<Vec<(EraIndex, u32)>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_era_stakers_overview(&self, storage: &S, era: EraIndex, validator: AccountId) -> Result<Option<PagedExposureMetadata>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_eras_validator_prefs(&self, storage: &S, era: EraIndex, validator: AccountId) -> Result<Option<ValidatorPrefs>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_identity_display(&self, storage: &S, account: AccountId) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_super_of(&self, storage: &S, account: AccountId) -> Result<Option<(AccountId, IdentityData)>, Box<dyn std::error::Error + Send + Sync>>;
 }
 
+/// Ledger lookups of `ledgers_batch` by stash: `None` when the stash is not bonded, an error
+/// message when its storage could not be read or decoded.
+pub type LedgerResults = BTreeMap<AccountId, Result<Option<StakingLedger>, String>>;
+
 pub struct MultiBlockClient<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync + 'static, S: StorageTrait + From<Storage> + 'static> {
     client: C,
     _phantom: PhantomData<(MC, S)>,
@@ -216,23 +320,23 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
     // Get block-specific details for a given block.
     async fn get_block_details(&self, storage: &S, block: Option<Hash>) -> Result<BlockDetails, Box<dyn std::error::Error + Send + Sync>> {
 		let phase = self.get_phase(storage).await?;
-        let round = self.get_round(&storage).await?;
-        let desired_targets = match self.get_desired_targets(&storage, round).await {
+        let round = self.get_round(storage).await?;
+        let desired_targets = match self.get_desired_targets(storage, round).await {
             Ok(desired_targets) => desired_targets,
             Err(_) => {
                 // DesiredTargets is only available when snapshot exists.
                 // Fall back to Staking::ValidatorCount which is always available.
-                let validator_count = self.get_staking_validator_count(&storage).await?;
+                let validator_count = self.get_staking_validator_count(storage).await?;
                 tracing::warn!(
-                    "MultiBlockElection::DesiredTargets not found for round {} (phase: {:?}), \
+                    "{}::DesiredTargets not found for round {} (phase: {:?}), \
                     using Staking::ValidatorCount: {}",
-                    round, phase, validator_count
+                    election_pallet(), round, phase, validator_count
                 );
                 validator_count
             }
         };
 		let n_pages = MC::Pages::get();
-		let block_number = self.get_block_number(&storage).await?;
+		let block_number = self.get_block_number(storage).await?;
 		let block_hash = block;
         Ok(BlockDetails { 
 			phase, 
@@ -245,22 +349,25 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
     }
 
     async fn get_phase(&self, storage: &S) -> Result<Phase, Box<dyn std::error::Error + Send + Sync>> {
-        let phase_key = subxt::dynamic::storage("MultiBlockElection", "CurrentPhase", vec![]);
+        let pallet = election_pallet();
+        let phase_key = subxt::dynamic::storage(pallet.as_str(), "CurrentPhase", vec![]);
         let phase = storage.fetch_or_default(&phase_key).await?;
-        let phase: Phase = codec::Decode::decode(&mut phase.encoded())?;
+        let phase: Phase = decode_storage(phase.encoded(), &pallet, "CurrentPhase")?;
         Ok(phase)
     }
 
     async fn get_round(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
-        let storage_key = subxt::dynamic::storage("MultiBlockElection", "Round", vec![]);
+        let pallet = election_pallet();
+        let storage_key = subxt::dynamic::storage(pallet.as_str(), "Round", vec![]);
         let round = storage.fetch_or_default(&storage_key).await?;
-        let round: u32 = codec::Decode::decode(&mut round.encoded())?;
+        let round: u32 = decode_storage(round.encoded(), &pallet, "Round")?;
         Ok(round)
     }
 
     async fn get_desired_targets(&self, storage: &S, round: u32) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let pallet = election_pallet();
         let storage_key = subxt::dynamic::storage(
-            "MultiBlockElection",
+            pallet.as_str(),
             "DesiredTargets",
             vec![Value::from(round)],
         );
@@ -268,16 +375,39 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
             .fetch(&storage_key)
             .await?
             .ok_or("DesiredTargets not found")?;
-        let desired_targets: u32 = codec::Decode::decode(&mut desired_targets_entry.encoded())?;
+        let desired_targets: u32 = decode_storage(desired_targets_entry.encoded(), &pallet, "DesiredTargets")?;
         Ok(desired_targets)
     }
 
+    // Score of the solution the verifier has queued for `round`; `None` until one is verified
+    async fn get_queued_solution_score(&self, storage: &S, round: u32) -> Result<Option<ElectionScore>, Box<dyn std::error::Error + Send + Sync>> {
+        let pallet = election_sub_pallet("Verifier");
+        let storage_key = subxt::dynamic::storage(pallet.as_str(), "QueuedSolutionScore", vec![Value::from(round)]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let score: ElectionScore = decode_storage(entry.encoded(), &pallet, "QueuedSolutionScore")?;
+                Ok(Some(score))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Signed submissions registered for `round` with their claimed scores, as kept by the signed pallet
+    async fn get_sorted_scores(&self, storage: &S, round: u32) -> Result<Vec<(AccountId, ElectionScore)>, Box<dyn std::error::Error + Send + Sync>> {
+        let pallet = election_sub_pallet("Signed");
+        let storage_key = subxt::dynamic::storage(pallet.as_str(), "SortedScores", vec![Value::from(round)]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => decode_storage(entry.encoded(), &pallet, "SortedScores"),
+            None => Ok(Vec::new()),
+        }
+    }
+
     async fn get_block_number(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
         let storage_key = subxt::dynamic::storage("System", "Number", vec![]);
         let block_number_entry = storage.fetch(&storage_key)
             .await?
             .ok_or("Block number not found")?;
-        let block_number: u32 = codec::Decode::decode(&mut block_number_entry.encoded())?;
+        let block_number: u32 = decode_storage(block_number_entry.encoded(), "System", "Number")?;
         Ok(block_number)
     }
 
@@ -286,7 +416,7 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
         let min_nominator_bond_entry = storage.fetch(&storage_key)
             .await?
             .ok_or("MinNominatorBond not found")?;
-        let min_nominator_bond: u128 = codec::Decode::decode(&mut min_nominator_bond_entry.encoded())?;
+        let min_nominator_bond: u128 = decode_storage(min_nominator_bond_entry.encoded(), "Staking", "MinNominatorBond")?;
         Ok(min_nominator_bond)
     }
 
@@ -295,44 +425,81 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
         let min_validator_bond_entry = storage.fetch(&storage_key)
             .await?
             .ok_or("MinValidatorBond not found")?;
-        let min_validator_bond: u128 = codec::Decode::decode(&mut min_validator_bond_entry.encoded())?;
+        let min_validator_bond: u128 = decode_storage(min_validator_bond_entry.encoded(), "Staking", "MinValidatorBond")?;
         Ok(min_validator_bond)
     }
 
+    async fn get_minimum_active_stake(&self, storage: &S) -> Result<Option<u128>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "MinimumActiveStake", vec![]);
+        let minimum_active_stake_entry = storage.fetch(&storage_key).await?;
+        minimum_active_stake_entry
+            .map(|entry| decode_storage::<u128>(entry.encoded(), "Staking", "MinimumActiveStake"))
+            .transpose()
+    }
+
+    // Balances::TotalIssuance, which scales stake down to vote weight once it exceeds u64
+    async fn get_total_issuance(&self, storage: &S) -> Result<u128, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Balances", "TotalIssuance", vec![]);
+        let total_issuance = storage.fetch_or_default(&storage_key).await?;
+        decode_storage(total_issuance.encoded(), "Balances", "TotalIssuance")
+    }
+
+    async fn get_nominator_count(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "CounterForNominators", vec![]);
+        let counter_entry = storage.fetch(&storage_key).await?;
+        let count = counter_entry
+            .map(|entry| decode_storage::<u32>(entry.encoded(), "Staking", "CounterForNominators"))
+            .transpose()?;
+        Ok(count.unwrap_or(0))
+    }
+
+    // Staking::MaxNominatorsCount, None when the chain does not cap the nominators
+    async fn get_max_nominators_count(&self, storage: &S) -> Result<Option<u32>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "MaxNominatorsCount", vec![]);
+        let max_nominators_entry = storage.fetch(&storage_key).await?;
+        max_nominators_entry
+            .map(|entry| decode_storage::<u32>(entry.encoded(), "Staking", "MaxNominatorsCount"))
+            .transpose()
+    }
+
     async fn get_staking_validator_count(&self, storage: &S) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
         let storage_key = subxt::dynamic::storage("Staking", "ValidatorCount", vec![]);
         let validator_count_entry = storage.fetch(&storage_key)
             .await?
             .ok_or("Staking::ValidatorCount not found")?;
-        let validator_count: u32 = codec::Decode::decode(&mut validator_count_entry.encoded())?;
+        let validator_count: u32 = decode_storage(validator_count_entry.encoded(), "Staking", "ValidatorCount")?;
         Ok(validator_count)
     }
 
     async fn fetch_paged_voter_snapshot(&self, storage: &S, round: u32, page: u32) -> Result<VoterSnapshotPage<MC>, Box<dyn std::error::Error + Send + Sync>> {
+        let pallet = election_pallet();
         let storage_key = subxt::dynamic::storage(
-            "MultiBlockElection",
+            pallet.as_str(),
             "PagedVoterSnapshot",
             vec![Value::from(round), Value::from(page)],
         );
         let voter_snapshot_entry = storage.fetch(&storage_key)
-            .await?
+            .await
+            .map_err(|e| rpc_error_message(&e))?
             .ok_or("Voter snapshot not found")?;
 
-        let voter_snapshot: VoterSnapshotPage<MC> = codec::Decode::decode(&mut voter_snapshot_entry.encoded())?;
+        let voter_snapshot: VoterSnapshotPage<MC> = decode_storage(voter_snapshot_entry.encoded(), &pallet, "PagedVoterSnapshot")?;
 
         Ok(voter_snapshot)
     }
 
     async fn fetch_paged_target_snapshot(&self, storage: &S, round: u32, page: u32) -> Result<TargetSnapshotPage<MC>, Box<dyn std::error::Error + Send + Sync>> {
+        let pallet = election_pallet();
         let storage_key = subxt::dynamic::storage(
-            "MultiBlockElection",
+            pallet.as_str(),
             "PagedTargetSnapshot",
             vec![Value::from(round), Value::from(page)],
         );
         let target_snapshot_entry = storage.fetch(&storage_key)
-            .await?
+            .await
+            .map_err(|e| rpc_error_message(&e))?
             .ok_or("Target snapshot not found")?;
-        let target_snapshot: TargetSnapshotPage<MC> = codec::Decode::decode(&mut target_snapshot_entry.encoded())?;
+        let target_snapshot: TargetSnapshotPage<MC> = decode_storage(target_snapshot_entry.encoded(), &pallet, "PagedTargetSnapshot")?;
         Ok(target_snapshot)
     }
     
@@ -342,16 +509,22 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
         let validator_prefs_entry = storage.fetch(&storage_key)
             .await?
             .ok_or("ValidatorPrefs not found")?;
-        let validator_prefs: ValidatorPrefs = codec::Decode::decode(&mut validator_prefs_entry.encoded())?;
+        let validator_prefs: ValidatorPrefs = decode_storage(validator_prefs_entry.encoded(), "Staking", "Validators")?;
         Ok(validator_prefs)
     }
 
+    // Whether the stash currently intends to validate; chilling removes its `Staking::Validators` entry
+    async fn is_validator(&self, storage: &S, validator: AccountId) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "Validators", vec![scale_value::Value::from(validator.encode())]);
+        Ok(storage.fetch(&storage_key).await?.is_some())
+    }
+
     async fn get_nominator(&self, storage: &S, nominator: AccountId) -> Result<Option<NominationsLight<AccountId>>, Box<dyn std::error::Error + Send + Sync>> {
         let encoded_nominator = nominator.encode();
         let storage_key = subxt::dynamic::storage("Staking", "Nominators", vec![scale_value::Value::from(encoded_nominator)]);
         match storage.fetch(&storage_key).await? {
             Some(entry) => {
-                let nominations: NominationsLight<AccountId> = codec::Decode::decode(&mut entry.encoded())?;
+                let nominations: NominationsLight<AccountId> = decode_storage(entry.encoded(), "Staking", "Nominators")?;
                 Ok(Some(nominations))
             }
             None => Ok(None),
@@ -364,31 +537,60 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
         let storage_key = subxt::dynamic::storage("Staking", "Bonded", vec![scale_value::Value::from(encoded_stash)]);
         match storage.fetch(&storage_key).await? {
             Some(entry) => {
-                let controller: AccountId = codec::Decode::decode(&mut entry.encoded())?;
+                let controller: AccountId = decode_storage(entry.encoded(), "Staking", "Bonded")?;
                 Ok(Some(controller))
             }
             None => Ok(None),
         }
     }
 
+    // Stash controlled by a separate controller account: the ledger stored under the controller
+    // names the stash, accepted only when the stash's `Staking::Bonded` entry points back to it
+    async fn get_stash_from_controller(&self, storage: &S, controller: AccountId) -> Result<Option<AccountId>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(ledger) = self.ledger(storage, controller.clone()).await? else {
+            return Ok(None);
+        };
+        if ledger.stash == controller {
+            return Ok(None);
+        }
+        let bonded_controller = self.get_controller_from_stash(storage, ledger.stash.clone()).await?;
+        Ok((bonded_controller == Some(controller)).then_some(ledger.stash))
+    }
+
     async fn ledger(&self, storage: &S, account: AccountId) -> Result<Option<StakingLedger>, Box<dyn std::error::Error + Send + Sync>> {
         let encoded_account = account.encode();
         let storage_key = subxt::dynamic::storage("Staking", "Ledger", vec![scale_value::Value::from(encoded_account)]);
         match storage.fetch(&storage_key).await? {
             Some(entry) => {
-                let ledger: StakingLedger = codec::Decode::decode(&mut entry.encoded())?;
+                let ledger: StakingLedger = decode_storage(entry.encoded(), "Staking", "Ledger")?;
                 Ok(Some(ledger))
             }
             None => Ok(None),
         }
     }
 
+    // Ledgers of `stashes`, read concurrently. Each is keyed by the stash's controller or, when
+    // `Staking::Bonded` has no entry, by the stash itself since controllers now default to the stash
+    async fn ledgers_batch(&self, storage: &S, stashes: Vec<AccountId>) -> Result<LedgerResults, Box<dyn std::error::Error + Send + Sync>> {
+        let ledger_futures = stashes.into_iter().map(|stash| async move {
+            let ledger = async {
+                if let Some(controller) = self.get_controller_from_stash(storage, stash.clone()).await? {
+                    return self.ledger(storage, controller).await;
+                }
+                // Only accept a ledger that belongs to the stash, not one it controls for another account
+                Ok(self.ledger(storage, stash.clone()).await?.filter(|ledger| ledger.stash == stash))
+            }.await;
+            (stash.clone(), ledger.map_err(|e: Box<dyn std::error::Error + Send + Sync>| e.to_string()))
+        });
+        Ok(join_all(ledger_futures).await.into_iter().collect())
+    }
+
     async fn list_bags(&self, storage: &S, index: u64) -> Result<Option<ListBag>, Box<dyn std::error::Error + Send + Sync>> {
         let storage_key = subxt::dynamic::storage("VoterList", "ListBags", vec![Value::from(index)]);
         let bags_entry = storage.fetch(&storage_key).await?;
         match bags_entry {
             Some(entry) => {
-                let bags: ListBag = codec::Decode::decode(&mut entry.encoded())?;
+                let bags: ListBag = decode_storage(entry.encoded(), "VoterList", "ListBags")?;
                 Ok(Some(bags))
             }
             None => Ok(None),
@@ -400,12 +602,158 @@ impl<C: ChainClientTrait + Send + Sync + 'static, MC: MinerConfig + Send + Sync
         let nodes_entry = storage.fetch(&storage_key).await?;
         match nodes_entry {
             Some(entry) => {
-                let nodes: ListNode = codec::Decode::decode(&mut entry.encoded())?;
+                let nodes: ListNode = decode_storage(entry.encoded(), "VoterList", "ListNodes")?;
                 Ok(Some(nodes))
             }
             None => Ok(None),
         }
     }
+
+    async fn get_active_era(&self, storage: &S) -> Result<Option<ActiveEraInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "ActiveEra", vec![]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let active_era: ActiveEraInfo = decode_storage(entry.encoded(), "Staking", "ActiveEra")?;
+                Ok(Some(active_era))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Eras still within the bonding duration with the session each of them started at
+    async fn get_bonded_eras(&self, storage: &S) -> Result<Vec<(EraIndex, u32)>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Staking", "BondedEras", vec![]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => decode_storage(entry.encoded(), "Staking", "BondedEras"),
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn get_era_stakers_overview(&self, storage: &S, era: EraIndex, validator: AccountId) -> Result<Option<PagedExposureMetadata>, Box<dyn std::error::Error + Send + Sync>> {
+        let encoded_validator = validator.encode();
+        let storage_key = subxt::dynamic::storage("Staking", "ErasStakersOverview", vec![Value::from(era), Value::from(encoded_validator)]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let overview: PagedExposureMetadata = decode_storage(entry.encoded(), "Staking", "ErasStakersOverview")?;
+                Ok(Some(overview))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Prefs recorded for a validator of `era`; only validators active in the era have an entry
+    async fn get_eras_validator_prefs(&self, storage: &S, era: EraIndex, validator: AccountId) -> Result<Option<ValidatorPrefs>, Box<dyn std::error::Error + Send + Sync>> {
+        let encoded_validator = validator.encode();
+        let storage_key = subxt::dynamic::storage("Staking", "ErasValidatorPrefs", vec![Value::from(era), Value::from(encoded_validator)]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let prefs: ValidatorPrefs = decode_storage(entry.encoded(), "Staking", "ErasValidatorPrefs")?;
+                Ok(Some(prefs))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Display name of the account's own identity, decoded through the metadata since the
+    // registration layout differs between runtimes
+    async fn get_identity_display(&self, storage: &S, account: AccountId) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Identity", "IdentityOf", vec![Value::from(account.encode())]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let registration = entry.to_value()
+                    .map_err(|e| format!("failed to decode Identity::IdentityOf: {e}"))?;
+                Ok(identity_display(&registration))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Parent identity and sub-account name of an account registered as a sub identity
+    async fn get_super_of(&self, storage: &S, account: AccountId) -> Result<Option<(AccountId, IdentityData)>, Box<dyn std::error::Error + Send + Sync>> {
+        let storage_key = subxt::dynamic::storage("Identity", "SuperOf", vec![Value::from(account.encode())]);
+        match storage.fetch(&storage_key).await? {
+            Some(entry) => {
+                let super_of: (AccountId, IdentityData) = decode_storage(entry.encoded(), "Identity", "SuperOf")?;
+                Ok(Some(super_of))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// `Data` of the Identity pallet: up to 32 raw bytes, or the hash of content kept off chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityData {
+    None,
+    Raw(Vec<u8>),
+    Hash(u8, [u8; 32]),
+}
+
+impl IdentityData {
+    /// The raw bytes as text, when set and valid UTF-8
+    pub fn text(&self) -> Option<String> {
+        match self {
+            IdentityData::Raw(bytes) => String::from_utf8(bytes.clone()).ok().filter(|text| !text.is_empty()),
+            _ => None,
+        }
+    }
+}
+
+// The variant index encodes the length of raw data (1 to 33) or the hash kind (34 to 37)
+impl Decode for IdentityData {
+    fn decode<I: parity_scale_codec::Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        match input.read_byte()? {
+            0 => Ok(IdentityData::None),
+            tag @ 1..=33 => {
+                let mut raw = vec![0u8; tag as usize - 1];
+                input.read(&mut raw)?;
+                Ok(IdentityData::Raw(raw))
+            }
+            tag @ 34..=37 => {
+                let mut hash = [0u8; 32];
+                input.read(&mut hash)?;
+                Ok(IdentityData::Hash(tag, hash))
+            }
+            _ => Err("invalid Identity Data variant".into()),
+        }
+    }
+}
+
+impl Encode for IdentityData {
+    fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+        match self {
+            IdentityData::None => dest.push_byte(0),
+            IdentityData::Raw(raw) => {
+                dest.push_byte(raw.len() as u8 + 1);
+                dest.write(raw);
+            }
+            IdentityData::Hash(tag, hash) => {
+                dest.push_byte(*tag);
+                dest.write(hash);
+            }
+        }
+    }
+}
+
+/// Display name in a decoded `Identity::IdentityOf` value. Recent runtimes store the registration
+/// alongside an optional username, older ones the registration alone, and the fields of the
+/// identity info vary between chains, so the display field is looked up by name.
+pub fn identity_display<T>(registration: &Value<T>) -> Option<String> {
+    let info = registration.at("info").or_else(|| registration.at(0).at("info"))?;
+    let ValueDef::Variant(display) = &info.at("display")?.value else {
+        return None;
+    };
+    if !display.name.starts_with("Raw") {
+        return None;
+    }
+    let bytes = match display.values.at(0).map(|field| &field.value) {
+        Some(ValueDef::Composite(raw)) => raw.values()
+            .map(|byte| byte.as_u128().and_then(|byte| u8::try_from(byte).ok()))
+            .collect::<Option<Vec<u8>>>()?,
+        Some(_) => return None,
+        None => Vec::new(),
+    };
+    String::from_utf8(bytes).ok().filter(|name| !name.is_empty())
 }
 
 /// Block-specific details for a given block.
@@ -421,17 +769,16 @@ pub struct BlockDetails {
 	pub block_hash: Option<Hash>,
 }
 
+// Storage mock for the tests of everything reading chain state through `StorageTrait`
 #[cfg(test)]
-mod tests {
-    use super::*;
+pub mod test_storage {
     use mockall::mock;
-    use mockall::predicate::*;
-    use sp_runtime::Perbill;
-    use subxt::dynamic::DecodedValueThunk;
-    use subxt::metadata::{DecodeWithMetadata};
+    use subxt::storage::Address;
+    use subxt::utils::Yes;
+    use crate::primitives::Storage;
+    use super::StorageTrait;
 
     mock! {
-        #[derive(Debug, Clone)]
         pub DummyStorage {}
 
         #[async_trait::async_trait]
@@ -452,6 +799,7 @@ mod tests {
         }
     }
 
+    // Lets MockDummyStorage stand in wherever storage is built from a client
     impl From<Storage> for MockDummyStorage {
         fn from(_storage: Storage) -> Self {
             MockDummyStorage::new()
@@ -463,6 +811,17 @@ mod tests {
             MockDummyStorage::new()
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_storage::MockDummyStorage;
+    use mockall::predicate::*;
+    use sp_runtime::Perbill;
+    use subxt::dynamic::DecodedValueThunk;
+    use subxt::metadata::{DecodeWithMetadata};
+    use parity_scale_codec as codec;
 
     pub fn fake_value_thunk_from<T: codec::Encode>(val: T) -> DecodedValueThunk {
         let metadata = dummy_metadata();
@@ -498,6 +857,26 @@ mod tests {
         assert!(!Phase::Emergency.has_snapshot());
     }
 
+    #[test]
+    fn test_phase_json_round_trip() {
+        assert_eq!(serde_json::to_string(&Phase::Signed(42)).unwrap(), r#"{"type":"Signed","blocks_left":42}"#);
+        assert_eq!(serde_json::to_string(&Phase::Off).unwrap(), r#"{"type":"Off"}"#);
+        let phases = [
+            Phase::Off,
+            Phase::Signed(1),
+            Phase::SignedValidation(2),
+            Phase::Unsigned(3),
+            Phase::Snapshot(4),
+            Phase::Done,
+            Phase::Export(5),
+            Phase::Emergency,
+        ];
+        for phase in phases {
+            let json = serde_json::to_string(&phase).unwrap();
+            assert_eq!(serde_json::from_str::<Phase>(&json).unwrap(), phase, "{}", json);
+        }
+    }
+
     #[tokio::test]
     async fn test_get_block_details() {
         initialize_runtime_constants();
@@ -577,6 +956,50 @@ mod tests {
         assert_eq!(desired_targets.unwrap(), 10);
     }
 
+    #[tokio::test]
+    async fn test_get_queued_solution_score() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let queued = subxt::dynamic::storage("MultiBlockElectionVerifier", "QueuedSolutionScore", vec![Value::from(10u32)]);
+        let not_queued = subxt::dynamic::storage("MultiBlockElectionVerifier", "QueuedSolutionScore", vec![Value::from(11u32)]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(queued))
+            .returning(|_address| Ok(Some(fake_value_thunk_from(ElectionScore { minimal_stake: 1, sum_stake: 2, sum_stake_squared: 3 }))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(not_queued))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        let score = client.get_queued_solution_score(&dummy_storage, 10).await.unwrap();
+        assert_eq!(score, Some(ElectionScore { minimal_stake: 1, sum_stake: 2, sum_stake_squared: 3 }));
+        assert!(client.get_queued_solution_score(&dummy_storage, 11).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_sorted_scores() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let submitted = subxt::dynamic::storage("MultiBlockElectionSigned", "SortedScores", vec![Value::from(10u32)]);
+        let empty = subxt::dynamic::storage("MultiBlockElectionSigned", "SortedScores", vec![Value::from(11u32)]);
+        let scores = vec![
+            (AccountId::from([1u8; 32]), ElectionScore { minimal_stake: 1, sum_stake: 2, sum_stake_squared: 3 }),
+            (AccountId::from([2u8; 32]), ElectionScore { minimal_stake: 4, sum_stake: 5, sum_stake_squared: 6 }),
+        ];
+        let stored = scores.clone();
+        dummy_storage
+            .expect_fetch()
+            .with(eq(submitted))
+            .returning(move |_address| Ok(Some(fake_value_thunk_from(stored.clone()))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(empty))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_sorted_scores(&dummy_storage, 10).await.unwrap(), scores);
+        assert!(client.get_sorted_scores(&dummy_storage, 11).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_block_number() {
         let mut dummy_storage = MockDummyStorage::new();
@@ -613,6 +1036,101 @@ mod tests {
         assert_eq!(min_nominator_bond.unwrap(), 10);
     }
 
+    #[tokio::test]
+    async fn test_get_minimum_active_stake() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let address = subxt::dynamic::storage("Staking", "MinimumActiveStake", vec![]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address.clone()))
+            .times(1)
+            .returning(|_address| Ok(Some(fake_value_thunk_from(250u128))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_minimum_active_stake(&dummy_storage).await.unwrap(), Some(250));
+        assert_eq!(client.get_minimum_active_stake(&dummy_storage).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_total_issuance() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let address = subxt::dynamic::storage("Balances", "TotalIssuance", vec![]);
+        dummy_storage
+            .expect_fetch_or_default()
+            .with(eq(address))
+            .returning(|_address| Ok(fake_value_thunk_from(u128::from(u64::MAX) * 3)));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_total_issuance(&dummy_storage).await.unwrap(), u128::from(u64::MAX) * 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_nominator_count_and_cap() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let counter = subxt::dynamic::storage("Staking", "CounterForNominators", vec![]);
+        let cap = subxt::dynamic::storage("Staking", "MaxNominatorsCount", vec![]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(counter))
+            .returning(|_address| Ok(Some(fake_value_thunk_from(40_000u32))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(cap.clone()))
+            .times(1)
+            .returning(|_address| Ok(Some(fake_value_thunk_from(50_000u32))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(cap))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_nominator_count(&dummy_storage).await.unwrap(), 40_000);
+        assert_eq!(client.get_max_nominators_count(&dummy_storage).await.unwrap(), Some(50_000));
+        assert_eq!(client.get_max_nominators_count(&dummy_storage).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_is_validator() {
+        let validator = AccountId::from([1u8; 32]);
+        let chilled = AccountId::from([2u8; 32]);
+        let mut dummy_storage = MockDummyStorage::new();
+        let validator_key = subxt::dynamic::storage("Staking", "Validators", vec![scale_value::Value::from(validator.encode())]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(validator_key))
+            .returning(|_address| Ok(Some(fake_value_thunk_from(ValidatorPrefs { commission: Perbill::from_percent(5), blocked: false }))));
+        dummy_storage
+            .expect_fetch()
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert!(client.is_validator(&dummy_storage, validator).await.unwrap());
+        assert!(!client.is_validator(&dummy_storage, chilled).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_get_bonded_eras() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let address = subxt::dynamic::storage("Staking", "BondedEras", vec![]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address.clone()))
+            .times(1)
+            .returning(|_address| Ok(Some(fake_value_thunk_from(vec![(9u32, 50u32), (10u32, 56u32)]))));
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_bonded_eras(&dummy_storage).await.unwrap(), vec![(9, 50), (10, 56)]);
+        assert!(client.get_bonded_eras(&dummy_storage).await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_min_validator_bond() {
         let mut dummy_storage = MockDummyStorage::new();
@@ -670,6 +1188,21 @@ mod tests {
         assert_eq!(voter_snapshot.unwrap(), BoundedVec::<VoterData<PolkadotMinerConfig>, <PolkadotMinerConfig as MinerConfig>::VoterSnapshotPerBlock>::new());
     }
 
+    #[tokio::test]
+    async fn test_fetch_paged_voter_snapshot_response_too_large() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let address = subxt::dynamic::storage("MultiBlockElection", "PagedVoterSnapshot", vec![Value::from(10), Value::from(1)]);
+        initialize_runtime_constants();
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address))
+            .returning(|_address| Err("Rpc error: WebSocket connection error: message too large: len >= 20971521, maximum = 20971520".into()));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        let result = client.fetch_paged_voter_snapshot(&dummy_storage, 10, 1).await;
+        assert_eq!(result.unwrap_err().to_string(), "response exceeded 20MB limit; increase with --max-response-size");
+    }
+
     #[tokio::test]
     async fn test_fetch_paged_target_snapshot() {
         let mut dummy_storage = MockDummyStorage::new();
@@ -716,6 +1249,30 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_get_eras_validator_prefs() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let validator = AccountId::new([0; 32]);
+        let address = subxt::dynamic::storage("Staking", "ErasValidatorPrefs", vec![Value::from(7u32), Value::from(validator.encode())]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address))
+            .returning(|_address| Ok(Some(fake_value_thunk_from(ValidatorPrefs {
+                commission: Perbill::from_percent(3),
+                blocked: true,
+            }))));
+        let other_era = subxt::dynamic::storage("Staking", "ErasValidatorPrefs", vec![Value::from(8u32), Value::from(validator.encode())]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(other_era))
+            .returning(|_address| Ok(None));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        let prefs = client.get_eras_validator_prefs(&dummy_storage, 7, validator.clone()).await.unwrap();
+        assert_eq!(prefs, Some(ValidatorPrefs { commission: Perbill::from_percent(3), blocked: true }));
+        assert!(client.get_eras_validator_prefs(&dummy_storage, 8, validator).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_get_nominator() {
         let mut dummy_storage = MockDummyStorage::new();
@@ -739,7 +1296,7 @@ mod tests {
         let nominator = nominator.unwrap().unwrap();
         assert_eq!(nominator.targets, vec![AccountId::new([0; 32])]);
         assert_eq!(nominator._submitted_in, 10);
-        assert_eq!(nominator.suppressed, false);
+        assert!(!nominator.suppressed);
     }
 
     #[tokio::test]
@@ -791,6 +1348,130 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn test_get_stash_from_controller() {
+        let stash = AccountId::new([1; 32]);
+        let controller = AccountId::new([2; 32]);
+        let stale_controller = AccountId::new([3; 32]);
+        let bonded = |account: &AccountId| subxt::dynamic::storage("Staking", "Bonded", vec![scale_value::Value::from(account.encode())]);
+        let ledger = |account: &AccountId| subxt::dynamic::storage("Staking", "Ledger", vec![scale_value::Value::from(account.encode())]);
+        let stash_ledger = StakingLedger {
+            stash: stash.clone(),
+            total: 10,
+            active: 10,
+            unlocking: Vec::new(),
+            legacy_claimed_rewards: None,
+        };
+
+        let mut dummy_storage = MockDummyStorage::new();
+        for account in [&controller, &stale_controller] {
+            let stash_ledger = stash_ledger.clone();
+            dummy_storage.expect_fetch().with(eq(ledger(account)))
+                .returning(move |_| Ok(Some(fake_value_thunk_from(stash_ledger.clone()))));
+        }
+        let controller_value = controller.clone();
+        dummy_storage.expect_fetch().with(eq(bonded(&stash)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from(controller_value.clone()))));
+        dummy_storage.expect_fetch().with(eq(ledger(&stash))).returning(|_| Ok(None));
+
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_stash_from_controller(&dummy_storage, controller).await.unwrap(), Some(stash.clone()));
+        // The stash is now bonded to another controller, so the leftover ledger does not count
+        assert_eq!(client.get_stash_from_controller(&dummy_storage, stale_controller).await.unwrap(), None);
+        assert_eq!(client.get_stash_from_controller(&dummy_storage, stash).await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_identity_data_codec() {
+        for data in [IdentityData::None, IdentityData::Raw(b"validator".to_vec()), IdentityData::Raw(vec![7; 32]), IdentityData::Hash(35, [9; 32])] {
+            assert_eq!(IdentityData::decode(&mut &data.encode()[..]).unwrap(), data);
+        }
+        assert_eq!(IdentityData::Raw(b"validator".to_vec()).encode()[0], 10);
+        assert_eq!(IdentityData::Raw(b"validator".to_vec()).text(), Some("validator".to_string()));
+        assert_eq!(IdentityData::Raw(Vec::new()).text(), None);
+        assert_eq!(IdentityData::Hash(34, [0; 32]).text(), None);
+        assert!(IdentityData::decode(&mut &[38u8][..]).is_err());
+    }
+
+    #[test]
+    fn test_identity_display() {
+        let registration = |display: Value<()>| Value::named_composite([
+            ("judgements", Value::unnamed_composite(Vec::new())),
+            ("deposit", Value::u128(100)),
+            ("info", Value::named_composite([("display", display)])),
+        ]);
+        let named = registration(Value::unnamed_variant("Raw5", [Value::from_bytes("Alice")]));
+        assert_eq!(identity_display(&named), Some("Alice".to_string()));
+        // Registration stored together with a username
+        let with_username = Value::unnamed_composite([named, Value::unnamed_variant("None", Vec::new())]);
+        assert_eq!(identity_display(&with_username), Some("Alice".to_string()));
+        assert_eq!(identity_display(&registration(Value::unnamed_variant("None", Vec::new()))), None);
+        assert_eq!(identity_display(&registration(Value::unnamed_variant("Raw0", [Value::from_bytes(Vec::<u8>::new())]))), None);
+        assert_eq!(identity_display(&registration(Value::unnamed_variant("BlakeTwo256", [Value::from_bytes([1u8; 32])]))), None);
+        assert_eq!(identity_display(&Value::u128(1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_super_of() {
+        let sub = AccountId::new([1; 32]);
+        let parent = AccountId::new([2; 32]);
+        let super_of = |account: &AccountId| subxt::dynamic::storage("Identity", "SuperOf", vec![Value::from(account.encode())]);
+        let mut dummy_storage = MockDummyStorage::new();
+        let parent_value = parent.clone();
+        dummy_storage.expect_fetch().with(eq(super_of(&sub)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from((parent_value.clone(), IdentityData::Raw(b"node-1".to_vec()))))));
+        dummy_storage.expect_fetch().with(eq(super_of(&parent))).returning(|_| Ok(None));
+
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        assert_eq!(client.get_super_of(&dummy_storage, sub).await.unwrap(), Some((parent.clone(), IdentityData::Raw(b"node-1".to_vec()))));
+        assert_eq!(client.get_super_of(&dummy_storage, parent).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_ledgers_batch() {
+        let bonded_stash = AccountId::new([1; 32]);
+        let controller = AccountId::new([2; 32]);
+        let unbonded_stash = AccountId::new([3; 32]);
+        let foreign_key = AccountId::new([4; 32]);
+        let ledger_of = |stash: &AccountId| StakingLedger {
+            stash: stash.clone(),
+            total: 10,
+            active: 10,
+            unlocking: Vec::new(),
+            legacy_claimed_rewards: None,
+        };
+        let bonded = |account: &AccountId| subxt::dynamic::storage("Staking", "Bonded", vec![scale_value::Value::from(account.encode())]);
+        let ledger = |account: &AccountId| subxt::dynamic::storage("Staking", "Ledger", vec![scale_value::Value::from(account.encode())]);
+
+        let mut dummy_storage = MockDummyStorage::new();
+        let controller_value = controller.clone();
+        dummy_storage.expect_fetch().with(eq(bonded(&bonded_stash)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from(controller_value.clone()))));
+        let bonded_ledger = ledger_of(&bonded_stash);
+        dummy_storage.expect_fetch().with(eq(ledger(&controller)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from(bonded_ledger.clone()))));
+        // No Bonded entry: the ledger is read under the stash itself
+        dummy_storage.expect_fetch().with(eq(bonded(&unbonded_stash))).returning(|_| Ok(None));
+        let unbonded_ledger = ledger_of(&unbonded_stash);
+        dummy_storage.expect_fetch().with(eq(ledger(&unbonded_stash)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from(unbonded_ledger.clone()))));
+        // A ledger stored under the key but owned by another stash is not this account's
+        dummy_storage.expect_fetch().with(eq(bonded(&foreign_key))).returning(|_| Ok(None));
+        let foreign_ledger = ledger_of(&bonded_stash);
+        dummy_storage.expect_fetch().with(eq(ledger(&foreign_key)))
+            .returning(move |_| Ok(Some(fake_value_thunk_from(foreign_ledger.clone()))));
+
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        let ledgers = client.ledgers_batch(&dummy_storage, vec![bonded_stash.clone(), unbonded_stash.clone(), foreign_key.clone()]).await.unwrap();
+        assert_eq!(ledgers.len(), 3);
+        assert_eq!(ledgers[&bonded_stash].clone().unwrap().map(|l| l.stash), Some(bonded_stash));
+        assert_eq!(ledgers[&unbonded_stash].clone().unwrap().map(|l| l.stash), Some(unbonded_stash));
+        assert_eq!(ledgers[&foreign_key], Ok(None));
+    }
+
     #[tokio::test]
     async fn test_ledger() {
         let mut dummy_storage = MockDummyStorage::new();
@@ -804,7 +1485,8 @@ mod tests {
                     stash: AccountId::new([0; 32]),
                     total: 10,
                     active: 10,
-                    unlocking: Vec::new()
+                    unlocking: Vec::new(),
+                    legacy_claimed_rewards: None,
                 };
                 let value = fake_value_thunk_from(ledger);
                 Ok(Some(value))
@@ -820,6 +1502,59 @@ mod tests {
         assert_eq!(ledger.unlocking, unlocking);
     }
 
+    #[test]
+    fn test_ledger_layout_matches_runtime_metadata() {
+        // The Staking::Ledger type of the bundled Asset Hub metadata must consume exactly the
+        // bytes of our encoding, field by field
+        let metadata = dummy_metadata();
+        let ledger_type = metadata.pallet_by_name("Staking").unwrap()
+            .storage().unwrap()
+            .entry_by_name("Ledger").unwrap()
+            .entry_type()
+            .value_ty();
+        let ledger = StakingLedger {
+            stash: AccountId::new([1; 32]),
+            total: 1_000_000_000_000,
+            active: 900_000_000_000,
+            unlocking: vec![UnlockChunk { value: 100_000_000_000, era: 1500 }],
+            legacy_claimed_rewards: None,
+        };
+        let encoded = ledger.encode();
+        let mut bytes: &[u8] = &encoded;
+        let value = scale_value::scale::decode_as_type(&mut bytes, ledger_type, metadata.types()).unwrap();
+        assert!(bytes.is_empty());
+        let ValueDef::Composite(scale_value::Composite::Named(fields)) = value.value else {
+            panic!("Staking::Ledger is not a struct");
+        };
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["stash", "total", "active", "unlocking"]);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_decode_error_names_storage_item() {
+        let mut dummy_storage = MockDummyStorage::new();
+        let account = AccountId::new([0; 32]);
+        let address = subxt::dynamic::storage("Staking", "Ledger", vec![scale_value::Value::from(account.encode())]);
+        dummy_storage
+            .expect_fetch()
+            .with(eq(address.clone()))
+            .returning(|_address| Ok(Some(fake_value_thunk_from(1u8))));
+        let chain_client = MockChainClientTrait::new();
+        let client = MultiBlockClient::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage> {client:chain_client, _phantom: PhantomData };
+        let result = client.ledger(&dummy_storage, account).await;
+        let msg = result.unwrap_err().to_string();
+        assert!(msg.contains("failed to decode Staking::Ledger as"));
+        assert!(msg.contains("StakingLedger"));
+    }
+
+    #[test]
+    fn test_decode_storage() {
+        let value: u32 = decode_storage(&10u32.encode(), "MultiBlockElection", "Round").unwrap();
+        assert_eq!(value, 10);
+        let err = decode_storage::<u32>(&[1u8], "MultiBlockElection", "Round").unwrap_err();
+        assert!(err.to_string().starts_with("failed to decode MultiBlockElection::Round as u32"));
+    }
+
     #[tokio::test]
     async fn test_ledger_none() {
         let mut dummy_storage = MockDummyStorage::new();
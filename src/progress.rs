@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::task_local;
+
+// Stage reached by one running simulation or snapshot
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OperationProgress {
+    pub id: u64,
+    pub operation: &'static str,
+    pub stage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub done: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+    pub elapsed_secs: u64,
+}
+
+// Response of GET /status: "idle", or "busy" with every operation still running
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ProgressStatus {
+    pub state: &'static str,
+    pub operations: Vec<OperationProgress>,
+}
+
+struct RunningOperation {
+    started: Instant,
+    progress: OperationProgress,
+}
+
+/// Progress of the operations a server is running, shared by its request handlers.
+/// Operations are removed as soon as they finish, so an idle server reports no progress.
+#[derive(Clone, Default)]
+pub struct ProgressTracker {
+    next_id: Arc<AtomicU64>,
+    running: Arc<Mutex<BTreeMap<u64, RunningOperation>>>,
+}
+
+// Operation the current task reports to, only present while running under `ProgressTracker::track`
+task_local! {
+    static CURRENT: (ProgressTracker, u64);
+}
+
+// Removes the operation when `track` returns or its future is dropped
+struct Untrack<'a> {
+    tracker: &'a ProgressTracker,
+    id: u64,
+}
+
+impl Drop for Untrack<'_> {
+    fn drop(&mut self) {
+        self.tracker.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl ProgressTracker {
+    /// Run `f` as an operation listed by `status`, reporting the stages it reaches through
+    /// `stage` and `step`
+    pub async fn track<F, R>(&self, operation: &'static str, f: F) -> R
+    where
+        F: std::future::Future<Output = R>,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.running.lock().unwrap().insert(id, RunningOperation {
+            started: Instant::now(),
+            progress: OperationProgress { id, operation, stage: "starting".to_string(), done: None, total: None, elapsed_secs: 0 },
+        });
+        let _untrack = Untrack { tracker: self, id };
+        CURRENT.scope((self.clone(), id), f).await
+    }
+
+    pub fn status(&self) -> ProgressStatus {
+        let operations: Vec<OperationProgress> = self.running.lock().unwrap().values()
+            .map(|running| OperationProgress { elapsed_secs: running.started.elapsed().as_secs(), ..running.progress.clone() })
+            .collect();
+        ProgressStatus {
+            state: if operations.is_empty() { "idle" } else { "busy" },
+            operations,
+        }
+    }
+
+    fn update(&self, id: u64, stage: &str, counts: Option<(u32, u32)>) {
+        if let Some(running) = self.running.lock().unwrap().get_mut(&id) {
+            if running.progress.stage != stage {
+                running.progress.stage = stage.to_string();
+            }
+            running.progress.done = counts.map(|(done, _)| done);
+            running.progress.total = counts.map(|(_, total)| total);
+        }
+    }
+}
+
+/// Report the stage the current operation reached. A no-op outside `ProgressTracker::track`,
+/// so the CLI pays nothing for it
+pub fn stage(stage: &str) {
+    let _ = CURRENT.try_with(|(tracker, id)| tracker.update(*id, stage, None));
+}
+
+/// Report `done` of `total` steps of the current stage, e.g. snapshot pages fetched
+pub fn step(stage: &str, done: u32, total: u32) {
+    let _ = CURRENT.try_with(|(tracker, id)| tracker.update(*id, stage, Some((done, total))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_reports_and_resets() {
+        let tracker = ProgressTracker::default();
+        stage("outside any operation");
+        assert_eq!(tracker.status(), ProgressStatus { state: "idle", operations: vec![] });
+
+        let observer = tracker.clone();
+        let value = tracker.track("simulate", async move {
+            step("fetching voter snapshot page", 12, 32);
+            let status = observer.status();
+            assert_eq!(status.state, "busy");
+            assert_eq!(status.operations.len(), 1);
+            let operation = &status.operations[0];
+            assert_eq!((operation.operation, operation.stage.as_str()), ("simulate", "fetching voter snapshot page"));
+            assert_eq!((operation.done, operation.total), (Some(12), Some(32)));
+
+            stage("mining solution");
+            let operation = observer.status().operations.remove(0);
+            assert_eq!(operation.stage, "mining solution");
+            assert_eq!(operation.done, None);
+            7
+        }).await;
+        assert_eq!(value, 7);
+        assert_eq!(tracker.status().state, "idle");
+    }
+}
@@ -1,16 +1,20 @@
 use jsonrpsee_core::client::ClientT;
 use jsonrpsee_core::traits::ToRpcParams;
 use jsonrpsee_core::ClientError;
-use jsonrpsee_ws_client::{WsClient, WsClientBuilder};
+use jsonrpsee_ws_client::{HeaderMap, HeaderValue, WsClient, WsClientBuilder};
+use futures::future::try_join_all;
 use mockall::automock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use parity_scale_codec::{Decode, Encode};
 use serde_json::to_value;
 
 use sp_core::{H256};
 use sp_core::storage::{StorageKey};
-use sp_core::hashing::{twox_128};
+use sp_core::hashing::{twox_128, twox_64};
 use sp_version::RuntimeVersion;
+use tracing::warn;
 
 use crate::primitives::{AccountId, EraIndex};
 
@@ -23,14 +27,46 @@ pub struct UnlockChunk<Balance> {
     pub era: u32,
 }
 
-#[derive(Debug, Clone, Decode, Encode)]
+/// Bonding ledger as stored under `Staking::Ledger`.
+///
+/// Older staking pallets append `legacy_claimed_rewards` to the encoding while the
+/// async staking pallet on Asset Hub drops it. Both layouts share the same prefix, so
+/// decoding reads the common fields and only then the trailing list if bytes remain.
+/// `legacy_claimed_rewards` is `None` for the newer layout and is re-encoded only when set.
+#[derive(Debug, Clone, PartialEq)]
 pub struct StakingLedger {
     pub stash: AccountId,
-    #[codec(compact)]
     pub total: u128,
-    #[codec(compact)]
     pub active: u128,
     pub unlocking: Vec<UnlockChunk<u128>>,
+    pub legacy_claimed_rewards: Option<Vec<EraIndex>>,
+}
+
+impl Decode for StakingLedger {
+    fn decode<I: parity_scale_codec::Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let stash = AccountId::decode(input)?;
+        let total = <parity_scale_codec::Compact<u128>>::decode(input)?.0;
+        let active = <parity_scale_codec::Compact<u128>>::decode(input)?.0;
+        let unlocking = Vec::<UnlockChunk<u128>>::decode(input)?;
+        let legacy_claimed_rewards = match input.remaining_len()? {
+            Some(0) => None,
+            Some(_) => Some(Vec::<EraIndex>::decode(input)?),
+            None => Vec::<EraIndex>::decode(input).ok(),
+        };
+        Ok(StakingLedger { stash, total, active, unlocking, legacy_claimed_rewards })
+    }
+}
+
+impl Encode for StakingLedger {
+    fn encode_to<T: parity_scale_codec::Output + ?Sized>(&self, dest: &mut T) {
+        self.stash.encode_to(dest);
+        parity_scale_codec::Compact(self.total).encode_to(dest);
+        parity_scale_codec::Compact(self.active).encode_to(dest);
+        self.unlocking.encode_to(dest);
+        if let Some(claimed) = &self.legacy_claimed_rewards {
+            claimed.encode_to(dest);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -62,6 +98,34 @@ impl RpcClient for WsClient {
     }
 }
 
+/// Several connections to the same node, used in turn.
+///
+/// Every storage request carries its block hash, so the answer does not depend on
+/// which connection served it.
+pub struct RpcPool<C: RpcClient> {
+    clients: Vec<C>,
+    next: AtomicUsize,
+}
+
+impl<C: RpcClient> RpcPool<C> {
+    pub fn new(clients: Vec<C>) -> Self {
+        assert!(!clients.is_empty(), "RpcPool needs at least one connection");
+        RpcPool { clients, next: AtomicUsize::new(0) }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: RpcClient> RpcClient for RpcPool<C> {
+    async fn rpc_request<T, P>(&self, method: &str, params: P) -> Result<T, ClientError>
+    where
+        T: serde::de::DeserializeOwned + 'static,
+        P: ToRpcParams + Send + 'static
+    {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len();
+        self.clients[index].rpc_request(method, params).await
+    }
+}
+
 #[automock]
 #[async_trait::async_trait]
 pub trait RawClientTrait<C: RpcClient + Send + Sync + 'static> {
@@ -72,6 +136,24 @@ pub trait RawClientTrait<C: RpcClient + Send + Sync + 'static> {
     async fn get_validators(&self, at: Option<H256>) -> Result<Vec<AccountId>, Box<dyn std::error::Error + Send + Sync>>;
     //async fn get_nominators(&self, at: Option<H256>) -> Result<Vec<AccountId>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_all_list_bags(&self, at: Option<H256>) -> Result<Vec<u64>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_era_validators(&self, era: EraIndex, at: Option<H256>) -> Result<Vec<AccountId>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_bonded_pool_ids(&self, at: Option<H256>) -> Result<Vec<u32>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_block_hash(&self, number: u32) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_block_number(&self, at: Option<H256>) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_timestamp(&self, at: Option<H256>) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_metadata_versions(&self, at: Option<H256>) -> Result<Vec<u32>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// Sent as User-Agent on every RPC connection unless --rpc-user-agent overrides it
+pub const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+// Websocket handshake headers identifying the tool to the RPC provider
+pub fn user_agent_headers(user_agent: &str) -> Result<HeaderMap, Box<dyn std::error::Error + Send + Sync>> {
+    let value = HeaderValue::from_str(user_agent)
+        .map_err(|e| format!("Invalid RPC user agent '{}': {}", user_agent, e))?;
+    let mut headers = HeaderMap::new();
+    headers.insert("user-agent", value);
+    Ok(headers)
 }
 
 #[derive(Clone, Copy)]
@@ -79,13 +161,18 @@ pub struct RawClient<C: RpcClient> {
     client: C,
 }
 
-impl RawClient<WsClient> {
-    pub async fn new(node_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let client = WsClientBuilder::default()
-            .max_response_size(20 * 1024 * 1024)     // 20MB
-            .build(node_url)
-            .await?;
-        Ok(RawClient { client })
+impl RawClient<RpcPool<WsClient>> {
+    // Open `connections` websocket connections and spread requests across them, each
+    // accepting responses of up to `max_response_size_mb` megabytes
+    pub async fn new(node_url: &str, connections: usize, max_response_size_mb: u32, user_agent: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let headers = user_agent_headers(user_agent).map_err(|e| e.to_string())?;
+        let clients = try_join_all((0..connections.max(1)).map(|_| {
+            WsClientBuilder::default()
+                .max_response_size(max_response_size_mb.saturating_mul(1024 * 1024))
+                .set_headers(headers.clone())
+                .build(node_url)
+        })).await?;
+        Ok(RawClient { client: RpcPool::new(clients) })
     }
 
 }
@@ -104,7 +191,14 @@ impl<C: RpcClient> RawClient<C> {
         StorageKey(self.module_prefix(module, storage))
     }
     
-    fn extract_key<T: Decode>(&self, key: &StorageKey, prefix_len: usize) -> Option<T> {
+    // Decode the map key following `prefix` and its 8-byte hash; keys outside the prefix are skipped
+    // since some providers return a foreign key at the page boundary
+    fn extract_key<T: Decode>(&self, key: &StorageKey, prefix: &StorageKey) -> Option<T> {
+        if !key.0.starts_with(&prefix.0) {
+            warn!("Skipping storage key 0x{} outside prefix 0x{}", hex::encode(&key.0), hex::encode(&prefix.0));
+            return None;
+        }
+        let prefix_len = prefix.0.len();
         if key.0.len() > prefix_len + 8 {
             let mut bytes = &key.0[prefix_len + 8..];
             T::decode(&mut bytes).ok()
@@ -114,6 +208,27 @@ impl<C: RpcClient> RawClient<C> {
     }
 }
 
+// Retries of a single failed keys page and the delay before the first retry
+const KEYS_PAGE_RETRIES: u32 = 3;
+const KEYS_PAGE_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+// A response over the size cap closes the connection and the RPC clients only report a generic
+// transport error, so spot the websocket message and point at the flag that raises the cap
+pub fn rpc_error_message(error: &impl std::fmt::Display) -> String {
+    let message = error.to_string();
+    if !message.contains("message too large") {
+        return message;
+    }
+    let limit = message
+        .split("maximum = ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|bytes| bytes.parse::<u64>().ok())
+        .map(|bytes| format!("{}MB", bytes / (1024 * 1024)))
+        .unwrap_or_else(|| "size".to_string());
+    format!("response exceeded {} limit; increase with --max-response-size", limit)
+}
+
 #[async_trait::async_trait]
 impl<C: RpcClient + Send + Sync + 'static> RawClientTrait<C> for RawClient<C> {
     async fn get_runtime_version(&self) -> Result<RuntimeVersion, Box<dyn std::error::Error>> {
@@ -139,17 +254,32 @@ impl<C: RpcClient + Send + Sync + 'static> RawClientTrait<C> for RawClient<C> {
             .rpc_request("state_getKeysPaged", (serialized_prefix, count, serialized_start, at_val))
             .await;
         
-        keys.map_err(|e| format!("Error getting keys paged: {}", e).into())
+        keys.map_err(|e| format!("Error getting keys paged: {}", rpc_error_message(&e)).into())
     }
 
     /// Get all keys from a storage map by paginating through results
+    ///
+    /// A failed page is retried from the same start key with a doubling delay, so a transient
+    /// error does not discard the pages already read.
     async fn get_all_keys(&self, prefix: StorageKey, at: Option<H256>) -> Result<Vec<StorageKey>, Box<dyn std::error::Error + Send + Sync>> {
         let mut all_keys = Vec::new();
         let mut start_key: Option<StorageKey> = None;
         let page_size = 1000u32;
 
         loop {
-            let keys = self.get_keys_paged(prefix.clone(), page_size, start_key.clone(), at).await?;
+            let mut attempt = 0;
+            let keys = loop {
+                match self.get_keys_paged(prefix.clone(), page_size, start_key.clone(), at).await {
+                    Ok(keys) => break keys,
+                    Err(e) if attempt < KEYS_PAGE_RETRIES => {
+                        let delay = KEYS_PAGE_RETRY_DELAY * 2u32.pow(attempt);
+                        warn!("Retrying keys page after {} keys in {:?}: {}", all_keys.len(), delay, e);
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
             
             if keys.is_empty() {
                 break;
@@ -174,7 +304,7 @@ impl<C: RpcClient + Send + Sync + 'static> RawClientTrait<C> for RawClient<C> {
         let keys = self.get_all_keys(prefix_key.clone(), at).await?;
         let mut accounts = Vec::new();
         for key in keys {
-            if let Some(account) = self.extract_key::<AccountId>(&key, prefix_key.0.len()) {
+            if let Some(account) = self.extract_key::<AccountId>(&key, &prefix_key) {
                 accounts.push(account);
             }
         }
@@ -197,12 +327,83 @@ impl<C: RpcClient + Send + Sync + 'static> RawClientTrait<C> for RawClient<C> {
         let keys = self.get_all_keys(prefix_key.clone(), at).await?;
         let mut list_bags = Vec::new();
         for key in keys {
-            if let Some(list_bag) = self.extract_key::<u64>(&key, prefix_key.0.len()) {
+            if let Some(list_bag) = self.extract_key::<u64>(&key, &prefix_key) {
                 list_bags.push(list_bag);
             }
         }
         Ok(list_bags)
     }
+
+    // Get all validators exposed in a given era by enumerating the second key of Staking.ErasStakersOverview
+    async fn get_era_validators(&self, era: EraIndex, at: Option<H256>) -> Result<Vec<AccountId>, Box<dyn std::error::Error + Send + Sync>> {
+        let encoded_era = era.encode();
+        let mut prefix = self.module_prefix(b"Staking", b"ErasStakersOverview");
+        prefix.extend_from_slice(&twox_64(&encoded_era));
+        prefix.extend_from_slice(&encoded_era);
+        let prefix_key = StorageKey(prefix);
+        let keys = self.get_all_keys(prefix_key.clone(), at).await?;
+        let mut validators = Vec::new();
+        for key in keys {
+            if let Some(validator) = self.extract_key::<AccountId>(&key, &prefix_key) {
+                validators.push(validator);
+            }
+        }
+        Ok(validators)
+    }
+
+    // Get all nomination pool ids by enumerating NominationPools.BondedPools
+    async fn get_bonded_pool_ids(&self, at: Option<H256>) -> Result<Vec<u32>, Box<dyn std::error::Error + Send + Sync>> {
+        let prefix_key = self.value_key(b"NominationPools", b"BondedPools");
+        let keys = self.get_all_keys(prefix_key.clone(), at).await?;
+        let mut pool_ids = Vec::new();
+        for key in keys {
+            if let Some(pool_id) = self.extract_key::<u32>(&key, &prefix_key) {
+                pool_ids.push(pool_id);
+            }
+        }
+        Ok(pool_ids)
+    }
+
+    async fn get_block_hash(&self, number: u32) -> Result<Option<H256>, Box<dyn std::error::Error + Send + Sync>> {
+        let hash: Result<Option<H256>, ClientError> = self.client
+            .rpc_request("chain_getBlockHash", (number,))
+            .await;
+        hash.map_err(|e| format!("Error getting hash of block {}: {}", number, rpc_error_message(&e)).into())
+    }
+
+    async fn get_block_number(&self, at: Option<H256>) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let at_val = to_value(at).expect("Block hash serialization infallible");
+        let header: serde_json::Value = self.client
+            .rpc_request("chain_getHeader", (at_val,))
+            .await
+            .map_err(|e| format!("Error getting header: {}", rpc_error_message(&e)))?;
+        // The header number is a hex string
+        let number = header["number"].as_str().ok_or("Header has no number")?;
+        u32::from_str_radix(number.trim_start_matches("0x"), 16)
+            .map_err(|e| format!("Invalid header number {}: {}", number, e).into())
+    }
+
+    // Timestamp::Now in milliseconds since the Unix epoch; unset at genesis
+    async fn get_timestamp(&self, at: Option<H256>) -> Result<Option<u64>, Box<dyn std::error::Error + Send + Sync>> {
+        let key = to_value(self.value_key(b"Timestamp", b"Now")).expect("StorageKey serialization infallible");
+        let at_val = to_value(at).expect("Block hash serialization infallible");
+        let data: Option<sp_core::Bytes> = self.client
+            .rpc_request("state_getStorage", (key, at_val))
+            .await
+            .map_err(|e| format!("Error getting Timestamp::Now: {}", rpc_error_message(&e)))?;
+        data.map(|bytes| u64::decode(&mut &bytes[..]).map_err(|e| format!("Error decoding Timestamp::Now: {}", e).into()))
+            .transpose()
+    }
+
+    // Metadata versions the runtime can return, from the Metadata_metadata_versions runtime API
+    async fn get_metadata_versions(&self, at: Option<H256>) -> Result<Vec<u32>, Box<dyn std::error::Error + Send + Sync>> {
+        let at_val = to_value(at).expect("Block hash serialization infallible");
+        let data: sp_core::Bytes = self.client
+            .rpc_request("state_call", ("Metadata_metadata_versions", "0x", at_val))
+            .await
+            .map_err(|e| format!("Error getting metadata versions: {}", rpc_error_message(&e)))?;
+        Vec::<u32>::decode(&mut &data[..]).map_err(|e| format!("Error decoding metadata versions: {}", e).into())
+    }
 }
 
 #[cfg(test)]
@@ -212,6 +413,14 @@ mod tests {
     use mockall::predicate::*;
     use serde_json::Value;
 
+    #[test]
+    fn test_user_agent_headers() {
+        let headers = user_agent_headers(DEFAULT_USER_AGENT).unwrap();
+        assert_eq!(headers["user-agent"], DEFAULT_USER_AGENT);
+        assert!(DEFAULT_USER_AGENT.starts_with("offline-election-tool/"));
+        assert!(user_agent_headers("bad\nagent").is_err());
+    }
+
     #[tokio::test]
     async fn test_module_prefix() {
         let mock_client = MockRpcClient::new();
@@ -269,6 +478,60 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Error getting runtime version"));
     }
 
+    #[tokio::test]
+    async fn test_rpc_pool_round_robin() {
+        let runtime_version = RuntimeVersion {
+            spec_name: "test".into(),
+            impl_name: "test".into(),
+            authoring_version: 1,
+            spec_version: 1,
+            impl_version: 1,
+            apis: vec![].into(),
+            transaction_version: 1,
+            system_version: 1,
+        };
+        let clients: Vec<MockRpcClient> = (0..2).map(|_| {
+            let mut mock_client = MockRpcClient::new();
+            let runtime_version = runtime_version.clone();
+            mock_client
+                .expect_rpc_request::<RuntimeVersion, (Option<()>,)>()
+                .times(2)
+                .returning(move |_, _| Ok(runtime_version.clone()));
+            mock_client
+        }).collect();
+        let client = RawClient { client: RpcPool::new(clients) };
+        for _ in 0..4 {
+            assert_eq!(client.get_runtime_version().await.unwrap(), runtime_version);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_block_number_and_timestamp() {
+        let mut mock_client = MockRpcClient::new();
+        mock_client
+            .expect_rpc_request::<Value, (Value,)>()
+            .with(eq("chain_getHeader"), mockall::predicate::always())
+            .returning(|_, _| Ok(serde_json::json!({ "number": "0x1a2b" })));
+        mock_client
+            .expect_rpc_request::<Option<sp_core::Bytes>, (Value, Value)>()
+            .with(eq("state_getStorage"), mockall::predicate::always())
+            .returning(|_, _| Ok(Some(sp_core::Bytes(1_700_000_000_000u64.encode()))));
+        let client = RawClient { client: mock_client };
+        assert_eq!(client.get_block_number(None).await.unwrap(), 0x1a2b);
+        assert_eq!(client.get_timestamp(None).await.unwrap(), Some(1_700_000_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_get_metadata_versions() {
+        let mut mock_client = MockRpcClient::new();
+        mock_client
+            .expect_rpc_request::<sp_core::Bytes, (&str, &str, Value)>()
+            .with(eq("state_call"), mockall::predicate::always())
+            .returning(|_, _| Ok(sp_core::Bytes(vec![14u32, 15, 16].encode())));
+        let client = RawClient { client: mock_client };
+        assert_eq!(client.get_metadata_versions(None).await.unwrap(), vec![14, 15, 16]);
+    }
+
     #[tokio::test]
     async fn test_get_keys_paged() {
         let mut mock_client = MockRpcClient::new();
@@ -308,6 +571,50 @@ mod tests {
         assert_eq!(result.unwrap().len(), 1500);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_get_all_keys_retries_failed_page() {
+        let mut mock_client = MockRpcClient::new();
+        let keys_page_1: Vec<StorageKey> = (0..1000).map(|i| StorageKey(vec![i as u8; 32])).collect();
+        let keys_page_2: Vec<StorageKey> = (1000..1500).map(|i| StorageKey(vec![i as u8; 32])).collect();
+        let resume_key = to_value(keys_page_1.last().cloned()).unwrap();
+        let mut call_count = 0;
+        mock_client
+            .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
+            .with(eq("state_getKeysPaged"), mockall::predicate::always())
+            .returning(move |_, (_, _, start_key, _)| {
+                call_count += 1;
+                match call_count {
+                    1 => Ok(keys_page_1.clone()),
+                    // The second page fails once and is asked again from the same start key
+                    2 => {
+                        assert_eq!(start_key.as_ref(), Some(&resume_key));
+                        Err(ClientError::RequestTimeout)
+                    }
+                    _ => {
+                        assert_eq!(start_key.as_ref(), Some(&resume_key));
+                        Ok(keys_page_2.clone())
+                    }
+                }
+            })
+            .times(3);
+        let client = RawClient { client: mock_client };
+        let result = client.get_all_keys(StorageKey(vec![1u8; 32]), None).await;
+        assert_eq!(result.unwrap().len(), 1500);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_get_all_keys_gives_up_after_retries() {
+        let mut mock_client = MockRpcClient::new();
+        mock_client
+            .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
+            .with(eq("state_getKeysPaged"), mockall::predicate::always())
+            .returning(|_, _| Err(ClientError::RequestTimeout))
+            .times(KEYS_PAGE_RETRIES as usize + 1);
+        let client = RawClient { client: mock_client };
+        let result = client.get_all_keys(StorageKey(vec![1u8; 32]), None).await;
+        assert!(result.unwrap_err().to_string().contains("Request timeout"));
+    }
+
     #[tokio::test]
     async fn test_get_all_keys_partial_page() {
         let mut mock_client = MockRpcClient::new();
@@ -329,7 +636,7 @@ mod tests {
         let mut key_bytes = vec![0u8; 32 + 8 + 32];
         key_bytes[32 + 8..].copy_from_slice(&[1u8; 32]);
         let key = StorageKey(key_bytes);
-        let result = client.extract_key::<AccountId>(&key, 32);
+        let result = client.extract_key::<AccountId>(&key, &StorageKey(vec![0u8; 32]));
         assert_eq!(result, Some(AccountId::from([1u8; 32])));
     }
 
@@ -339,14 +646,23 @@ mod tests {
         let client = RawClient { client: mock_client };
         let key_bytes = vec![0u8; 32 + 8];
         let key = StorageKey(key_bytes);
-        let result = client.extract_key::<AccountId>(&key, 32);
+        let result = client.extract_key::<AccountId>(&key, &StorageKey(vec![0u8; 32]));
         assert_eq!(result, None);
     }
 
+    // Key of a Twox64Concat map entry under the given prefix
+    fn map_key(prefix: &StorageKey, key: &[u8]) -> StorageKey {
+        let mut bytes = prefix.0.clone();
+        bytes.extend_from_slice(&[0u8; 8]); // twox64 hash placeholder
+        bytes.extend_from_slice(key);
+        StorageKey(bytes)
+    }
+
     #[tokio::test]
     async fn test_enumerate_accounts() {
         let mut mock_client = MockRpcClient::new();
-        let keys = vec![StorageKey(vec![0u8; 32 + 8 + 32])];
+        let prefix_key = RawClient { client: MockRpcClient::new() }.value_key(b"Staking", b"Validators");
+        let keys = vec![map_key(&prefix_key, &[0u8; 32])];
         mock_client
             .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
             .with(eq("state_getKeysPaged"), mockall::predicate::always())
@@ -357,12 +673,27 @@ mod tests {
         assert_eq!(accounts.unwrap(), vec![AccountId::from([0u8; 32])]);
     }
 
+    #[tokio::test]
+    async fn test_enumerate_accounts_skips_foreign_keys() {
+        let mut mock_client = MockRpcClient::new();
+        let prefix_key = RawClient { client: MockRpcClient::new() }.value_key(b"Staking", b"Validators");
+        let foreign_prefix = RawClient { client: MockRpcClient::new() }.value_key(b"Staking", b"Nominators");
+        let keys = vec![map_key(&prefix_key, &[1u8; 32]), map_key(&foreign_prefix, &[2u8; 32])];
+        mock_client
+            .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
+            .with(eq("state_getKeysPaged"), mockall::predicate::always())
+            .returning(move |_, _| Ok(keys.clone()));
+        let client = RawClient { client: mock_client };
+        let accounts = client.enumerate_accounts(b"Staking", b"Validators", None).await.unwrap();
+        assert_eq!(accounts, vec![AccountId::from([1u8; 32])]);
+    }
+
     #[tokio::test]
     async fn test_get_validators() {
         let mock_client = MockRpcClient::new();
         let mut client = RawClient { client: mock_client };
-        let keys = vec![StorageKey(vec![0u8; 32 + 8 + 32])];
         let prefix_key = client.value_key(b"Staking", b"Validators");
+        let keys = vec![map_key(&prefix_key, &[0u8; 32])];
         let serialized_prefix = to_value(prefix_key).unwrap();
         let at = to_value(None::<H256>).unwrap();
         let params: (Value, u32, Option<Value>, Value) = (serialized_prefix.clone(), 1000, None, at);
@@ -379,8 +710,8 @@ mod tests {
     async fn test_get_nominators() {
         let mock_client = MockRpcClient::new();
         let mut client = RawClient { client: mock_client };
-        let keys = vec![StorageKey(vec![0u8; 32 + 8 + 32])];
         let prefix_key = client.value_key(b"Staking", b"Nominators");
+        let keys = vec![map_key(&prefix_key, &[0u8; 32])];
         let serialized_prefix = to_value(prefix_key).unwrap();
         let at = to_value(None::<H256>).unwrap();
         let params: (Value, u32, Option<Value>, Value) = (serialized_prefix.clone(), 1000, None, at);
@@ -430,6 +761,33 @@ mod tests {
         assert_eq!(list_bags[1], bag_threshold_2);
     }
 
+    #[tokio::test]
+    async fn test_get_era_validators() {
+        let mock_client = MockRpcClient::new();
+        let mut client = RawClient { client: mock_client };
+
+        let era: EraIndex = 10;
+        let mut prefix = client.module_prefix(b"Staking", b"ErasStakersOverview");
+        prefix.extend_from_slice(&twox_64(&era.encode()));
+        prefix.extend_from_slice(&era.encode());
+
+        let mut key = prefix.clone();
+        key.extend_from_slice(&[0u8; 8]); // twox64 hash placeholder
+        key.extend_from_slice(&[7u8; 32]);
+        let keys = vec![StorageKey(key)];
+
+        let serialized_prefix = to_value(StorageKey(prefix)).unwrap();
+        let at = to_value(None::<H256>).unwrap();
+        let params: (Value, u32, Option<Value>, Value) = (serialized_prefix, 1000, None, at);
+        client.client
+            .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
+            .with(eq("state_getKeysPaged"), eq(params))
+            .returning(move |_, _| Ok(keys.clone()));
+
+        let result = client.get_era_validators(era, None).await;
+        assert_eq!(result.unwrap(), vec![AccountId::from([7u8; 32])]);
+    }
+
     #[tokio::test]
     async fn test_get_keys_paged_error() {
         let mut mock_client = MockRpcClient::new();
@@ -442,7 +800,115 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Error getting keys paged"));
     }
-}
 
+    #[tokio::test]
+    async fn test_get_keys_paged_response_too_large() {
+        let mut mock_client = MockRpcClient::new();
+        mock_client
+            .expect_rpc_request::<Vec<StorageKey>, (Value, u32, Option<Value>, Value)>()
+            .with(eq("state_getKeysPaged"), mockall::predicate::always())
+            .returning(|_, _| Err(ClientError::Transport("WebSocket connection error: message too large: len >= 20971521, maximum = 20971520".into())));
+        let client = RawClient { client: mock_client };
+        let result = client.get_keys_paged(StorageKey(vec![1u8; 32]), 100, None, None).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Error getting keys paged: response exceeded 20MB limit; increase with --max-response-size"
+        );
+    }
 
+    #[test]
+    fn test_staking_ledger_decode_current_layout() {
+        let ledger = StakingLedger {
+            stash: AccountId::from([1u8; 32]),
+            total: 100,
+            active: 80,
+            unlocking: vec![UnlockChunk { value: 20, era: 5 }],
+            legacy_claimed_rewards: None,
+        };
+        let encoded = ledger.encode();
+        let decoded = StakingLedger::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded, ledger);
+    }
 
+    #[test]
+    fn test_staking_ledger_decode_legacy_layout() {
+        // Older runtimes append the claimed reward eras after `unlocking`
+        let mut encoded = AccountId::from([1u8; 32]).encode();
+        encoded.extend(parity_scale_codec::Compact(100u128).encode());
+        encoded.extend(parity_scale_codec::Compact(100u128).encode());
+        encoded.extend(Vec::<UnlockChunk<u128>>::new().encode());
+        encoded.extend(vec![3u32, 4u32].encode());
+
+        let decoded = StakingLedger::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded.active, 100);
+        assert_eq!(decoded.legacy_claimed_rewards, Some(vec![3, 4]));
+        assert_eq!(decoded.encode(), encoded);
+    }
+
+    // Ledger values spelled out byte by byte rather than produced by `Encode`: a 0x11.. stash,
+    // compact total and active, the unlocking chunks (compact value and era) and, in the legacy
+    // layout only, the claimed reward eras as u32s
+    const CURRENT_LAYOUT_LEDGER: &str = concat!(
+        "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "0b00c0bcf7e90a", "0b00b01723010a", "04", "070010a5d4e8", "7117",
+    );
+    const LEGACY_LAYOUT_LEDGER: &str = concat!(
+        "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "0b00c0bcf7e90a", "0b00c0bcf7e90a", "00", "08", "db050000", "dc050000",
+    );
+
+    #[test]
+    fn test_staking_ledger_decode_fixtures() {
+        use parity_scale_codec::DecodeAll;
+
+        let bytes = hex::decode(CURRENT_LAYOUT_LEDGER.trim_start_matches("0x")).unwrap();
+        let ledger = StakingLedger::decode_all(&mut &bytes[..]).unwrap();
+        assert_eq!(ledger, StakingLedger {
+            stash: AccountId::from([0x11u8; 32]),
+            total: 12_000_000_000_000,
+            active: 11_000_000_000_000,
+            unlocking: vec![UnlockChunk { value: 1_000_000_000_000, era: 1500 }],
+            legacy_claimed_rewards: None,
+        });
+        assert_eq!(ledger.encode(), bytes);
+
+        let bytes = hex::decode(LEGACY_LAYOUT_LEDGER.trim_start_matches("0x")).unwrap();
+        let ledger = StakingLedger::decode_all(&mut &bytes[..]).unwrap();
+        assert_eq!(ledger, StakingLedger {
+            stash: AccountId::from([0x11u8; 32]),
+            total: 12_000_000_000_000,
+            active: 12_000_000_000_000,
+            unlocking: Vec::new(),
+            legacy_claimed_rewards: Some(vec![1499, 1500]),
+        });
+        assert_eq!(ledger.encode(), bytes);
+    }
+
+    // Decodes real Staking::Ledger entries, so it needs a node: run with `cargo test -- --ignored`,
+    // against Polkadot Asset Hub or the node given in LEDGER_TEST_RPC
+    #[tokio::test]
+    #[ignore]
+    async fn test_staking_ledger_decode_chain_entries() {
+        use parity_scale_codec::DecodeAll;
+
+        let endpoint = std::env::var("LEDGER_TEST_RPC")
+            .unwrap_or_else(|_| "wss://polkadot-asset-hub-rpc.polkadot.io".to_string());
+        let raw_client = RawClient::new(&endpoint, 1, 64, DEFAULT_USER_AGENT).await.unwrap();
+        let block = raw_client.get_block_hash(raw_client.get_block_number(None).await.unwrap()).await.unwrap();
+        let prefix = StorageKey([twox_128(b"Staking"), twox_128(b"Ledger")].concat());
+        let keys = raw_client.get_keys_paged(prefix, 100, None, block).await.unwrap();
+        assert!(!keys.is_empty(), "no Staking::Ledger entries at {} on {}", block.unwrap(), endpoint);
+
+        for key in keys {
+            let key_value = to_value(&key).unwrap();
+            let at_value = to_value(block).unwrap();
+            let data: Option<sp_core::Bytes> = raw_client.client.rpc_request("state_getStorage", (key_value, at_value)).await.unwrap();
+            let bytes = data.unwrap().0;
+            // The whole value is consumed, with or without the legacy claimed rewards
+            let ledger = StakingLedger::decode_all(&mut &bytes[..])
+                .unwrap_or_else(|e| panic!("ledger 0x{} at {} does not decode: {}", hex::encode(&bytes), block.unwrap(), e));
+            assert!(ledger.active <= ledger.total);
+            assert_eq!(ledger.encode(), bytes);
+        }
+    }
+}
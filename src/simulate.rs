@@ -1,22 +1,30 @@
-use std::collections::{BTreeMap};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
 use pallet_staking::ValidatorPrefs;
 use serde::Deserialize;
 use sp_core::{crypto::Ss58Codec, Get, H256};
-use sp_npos_elections::Support;
+use sp_npos_elections::{assignment_ratio_to_staked_normalized, Support};
+use frame_election_provider_support::NposSolution;
 use pallet_election_provider_multi_block::{unsigned::miner::{BaseMiner, MineInput}};
-use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
+use pallet_election_provider_multi_block::unsigned::miner::{MinerConfig, PageSupportsOfMiner};
 use futures::future::join_all;
 use sp_runtime::Perbill;
-use tracing::info;
+use tracing::{info, warn};
 use frame_support::BoundedVec;
+use parity_scale_codec::Encode;
 use mockall::automock;
-use crate::{miner_config, models::StakingStats, multi_block_state_client::{MultiBlockClientTrait, StorageTrait, VoterData, VoterSnapshotPage}, primitives::Storage, snapshot::SnapshotService};
+use rand::{rngs::StdRng, SeedableRng};
+use crate::{budget, cache::ValidatorPrefsCache, explain, progress, miner_config::{self, to_ss58}, models::{ElectionScore, ExcludedRole, ReconstructionCounts, Snapshot, SnapshotNominator, SnapshotValidator, StakingConfig, ExclusionReason, NominatorUnusedStake, SolutionLength, StakePercentiles, StakingStats, UnelectedCandidate, UnelectedReason, WastedVoter}, multi_block_state_client::{BlockDetails, MultiBlockClientTrait, StorageTrait, TargetSnapshotPage, VoterData, VoterSnapshotPage}, primitives::Storage, snapshot::{self, SnapshotService}};
 
-use crate::{models::{Validator, ValidatorNomination, SimulationResult, RunParameters}, multi_block_state_client::ChainClientTrait, primitives::AccountId};
+use crate::{models::{AssignmentEdge, EncodedSolutionPage, PageSupports, RawSupport, Validator, ValidatorNomination, SimulationResult, RunParameters, SyntheticElection, VoterAssignment, VoterSample}, multi_block_state_client::ChainClientTrait, primitives::AccountId};
 
-#[derive(Debug, Deserialize, Clone)]
+// Usage of MaxLength above which the mined solution is reported as close to the limit
+const SOLUTION_LENGTH_WARNING_PERCENT: f64 = 90.0;
+// Relative difference between backing and voter stake tolerated by --verify-conservation
+const CONSERVATION_TOLERANCE: f64 = 0.001;
+
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct Override {
     pub voters: Vec<(String, u64, Vec<String>)>,
     pub voters_remove: Vec<String>,
@@ -24,10 +32,53 @@ pub struct Override {
     pub candidates_remove: Vec<String>,
 }
 
+impl Override {
+    // Read and parse a manual override JSON file
+    pub fn load(path: &str) -> Result<Override, Box<dyn std::error::Error + Send + Sync>> {
+        let file = std::fs::read(path)
+            .map_err(|e| format!("Failed to read manual override file '{}': {}", path, e))?;
+        serde_json::from_slice(&file)
+            .map_err(|e| format!("Failed to parse manual override JSON: {}", e).into())
+    }
+
+    // Fail on the first address that is neither SS58 nor 32-byte hex
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let voters = self.voters.iter().flat_map(|(voter, _, targets)| std::iter::once(voter).chain(targets));
+        for account in voters.chain(&self.voters_remove).chain(&self.candidates).chain(&self.candidates_remove) {
+            parse_account(account)?;
+        }
+        Ok(())
+    }
+
+    // Every override voter must fit the chain's MaxVotesPerVoter; all offending voters are reported at once
+    pub fn check_votes_per_voter(&self, max_votes: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let offending: Vec<String> = self.voters.iter()
+            .filter(|(_, _, targets)| targets.len() > max_votes as usize)
+            .map(|(voter, _, targets)| format!("{} nominates {} targets", voter, targets.len()))
+            .collect();
+        if offending.is_empty() {
+            return Ok(());
+        }
+        Err(format!("Manual override voters exceed MaxVotesPerVoter ({}): {}", max_votes, offending.join(", ")).into())
+    }
+
+    // Add quick exclusions on top of an optional override file, creating an override if needed
+    pub fn with_exclusions(manual_override: Option<Override>, exclude_voters: Vec<String>, exclude_candidates: Vec<String>) -> Option<Override> {
+        if exclude_voters.is_empty() && exclude_candidates.is_empty() {
+            return manual_override;
+        }
+        let mut manual = manual_override.unwrap_or_default();
+        manual.voters_remove.extend(exclude_voters);
+        manual.candidates_remove.extend(exclude_candidates);
+        Some(manual)
+    }
+}
+
 // Service trait - application port for handlers
 #[automock]
 #[async_trait::async_trait]
 pub trait SimulateService: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
     async fn simulate(
         &self,
         block: Option<H256>,
@@ -36,7 +87,48 @@ pub trait SimulateService: Send + Sync {
         manual_override: Option<Override>,
         min_nominator_bond: Option<u128>,   
         min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
     ) -> Result<SimulationResult, Box<dyn std::error::Error + Send + Sync>>;
+    // Run the same election input once per balancing iteration count, fetching the snapshot only once
+    #[allow(clippy::too_many_arguments)]
+    async fn simulate_sweep(
+        &self,
+        block: Option<H256>,
+        iterations: Vec<usize>,
+        desired_validators: Option<u32>,
+        apply_reduce: bool,
+        manual_override: Option<Override>,
+        min_nominator_bond: Option<u128>,
+        min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
+    ) -> Result<Vec<SimulationResult>, Box<dyn std::error::Error + Send + Sync>>;
+    // Mine the same election input without and then with reduce, fetching the snapshot only once
+    async fn simulate_reduce_pair(
+        &self,
+        block: Option<H256>,
+        desired_validators: Option<u32>,
+        manual_override: Option<Override>,
+        min_nominator_bond: Option<u128>,
+        min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
+    ) -> Result<(SimulationResult, SimulationResult), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// Receives the exact election input of `simulate` before it is mined, for `--dump-snapshot`
+pub type SnapshotSink = Box<dyn Fn(&Snapshot) -> Result<(), Box<dyn std::error::Error + Send + Sync>> + Send + Sync>;
+
+// Election input after the snapshot fetch, bond filters and manual override
+pub struct PreparedElection<MC: MinerConfig, S> {
+    storage: S,
+    block_details: BlockDetails,
+    run_parameters: RunParameters,
+    voter_pages: BoundedVec<VoterSnapshotPage<MC>, MC::Pages>,
+    targets: TargetSnapshotPage<MC>,
+    desired_targets: u32,
+    minimum_active_stake: Option<u128>,
+    pool_accounts: BTreeMap<AccountId, u32>,
+    reconstruction: ReconstructionCounts,
+    warnings: Vec<String>,
 }
 
 pub struct SimulateServiceImpl<
@@ -48,6 +140,11 @@ pub struct SimulateServiceImpl<
 > {
     multi_block_state_client: Arc<MBC>,
     snapshot_service: Arc<Snap>,
+    prefs_cache: ValidatorPrefsCache,
+    verify_conservation: bool,
+    unsigned_pages: Option<u32>,
+    exclude_chilled: bool,
+    dump_snapshot: Option<SnapshotSink>,
     _phantom: std::marker::PhantomData<(CC, S, MC)>,
 }
 
@@ -62,30 +159,62 @@ impl<
         Self {
             multi_block_state_client,
             snapshot_service,
+            prefs_cache: ValidatorPrefsCache::default(),
+            verify_conservation: false,
+            unsigned_pages: None,
+            exclude_chilled: false,
+            dump_snapshot: None,
             _phantom: std::marker::PhantomData,
         }
     }
+
+    // Check that the combined supports account for all of the backing voters' stake
+    pub fn with_verify_conservation(mut self, verify_conservation: bool) -> Self {
+        self.verify_conservation = verify_conservation;
+        self
+    }
+
+    // Mine like the unsigned-phase offchain worker: only the `miner_pages` most significant
+    // snapshot pages, always reduced
+    pub fn with_unsigned_pages(mut self, miner_pages: Option<u32>) -> Self {
+        self.unsigned_pages = miner_pages;
+        self
+    }
+
+    // Drop snapshot candidates that chilled after the snapshot was taken
+    pub fn with_exclude_chilled(mut self, exclude_chilled: bool) -> Self {
+        self.exclude_chilled = exclude_chilled;
+        self
+    }
+
+    // Hand the exact election input in snapshot form to `dump_snapshot` once it is prepared,
+    // before mining, so it is kept even when mining fails
+    pub fn with_dump_snapshot(mut self, dump_snapshot: Option<SnapshotSink>) -> Self {
+        self.dump_snapshot = dump_snapshot;
+        self
+    }
 }
 
-#[async_trait::async_trait]
 impl<
     CC: ChainClientTrait + Send + Sync + 'static,
     S: StorageTrait + From<Storage> + Clone + 'static,
     MC: MinerConfig + Send + Sync + 'static,
     MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
     Snap: SnapshotService<MC, S> + Send + Sync + 'static,
-> SimulateService for SimulateServiceImpl<CC, S, MC, MBC, Snap>
+> SimulateServiceImpl<CC, S, MC, MBC, Snap>
 where
     MC: MinerConfig<AccountId = AccountId> + Send,
-    MC::TargetSnapshotPerBlock: Send,
-    MC::VoterSnapshotPerBlock: Send,
-    MC::Pages: Send,
-    MC::MaxVotesPerVoter: Send,
-    MC::Solution: Send,
-    MC::MaxBackersPerWinner: Send,
-    MC::MaxWinnersPerPage: Send,
+    MC::TargetSnapshotPerBlock: Send + Sync,
+    MC::VoterSnapshotPerBlock: Send + Sync,
+    MC::Pages: Send + Sync,
+    MC::MaxVotesPerVoter: Send + Sync,
+    MC::Solution: Send + Sync,
+    MC::MaxBackersPerWinner: Send + Sync,
+    MC::MaxWinnersPerPage: Send + Sync,
 {
-    async fn simulate(
+    // Fetch the snapshot and apply bond filters, the manual override and sampling; the result can be mined repeatedly
+    #[allow(clippy::too_many_arguments)]
+    async fn prepare_election(
         &self,
         block: Option<H256>,
         desired_validators: Option<u32>,
@@ -93,7 +222,11 @@ where
         manual_override: Option<Override>,
         min_nominator_bond: Option<u128>,
         min_validator_bond: Option<u128>,
-    ) -> Result<SimulationResult, Box<dyn std::error::Error + Send + Sync>> {
+        sample: Option<VoterSample>,
+    ) -> Result<PreparedElection<MC, S>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(manual) = &manual_override {
+            manual.check_votes_per_voter(MC::MaxVotesPerVoter::get())?;
+        }
         let multi_block_state_client = self.multi_block_state_client.as_ref();
         let storage = multi_block_state_client.get_storage(block).await?;
         let block_details = multi_block_state_client.get_block_details(&storage, block).await?;
@@ -102,18 +235,20 @@ where
         let balancing_iter = miner_config::BalancingIterations::get();
         let algorithm = miner_config::get_current_algorithm();
         let max_nominations = miner_config::MaxVotesPerVoter::get();
-        let run_parameters = RunParameters {
-            algorithm: algorithm,
+        let mut run_parameters = RunParameters {
+            algorithm,
             iterations: balancing_iter.unwrap_or(sp_npos_elections::BalancingConfig { iterations: 0, tolerance: 0 }).iterations,
-            reduce: apply_reduce,
-            max_nominations: max_nominations,
+            reduce: apply_reduce || self.unsigned_pages.is_some(),
+            max_nominations,
             min_nominator_bond: min_nominator_bond.unwrap_or(0),
             min_validator_bond: min_validator_bond.unwrap_or(0),
             desired_validators: desired_validators.unwrap_or(block_details.desired_targets),
+            sample,
+            unsigned_pages: self.unsigned_pages,
         };        
 
         info!("Fetching snapshot data for election...");
-        let (mut snapshot, staking_config) = self.snapshot_service.get_snapshot_data_from_multi_block(&block_details, &storage).await?;
+        let (mut snapshot, staking_config, reconstruction) = self.snapshot_service.get_snapshot_data_from_multi_block(&block_details, &storage).await?;
 
         // Apply min_nominator_bond filter if provided > 0
         let effective_min_nominator_bond = min_nominator_bond.unwrap_or(0);
@@ -122,7 +257,14 @@ where
             let mut filtered_voter_pages = Vec::new();
             for voter_page in snapshot.voters.iter() {
                 let filtered_page: Vec<_> = voter_page.iter()
-                    .filter(|voter| voter.1 as u128 >= effective_min_nominator_bond)
+                    .filter(|voter| {
+                        let sufficient = voter.1 as u128 >= effective_min_nominator_bond;
+                        if !sufficient {
+                            explain::record(&voter.0, ExcludedRole::Voter, ExclusionReason::BelowMinBond,
+                                Some(format!("stake {} below {}", voter.1, effective_min_nominator_bond)));
+                        }
+                        sufficient
+                    })
                     .cloned()
                     .collect();
                 if !filtered_page.is_empty() {
@@ -131,41 +273,52 @@ where
                     filtered_voter_pages.push(bounded_page);
                 }
             }
-            snapshot.voters = filtered_voter_pages.try_into()
-                .map_err(|_| "Failed to create AllVoterPagesOf")?;
+            snapshot.voters = filtered_voter_pages;
         }
         
         // Apply min_validator_bond filter if provided > 0
         let effective_min_validator_bond = min_validator_bond.unwrap_or(0);
         if effective_min_validator_bond > 0 {
             info!("Filtering validators by min_validator_bond: {}", effective_min_validator_bond);
-            let validator_futures: Vec<_> = snapshot.targets.iter().map(|validator| {
-                let validator = validator.clone();
-                let storage = storage.clone();
-                async move {
-                    let controller = multi_block_state_client.get_controller_from_stash(&storage, validator.clone()).await
-                        .map_err(|e| format!("Error getting controller: {}", e))?;
-                    if controller.is_none() {
-                        return Ok::<Option<AccountId>, String>(None);
-                    }
-                    let controller = controller.unwrap();
-                    let ledger = multi_block_state_client.ledger(&storage, controller).await
-                        .map_err(|e| format!("Error getting ledger: {}", e))?;
-                    let has_sufficient_bond = ledger.map_or(false, |l| l.active >= effective_min_validator_bond);
-                    Ok(has_sufficient_bond.then_some(validator))
+            let mut ledgers = multi_block_state_client.ledgers_batch(&storage, snapshot.targets.to_vec()).await
+                .map_err(|e| format!("Error getting ledgers: {}", e))?;
+            let filtered_validators: Vec<_> = snapshot.targets.iter().map(|validator| {
+                let ledger = ledgers.remove(validator).unwrap_or(Ok(None))
+                    .map_err(|e| format!("Error getting ledger: {}", e))?;
+                if ledger.is_none() {
+                    explain::record(validator, ExcludedRole::Candidate, ExclusionReason::NotBonded, None);
+                    return Ok::<Option<AccountId>, String>(None);
                 }
-            }).collect();
-            
-            let filtered_validators: Vec<_> = join_all(validator_futures)
-                .await
-                .into_iter()
+                let active_stake = ledger.map_or(0, |l| l.active);
+                let has_sufficient_bond = active_stake >= effective_min_validator_bond;
+                if !has_sufficient_bond {
+                    explain::record(validator, ExcludedRole::Candidate, ExclusionReason::BelowMinBond,
+                        Some(format!("active stake {} below {}", active_stake, effective_min_validator_bond)));
+                }
+                Ok(has_sufficient_bond.then_some(validator.clone()))
+            })
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| format!("Error filtering validators: {}", e))?;
             
-            let filtered_validators: Vec<_> = filtered_validators.into_iter().filter_map(|x| x).collect();
+            let filtered_validators: Vec<_> = filtered_validators.into_iter().flatten().collect();
             snapshot.targets = BoundedVec::try_from(filtered_validators)
                 .map_err(|_| "Failed to create bounded target page")?;
         }
+
+        // Chilling mid-round does not update a snapshot the chain already took
+        if self.exclude_chilled {
+            let chilled = snapshot::chilled_targets(multi_block_state_client, &storage, &snapshot.targets).await?;
+            if !chilled.is_empty() {
+                info!("Excluding {} candidates chilled since the snapshot", chilled.len());
+                let (removed, validating): (Vec<AccountId>, Vec<AccountId>) = snapshot.targets.iter().cloned()
+                    .partition(|target| chilled.contains(target));
+                for target in &removed {
+                    explain::record(target, ExcludedRole::Candidate, ExclusionReason::Chilled, None);
+                }
+                snapshot.targets = BoundedVec::try_from(validating)
+                    .map_err(|_| "Failed to create bounded target page")?;
+            }
+        }
         
         // Manual override
         if let Some(manual) = manual_override {
@@ -174,7 +327,7 @@ where
 
             // Add any additional candidates
             for c in &manual.candidates {
-                let candidate_id: AccountId = AccountId::from_ss58check(c)?;
+                let candidate_id: AccountId = parse_account(c)?;
                 if targets.contains(&candidate_id) {
                     info!("manual override: {:?} is already a candidate.", c);
                 } else {
@@ -185,9 +338,10 @@ where
 
             // Remove candidates in the removal list
             for c in &manual.candidates_remove {
-                let candidate_id: AccountId = AccountId::from_ss58check(c)?;
+                let candidate_id: AccountId = parse_account(c)?;
                 if targets.contains(&candidate_id) {
                     info!("manual override: {:?} is removed as candidate.", c);
+                    explain::record(&candidate_id, ExcludedRole::Candidate, ExclusionReason::ManuallyRemoved, None);
                     targets.retain(|x| x != &candidate_id);
                 }
             }
@@ -206,13 +360,13 @@ where
 
             // Add or override voters
             for v in &manual.voters {
-                let voter_id: AccountId = AccountId::from_ss58check(&v.0)?;
+                let voter_id: AccountId = parse_account(&v.0)?;
                 let stake = v.1;
                 let votes: Vec<AccountId> = v.2.iter()
-                    .map(|vote| AccountId::from_ss58check(vote).map(|id| id.into()))
+                    .map(|vote| parse_account(vote))
                     .collect::<Result<_, _>>()?;
                 let bounded_votes = BoundedVec::try_from(votes)
-                    .map_err(|_| "Too many nominations")?;
+                    .map_err(|_| format!("Too many nominations for manual override voter {}", v.0))?;
 
                 let voter_data: VoterData<MC> = (voter_id.clone(), stake, bounded_votes);
                 if let Some(existing_voter) = all_voters.iter_mut().find(|vv| vv.0 == voter_data.0) {
@@ -226,9 +380,10 @@ where
 
             // Remove voters in the removal list
             for v in &manual.voters_remove {
-                let voter_id: AccountId = AccountId::from_ss58check(v)?;
+                let voter_id: AccountId = parse_account(v)?;
                 if all_voters.iter().any(|vv| vv.0 == voter_id) {
                     info!("manual override: {:?} is removed as voter.", v);
+                    explain::record(&voter_id, ExcludedRole::Voter, ExclusionReason::ManuallyRemoved, None);
                     all_voters.retain(|vv| vv.0 != voter_id);
                 }
             }
@@ -238,8 +393,18 @@ where
                 .chunks(MC::VoterSnapshotPerBlock::get() as usize)
                 .map(|chunk| BoundedVec::try_from(chunk.to_vec()).map_err(|_| "Too many voters in chunk"))
                 .collect::<Result<Vec<_>, _>>()?;
-            snapshot.voters = voters_vec.try_into()
-                .map_err(|_| "Failed to create AllVoterPagesOf")?;
+            snapshot.voters = voters_vec;
+        }
+
+        if let Some(sample) = sample {
+            let total_voters: usize = snapshot.voters.iter().map(|page| page.len()).sum();
+            if sample.size < total_voters {
+                warn!("Sampling {} of {} voters with seed {}; the result is approximate", sample.size, total_voters, sample.seed);
+                snapshot.voters = sample_voters::<MC>(snapshot.voters, sample)?;
+            } else {
+                info!("Sample size {} covers all {} voters, running the full election", sample.size, total_voters);
+                run_parameters.sample = None;
+            }
         }
 
         let desired_targets = if let Some(desired_validators) = desired_validators {
@@ -247,50 +412,183 @@ where
         } else {
             staking_config.desired_validators
         };
+        let mut warnings = Vec::new();
+        if desired_targets as usize > snapshot.targets.len() {
+            let filter_note = if effective_min_validator_bond > 0 { " after the min_validator_bond filter" } else { "" };
+            let warning = format!(
+                "requested {} validators but only {} candidates available{}; electing all {}",
+                desired_targets, snapshot.targets.len(), filter_note, snapshot.targets.len(),
+            );
+            info!("{}", warning);
+            warnings.push(warning);
+        }
 
         let voter_pages: BoundedVec<VoterSnapshotPage<MC>, MC::Pages> = BoundedVec::truncate_from(snapshot.voters);
+        let largest_voter_page = voter_pages.iter().map(|page| page.len()).max().unwrap_or(0);
+        miner_config::check_solution_index_width::<MC>(largest_voter_page, snapshot.targets.len())?;
+        let pool_accounts = self.snapshot_service.get_pool_accounts(block_details.block_hash).await?;
+        if let Some(dump_snapshot) = self.dump_snapshot.as_ref() {
+            let config = StakingConfig { desired_validators: desired_targets, ..staking_config.clone() };
+            let input_snapshot = self.input_snapshot(&storage, &voter_pages, &snapshot.targets, &pool_accounts, config, reconstruction).await?;
+            dump_snapshot(&input_snapshot).map_err(|e| format!("Error writing the election input snapshot: {}", e))?;
+        }
+
+        Ok(PreparedElection {
+            storage,
+            block_details,
+            run_parameters,
+            voter_pages,
+            targets: snapshot.targets,
+            desired_targets,
+            minimum_active_stake: staking_config.minimum_active_stake,
+            pool_accounts,
+            reconstruction,
+            warnings,
+        })
+    }
+
+    // Election input after filters, override and sampling, with the prefs of each candidate
+    async fn input_snapshot(
+        &self,
+        storage: &S,
+        voter_pages: &[VoterSnapshotPage<MC>],
+        targets: &[AccountId],
+        pool_accounts: &BTreeMap<AccountId, u32>,
+        config: StakingConfig,
+        reconstruction: ReconstructionCounts,
+    ) -> Result<Snapshot, Box<dyn std::error::Error + Send + Sync>> {
+        let multi_block_state_client = self.multi_block_state_client.as_ref();
+        let era = snapshot::prefs_era(multi_block_state_client, storage).await?;
+        let prefs = join_all(targets.iter().map(|target| {
+            let storage = storage.clone();
+            async move { snapshot::validator_prefs_at(multi_block_state_client, &storage, era, target.clone()).await }
+        })).await;
+        let validators = targets.iter().zip(prefs).map(|(target, prefs)| {
+            let prefs = prefs.map_err(|e| format!("Error getting validator prefs of {}: {}", to_ss58(target), e))?;
+            Ok(SnapshotValidator {
+                stash: to_ss58(target),
+                commission: prefs.commission.deconstruct() as f64 / 1_000_000_000.0,
+                commission_parts: prefs.commission.deconstruct(),
+                commission_bps: None,
+                blocked: prefs.blocked,
+            })
+        }).collect::<Result<Vec<_>, String>>()?;
+        let nominators = voter_pages.iter().flat_map(|page| page.iter()).map(|voter| SnapshotNominator {
+            stash: to_ss58(&voter.0),
+            stake: voter.1 as u128,
+            nominations: voter.2.iter().map(to_ss58).collect(),
+            pool_id: pool_accounts.get(&voter.0).copied(),
+        }).collect();
+        Ok(Snapshot { validators, nominators, config, reconstruction })
+    }
 
-        // Use actual voter pages for mining solution when snapshot is not available and is created from staking
-        let actual_voter_pages = voter_pages.len() as u32;
+    // Mine the prepared election with the current algorithm and balancing settings
+    async fn run_election(
+        &self,
+        prepared: &PreparedElection<MC, S>,
+    ) -> Result<SimulationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let multi_block_state_client = self.multi_block_state_client.as_ref();
+        let PreparedElection { storage, block_details, voter_pages, targets: snapshot_targets, desired_targets, pool_accounts, .. } = prepared;
+        let desired_targets = *desired_targets;
+        // Balancing iterations may differ between runs of the same prepared election
+        let mut run_parameters = prepared.run_parameters.clone();
+        run_parameters.iterations = miner_config::BalancingIterations::get().map_or(0, |config| config.iterations);
+
+        // Use actual voter pages for mining solution when snapshot is not available and is created from staking.
+        // The unsigned miner only sees the most significant pages, the voters of the others are left out
+        let mined_from = run_parameters.unsigned_pages
+            .map_or(0, |miner_pages| voter_pages.len().saturating_sub(miner_pages as usize));
+        let mined_voter_pages: BoundedVec<VoterSnapshotPage<MC>, MC::Pages> = BoundedVec::truncate_from(voter_pages[mined_from..].to_vec());
+        let actual_voter_pages = mined_voter_pages.len() as u32;
         
         let mine_input = MineInput {
-            desired_targets: desired_targets,
-            all_targets: snapshot.targets.clone(),
-            voter_pages: voter_pages.clone(),
+            desired_targets,
+            all_targets: snapshot_targets.clone(),
+            voter_pages: mined_voter_pages,
             pages: actual_voter_pages,
-            do_reduce: apply_reduce,
+            do_reduce: run_parameters.reduce,
             round: block_details.round,
         };
         info!("Mining solution for election...");
 
-        let paged_solution = BaseMiner::<MC>::mine_solution(mine_input).map_err(|e| format!("Error mining solution: {:?}", e))?;
+        progress::stage("mining solution");
+        let paged_solution = budget::run_blocking("Mining", move || {
+            BaseMiner::<MC>::mine_solution(mine_input).map_err(|e| format!("Error mining solution: {:?}", e))
+        }).await??;
+
+        let solution_length = SolutionLength::new(paged_solution.encoded_size() as u32, MC::MaxLength::get());
+        info!("Solution length: {} / {} bytes ({:.2}%)", solution_length.encoded_length, solution_length.max_length, solution_length.usage_percent);
+        if solution_length.usage_percent >= SOLUTION_LENGTH_WARNING_PERCENT {
+            warn!("Solution is within {:.0}% of MaxLength and may not fit in a real submission", 100.0 - SOLUTION_LENGTH_WARNING_PERCENT);
+        }
         
         // Convert each solution page to supports and combine them
-        let mut total_supports: BTreeMap<AccountId, Support<AccountId>> = BTreeMap::new();
-
+        progress::stage("checking feasibility");
         let paged_supports = BaseMiner::<MC>::check_feasibility(
-            &paged_solution, &voter_pages, &snapshot.targets, desired_targets)
+            &paged_solution, voter_pages, snapshot_targets, desired_targets)
             .map_err(|e| format!("Error checking feasibility: {:?}", e))?;
 
-        for page in paged_supports.iter() {
-            for (winner, support) in page.iter() {
-                let entry = total_supports.entry(winner.clone()).or_insert_with(|| Support {
-                    total: 0,
-                    voters: Vec::new(),
-                });
-                entry.total = entry.total.saturating_add(support.total);
-                entry.voters.extend(support.voters.clone().into_iter());
-            }
+        // Feasibility checked pages line up with the solution pages
+        let page_offset = MC::Pages::get().saturating_sub(paged_solution.solution_pages.len() as u32);
+        let page_supports = paged_supports.iter().enumerate().map(|(index, page)| PageSupports {
+            page: page_offset + index as u32,
+            winner_count: page.len(),
+            winners: page.iter().map(|(winner, support)| raw_support(winner, support.total, &support.voters)).collect(),
+        }).collect();
+
+        let total_supports = combine_page_supports::<MC>(&paged_supports);
+
+        let mut warnings = prepared.warnings.clone();
+        let winner_cap = MC::Pages::get().saturating_mul(MC::MaxWinnersPerPage::get()) as usize;
+        if let Some(warning) = elected_count_warning::<MC>(total_supports.len(), desired_targets, snapshot_targets, &voter_pages[mined_from..], winner_cap) {
+            warn!("{}", warning);
+            warnings.push(warning);
         }
+        if self.verify_conservation && let Some(warning) = stake_conservation_warning::<MC>(voter_pages, &total_supports) {
+            warn!("{}", warning);
+            warnings.push(warning);
+        }
+
+        let assignments = solution_assignments::<MC>(&paged_solution.solution_pages, voter_pages, snapshot_targets)?;
+        // Solutions with fewer pages than the chain are aligned to its last pages
+        let solution_pages = paged_solution.solution_pages.iter().enumerate()
+            .map(|(index, page)| EncodedSolutionPage { page: page_offset + index as u32, solution: page.encode() })
+            .collect();
+        let unelected_candidates = unelected_candidates::<MC>(snapshot_targets, voter_pages, &total_supports);
+        let min_active_nominator_stake = min_active_nominator_stake::<MC>(voter_pages, &total_supports);
+        let unused_stake = unused_nominator_stake::<MC>(snapshot_targets, voter_pages, &total_supports);
+        let wasted_voters = wasted_voters::<MC>(snapshot_targets, voter_pages, &total_supports);
+        if !wasted_voters.is_empty() {
+            info!("{} nominators back no elected validator and should re-nominate", wasted_voters.len());
+        }
+        let total_unused_stake = unused_stake.iter().map(|n| n.unused_stake).sum();
+
+        let prefs_cache = &self.prefs_cache;
+        let block_hash = block_details.block_hash;
+        let era = snapshot::prefs_era(multi_block_state_client, storage).await?;
+
+        let supports = raw_supports(&total_supports);
+        progress::stage("reading validator prefs");
 
         let validator_futures: Vec<_> = total_supports.into_iter().map(|(winner, support)| {
             let storage = storage.clone();
             async move {
-                let validator_prefs = multi_block_state_client.get_validator_prefs(&storage, winner.clone()).await
-                    .unwrap_or(ValidatorPrefs {
-                        commission: Perbill::from_parts(0),
-                        blocked: false,
-                    });
+                let cached_prefs = block_hash.and_then(|hash| prefs_cache.get(hash, &winner));
+                let validator_prefs = match cached_prefs {
+                    Some(prefs) => prefs,
+                    None => match snapshot::validator_prefs_at(multi_block_state_client, &storage, era, winner.clone()).await {
+                        Ok(prefs) => {
+                            if let Some(hash) = block_hash {
+                                prefs_cache.insert(hash, winner.clone(), prefs.clone());
+                            }
+                            prefs
+                        }
+                        Err(_) => ValidatorPrefs {
+                            commission: Perbill::from_parts(0),
+                            blocked: false,
+                        },
+                    },
+                };
 
                 let self_stake = support.voters.iter()
                     .find(|voter| voter.0 == winner)
@@ -301,19 +599,21 @@ where
                     .filter(|voter| voter.0 != winner)
                     .map(|voter| {
                     ValidatorNomination {
-                        nominator: voter.0.to_ss58check(),
-                        stake: voter.1 as u128,
+                        nominator: to_ss58(&voter.0),
+                        stake: voter.1,
+                        pool_id: pool_accounts.get(&voter.0).copied(),
                     }
                 }).collect();
 
                 Ok::<Validator, String>(Validator {
-                    stash: winner.to_ss58check(),
-                    self_stake: self_stake as u128,
-                    total_stake: support.total as u128,
+                    stash: to_ss58(&winner),
+                    self_stake,
+                    total_stake: support.total,
                     commission: validator_prefs.commission.deconstruct() as f64 / 1_000_000_000.0,
+                    commission_parts: validator_prefs.commission.deconstruct(),
                     blocked: validator_prefs.blocked,
                     nominations_count: nominations.len(),
-                    nominations: nominations,
+                    nominations,
                 })
             }
         }).collect();
@@ -327,73 +627,537 @@ where
         let total_staked = active_validators.iter().map(|v| v.total_stake).sum();
         let lowest_staked = active_validators.iter().map(|v| v.total_stake).min().unwrap_or(0);
         let avg_staked = total_staked / active_validators.len() as u128;
+        // Self-vote of each winner vs stake backing it from other voters
+        let total_self_stake = active_validators.iter().map(|v| v.self_stake).sum();
+        let total_nominated_stake = active_validators.iter()
+            .flat_map(|v| v.nominations.iter())
+            .map(|n| n.stake)
+            .sum();
+        let validator_stakes: Vec<u128> = active_validators.iter().map(|v| v.total_stake).collect();
+        let mut nominator_stakes: BTreeMap<&str, u128> = BTreeMap::new();
+        for nomination in active_validators.iter().flat_map(|v| v.nominations.iter()) {
+            *nominator_stakes.entry(nomination.nominator.as_str()).or_default() += nomination.stake;
+        }
+        let nominator_stakes: Vec<u128> = nominator_stakes.into_values().collect();
+        let validator_stake_gini = gini(&validator_stakes);
+        let nominator_stake_gini = gini(&nominator_stakes);
 
         let simulation_result = crate::models::SimulationResult {
-            run_parameters: run_parameters.clone(),
+            run_parameters,
             active_validators,
             staking_stats: StakingStats {
-                total_staked: total_staked,
-                lowest_staked: lowest_staked,
-                avg_staked: avg_staked,
+                total_staked,
+                lowest_staked,
+                avg_staked,
+                total_self_stake,
+                total_nominated_stake,
+                min_active_nominator_stake,
+                minimum_active_stake: prepared.minimum_active_stake.unwrap_or(min_active_nominator_stake),
+                validator_stake_gini,
+                nominator_stake_gini,
+                total_unused_stake,
+                validator_stake_percentiles: stake_percentiles(&validator_stakes),
+                nominator_stake_percentiles: stake_percentiles(&nominator_stakes),
+            },
+            solution_length,
+            score: ElectionScore {
+                minimal_stake: paged_solution.score.minimal_stake,
+                sum_stake: paged_solution.score.sum_stake,
+                sum_stake_squared: paged_solution.score.sum_stake_squared,
             },
+            unelected_candidates,
+            unused_stake,
+            wasted_voters,
+            queued_comparison: None,
+            assignments,
+            supports,
+            page_supports,
+            solution_pages,
+            reconstruction: prepared.reconstruction,
+            warnings,
         };
 
         Ok(simulation_result)
     }
 }
 
+#[async_trait::async_trait]
+impl<
+    CC: ChainClientTrait + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+    Snap: SnapshotService<MC, S> + Send + Sync + 'static,
+> SimulateService for SimulateServiceImpl<CC, S, MC, MBC, Snap>
+where
+    MC: MinerConfig<AccountId = AccountId> + Send,
+    MC::TargetSnapshotPerBlock: Send + Sync,
+    MC::VoterSnapshotPerBlock: Send + Sync,
+    MC::Pages: Send + Sync,
+    MC::MaxVotesPerVoter: Send + Sync,
+    MC::Solution: Send + Sync,
+    MC::MaxBackersPerWinner: Send + Sync,
+    MC::MaxWinnersPerPage: Send + Sync,
+{
+    async fn simulate(
+        &self,
+        block: Option<H256>,
+        desired_validators: Option<u32>,
+        apply_reduce: bool,
+        manual_override: Option<Override>,
+        min_nominator_bond: Option<u128>,
+        min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
+    ) -> Result<SimulationResult, Box<dyn std::error::Error + Send + Sync>> {
+        let prepared = self.prepare_election(block, desired_validators, apply_reduce, manual_override, min_nominator_bond, min_validator_bond, sample).await?;
+        self.run_election(&prepared).await
+    }
+
+    async fn simulate_sweep(
+        &self,
+        block: Option<H256>,
+        iterations: Vec<usize>,
+        desired_validators: Option<u32>,
+        apply_reduce: bool,
+        manual_override: Option<Override>,
+        min_nominator_bond: Option<u128>,
+        min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
+    ) -> Result<Vec<SimulationResult>, Box<dyn std::error::Error + Send + Sync>> {
+        let prepared = self.prepare_election(block, desired_validators, apply_reduce, manual_override, min_nominator_bond, min_validator_bond, sample).await?;
+        let algorithm = miner_config::get_current_algorithm();
+        let max_nominations = miner_config::MaxVotesPerVoter::get();
+        let mut results = Vec::with_capacity(iterations.len());
+        for count in iterations {
+            info!("Running election with {} balancing iterations", count);
+            let result = miner_config::with_election_config(algorithm, count, Some(max_nominations), self.run_election(&prepared)).await?;
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    async fn simulate_reduce_pair(
+        &self,
+        block: Option<H256>,
+        desired_validators: Option<u32>,
+        manual_override: Option<Override>,
+        min_nominator_bond: Option<u128>,
+        min_validator_bond: Option<u128>,
+        sample: Option<VoterSample>,
+    ) -> Result<(SimulationResult, SimulationResult), Box<dyn std::error::Error + Send + Sync>> {
+        let mut prepared = self.prepare_election(block, desired_validators, false, manual_override, min_nominator_bond, min_validator_bond, sample).await?;
+        info!("Running election without reduce");
+        let without_reduce = self.run_election(&prepared).await?;
+        prepared.run_parameters.reduce = true;
+        info!("Running election with reduce");
+        let with_reduce = self.run_election(&prepared).await?;
+        Ok((without_reduce, with_reduce))
+    }
+}
+
+// Add up the supports a winner receives on each solution page
+fn combine_page_supports<MC: MinerConfig<AccountId = AccountId>>(
+    paged_supports: &[PageSupportsOfMiner<MC>],
+) -> BTreeMap<AccountId, Support<AccountId>> {
+    let mut total_supports: BTreeMap<AccountId, Support<AccountId>> = BTreeMap::new();
+    for page in paged_supports.iter() {
+        for (winner, support) in page.iter() {
+            let entry = total_supports.entry(winner.clone()).or_insert_with(|| Support {
+                total: 0,
+                voters: Vec::new(),
+            });
+            entry.total = entry.total.saturating_add(support.total);
+            entry.voters.extend(support.voters.clone());
+        }
+    }
+    total_supports
+}
+
+/// Mine an election over a posted electorate, without any chain state, with the current algorithm
+/// and balancing settings. Voters are paged like a snapshot, so the electorate must fit the
+/// snapshot bounds of the chain's miner.
+pub fn elect<MC: MinerConfig<AccountId = AccountId>>(
+    voters: &[(String, u64, Vec<String>)],
+    targets: &[String],
+    desired_targets: u32,
+    reduce: bool,
+) -> Result<SyntheticElection, Box<dyn std::error::Error + Send + Sync>> {
+    if voters.is_empty() {
+        return Err("No voters given".into());
+    }
+    let max_voters = MC::Pages::get() as usize * MC::VoterSnapshotPerBlock::get() as usize;
+    if voters.len() > max_voters {
+        return Err(format!("{} voters exceed the {} that fit the voter snapshot (Pages x VoterSnapshotPerBlock)", voters.len(), max_voters).into());
+    }
+    let targets: Vec<AccountId> = targets.iter().map(|target| parse_account(target)).collect::<Result<_, _>>()?;
+    let target_count = targets.len();
+    let targets: TargetSnapshotPage<MC> = BoundedVec::try_from(targets)
+        .map_err(|_| format!("{} targets exceed TargetSnapshotPerBlock ({})", target_count, MC::TargetSnapshotPerBlock::get()))?;
+
+    let voters = voters.iter().map(|(voter, stake, votes)| {
+        let votes: Vec<AccountId> = votes.iter().map(|vote| parse_account(vote)).collect::<Result<_, _>>()?;
+        let vote_count = votes.len();
+        let votes = BoundedVec::try_from(votes)
+            .map_err(|_| format!("Voter {} nominates {} targets but MaxVotesPerVoter is {}", voter, vote_count, MC::MaxVotesPerVoter::get()))?;
+        Ok((parse_account(voter)?, *stake, votes))
+    }).collect::<Result<Vec<VoterData<MC>>, Box<dyn std::error::Error + Send + Sync>>>()?;
+    let voter_pages: Vec<VoterSnapshotPage<MC>> = voters
+        .chunks(MC::VoterSnapshotPerBlock::get() as usize)
+        .map(|chunk| BoundedVec::truncate_from(chunk.to_vec()))
+        .collect();
+    let voter_pages: BoundedVec<VoterSnapshotPage<MC>, MC::Pages> = BoundedVec::truncate_from(voter_pages);
+
+    let mine_input = MineInput {
+        desired_targets,
+        all_targets: targets.clone(),
+        voter_pages: voter_pages.clone(),
+        pages: voter_pages.len() as u32,
+        do_reduce: reduce,
+        round: 0,
+    };
+    let paged_solution = BaseMiner::<MC>::mine_solution(mine_input).map_err(|e| format!("Error mining solution: {:?}", e))?;
+    let paged_supports = BaseMiner::<MC>::check_feasibility(&paged_solution, &voter_pages, &targets, desired_targets)
+        .map_err(|e| format!("Error checking feasibility: {:?}", e))?;
+    Ok(SyntheticElection {
+        score: paged_solution.score.into(),
+        winners: raw_supports(&combine_page_supports::<MC>(&paged_supports)),
+    })
+}
+
+// Combined supports without any conversion, amounts kept as integer strings
+fn raw_supports(supports: &BTreeMap<AccountId, Support<AccountId>>) -> Vec<RawSupport> {
+    supports.iter().map(|(winner, support)| raw_support(winner, support.total, &support.voters)).collect()
+}
+
+fn raw_support(winner: &AccountId, total: u128, voters: &[(AccountId, u128)]) -> RawSupport {
+    RawSupport {
+        validator: to_ss58(winner),
+        total: total.to_string(),
+        voters: voters.iter().map(|(voter, weight)| (to_ss58(voter), weight.to_string())).collect(),
+    }
+}
+
+// Per-voter edges of the mined solution, decoded the way the chain's feasibility check does
+fn solution_assignments<MC: MinerConfig<AccountId = AccountId>>(
+    solution_pages: &[MC::Solution],
+    voter_pages: &[VoterSnapshotPage<MC>],
+    targets: &TargetSnapshotPage<MC>,
+) -> Result<Vec<VoterAssignment>, Box<dyn std::error::Error + Send + Sync>> {
+    // Missing leading pages are padded on both sides, so align the last pages
+    let offset = voter_pages.len().saturating_sub(solution_pages.len());
+    let mut assignments = Vec::new();
+    for (page, solution) in solution_pages.iter().enumerate() {
+        let voters = voter_pages.get(page + offset).ok_or("Solution page has no matching voter page")?;
+        let voter_at = |index: <MC::Solution as NposSolution>::VoterIndex| {
+            let index: usize = index.try_into().ok()?;
+            voters.get(index).map(|voter| voter.0.clone())
+        };
+        let target_at = |index: <MC::Solution as NposSolution>::TargetIndex| {
+            let index: usize = index.try_into().ok()?;
+            targets.get(index).cloned()
+        };
+        let ratio_assignments = solution.clone().into_assignment(voter_at, target_at)
+            .map_err(|e| format!("Error decoding solution page {}: {:?}", page, e))?;
+        let stakes: BTreeMap<&AccountId, u64> = voters.iter().map(|voter| (&voter.0, voter.1)).collect();
+        let staked_assignments = assignment_ratio_to_staked_normalized(ratio_assignments, |who| stakes.get(who).copied().unwrap_or(0))
+            .map_err(|e| format!("Error normalizing solution page {}: {:?}", page, e))?;
+        assignments.extend(staked_assignments.into_iter().map(|assignment| VoterAssignment {
+            voter: to_ss58(&assignment.who),
+            edges: assignment.distribution.into_iter()
+                .map(|(validator, stake)| AssignmentEdge { validator: to_ss58(&validator), stake })
+                .collect(),
+        }));
+    }
+    Ok(assignments)
+}
+
+// Keep a seeded random subset of `sample.size` voters, preserving their snapshot order and pages
+pub fn sample_voters<MC: MinerConfig>(
+    voter_pages: Vec<VoterSnapshotPage<MC>>,
+    sample: VoterSample,
+) -> Result<Vec<VoterSnapshotPage<MC>>, Box<dyn std::error::Error + Send + Sync>> {
+    let total: usize = voter_pages.iter().map(|page| page.len()).sum();
+    let mut rng = StdRng::seed_from_u64(sample.seed);
+    let selected: HashSet<usize> = rand::seq::index::sample(&mut rng, total, sample.size.min(total)).into_iter().collect();
+
+    let mut index = 0;
+    let mut sampled_pages = Vec::new();
+    for voter_page in voter_pages {
+        let sampled_page: Vec<_> = voter_page.into_iter()
+            .filter(|_| {
+                index += 1;
+                selected.contains(&(index - 1))
+            })
+            .collect();
+        if !sampled_page.is_empty() {
+            let bounded_page = BoundedVec::try_from(sampled_page)
+                .map_err(|_| "Failed to create bounded voter page")?;
+            sampled_pages.push(bounded_page);
+        }
+    }
+    Ok(sampled_pages)
+}
+
+// Parse an override address given either as SS58 or as 0x-prefixed 32-byte hex
+pub fn parse_account(address: &str) -> Result<AccountId, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(hex_str) = address.strip_prefix("0x") {
+        let bytes = hex::decode(hex_str)
+            .map_err(|e| format!("Invalid hex account '{}': {}", address, e))?;
+        let raw: [u8; 32] = bytes.try_into()
+            .map_err(|bytes: Vec<u8>| format!("Invalid hex account '{}': expected 32 bytes, got {}", address, bytes.len()))?;
+        Ok(AccountId::from(raw))
+    } else {
+        AccountId::from_ss58check(address)
+            .map_err(|e| format!("Invalid SS58 account '{}': {}", address, e).into())
+    }
+}
+
+// Why fewer validators than requested were elected, checking the likeliest cause first.
+// A shortage of candidates alone is already reported when the election is prepared
+fn elected_count_warning<MC: MinerConfig<AccountId = AccountId>>(
+    elected: usize,
+    desired_targets: u32,
+    targets: &[AccountId],
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winner_cap: usize,
+) -> Option<String> {
+    let expected = (desired_targets as usize).min(targets.len());
+    if elected >= expected {
+        return None;
+    }
+    let candidates: HashSet<&AccountId> = targets.iter().collect();
+    let backed: HashSet<&AccountId> = voter_pages.iter()
+        .flat_map(|page| page.iter())
+        .filter(|voter| voter.1 > 0)
+        .flat_map(|voter| voter.2.iter())
+        .filter(|target| candidates.contains(target))
+        .collect();
+    let cause = if backed.len() < expected {
+        format!("only {} of {} candidates are backed by a voter with stake", backed.len(), targets.len())
+    } else if winner_cap < expected {
+        format!("the solution holds at most {} winners (Pages x MaxWinnersPerPage)", winner_cap)
+    } else {
+        "the other winners lost their backing when the solution was trimmed to MaxBackersPerWinner or MaxLength".to_string()
+    };
+    Some(format!("elected {} of {} requested validators: {}", elected, desired_targets, cause))
+}
+
+// Targets missing from the winners, with the stake that nominated them in the election input
+fn unelected_candidates<MC: MinerConfig<AccountId = AccountId>>(
+    targets: &[AccountId],
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winners: &BTreeMap<AccountId, Support<AccountId>>,
+) -> Vec<UnelectedCandidate> {
+    let mut backing: BTreeMap<&AccountId, (u128, usize)> = BTreeMap::new();
+    for voter in voter_pages.iter().flat_map(|page| page.iter()) {
+        for target in voter.2.iter() {
+            let entry = backing.entry(target).or_insert((0, 0));
+            entry.0 = entry.0.saturating_add(voter.1 as u128);
+            entry.1 += 1;
+        }
+    }
+
+    let mut unelected: Vec<UnelectedCandidate> = targets.iter()
+        .filter(|target| !winners.contains_key(*target))
+        .map(|target| {
+            let (backing_stake, backers_count) = backing.get(target).copied().unwrap_or((0, 0));
+            UnelectedCandidate {
+                stash: to_ss58(target),
+                backing_stake,
+                backers_count,
+                reason: if backers_count == 0 { UnelectedReason::NoSupport } else { UnelectedReason::InsufficientSupport },
+            }
+        })
+        .collect();
+    unelected.sort_by_key(|c| std::cmp::Reverse(c.backing_stake));
+    unelected
+}
+
+// Nominators whose bonded stake is not fully assigned to winners, e.g. because none of their targets got
+// elected. Validators' self votes are not nominations and are skipped.
+fn unused_nominator_stake<MC: MinerConfig<AccountId = AccountId>>(
+    targets: &[AccountId],
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winners: &BTreeMap<AccountId, Support<AccountId>>,
+) -> Vec<NominatorUnusedStake> {
+    let validators: HashSet<&AccountId> = targets.iter().chain(winners.keys()).collect();
+    let mut assigned: BTreeMap<&AccountId, u128> = BTreeMap::new();
+    for (voter, stake) in winners.values().flat_map(|support| support.voters.iter()) {
+        let entry = assigned.entry(voter).or_default();
+        *entry = entry.saturating_add(*stake);
+    }
+
+    let mut unused: Vec<NominatorUnusedStake> = voter_pages.iter()
+        .flat_map(|page| page.iter())
+        .filter(|voter| !validators.contains(&voter.0))
+        .filter_map(|voter| {
+            let bonded_stake = voter.1 as u128;
+            let assigned_stake = assigned.get(&voter.0).copied().unwrap_or(0);
+            let unused_stake = bonded_stake.saturating_sub(assigned_stake);
+            (unused_stake > 0).then(|| NominatorUnusedStake {
+                nominator: to_ss58(&voter.0),
+                bonded_stake,
+                assigned_stake,
+                unused_stake,
+            })
+        })
+        .collect();
+    unused.sort_by_key(|n| std::cmp::Reverse(n.unused_stake));
+    unused
+}
+
+// Nominators none of whose targets was elected, so their whole stake backs no one, largest stake first.
+// Validators' self votes are left out, as in `unused_nominator_stake`
+fn wasted_voters<MC: MinerConfig<AccountId = AccountId>>(
+    targets: &[AccountId],
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winners: &BTreeMap<AccountId, Support<AccountId>>,
+) -> Vec<WastedVoter> {
+    let validators: HashSet<&AccountId> = targets.iter().chain(winners.keys()).collect();
+    let mut wasted: Vec<WastedVoter> = voter_pages.iter()
+        .flat_map(|page| page.iter())
+        .filter(|voter| !validators.contains(&voter.0))
+        .filter(|voter| !voter.2.iter().any(|target| winners.contains_key(target)))
+        .map(|voter| WastedVoter {
+            nominator: to_ss58(&voter.0),
+            stake: voter.1 as u128,
+            targets: voter.2.iter().map(to_ss58).collect(),
+        })
+        .collect();
+    wasted.sort_by_key(|voter| std::cmp::Reverse(voter.stake));
+    wasted
+}
+
+// Smallest bonded stake of a nominator backing any winner, i.e. the stake needed to be in an active nomination.
+// Winners' self votes are not nominations and are skipped.
+fn min_active_nominator_stake<MC: MinerConfig<AccountId = AccountId>>(
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winners: &BTreeMap<AccountId, Support<AccountId>>,
+) -> u128 {
+    let active: HashSet<&AccountId> = winners.values()
+        .flat_map(|support| support.voters.iter())
+        .filter(|(voter, stake)| *stake > 0 && !winners.contains_key(voter))
+        .map(|(voter, _)| voter)
+        .collect();
+    voter_pages.iter()
+        .flat_map(|page| page.iter())
+        .filter(|voter| active.contains(&voter.0))
+        .map(|voter| voter.1 as u128)
+        .min()
+        .unwrap_or(0)
+}
+
+// Compare the stake behind the combined supports with the full stake of the voters in them; each
+// backing voter's whole stake should be spread over the winners, so a gap points at page combination
+fn stake_conservation_warning<MC: MinerConfig<AccountId = AccountId>>(
+    voter_pages: &[VoterSnapshotPage<MC>],
+    winners: &BTreeMap<AccountId, Support<AccountId>>,
+) -> Option<String> {
+    let supported: u128 = winners.values().map(|support| support.total).sum();
+    let backing: HashSet<&AccountId> = winners.values()
+        .flat_map(|support| support.voters.iter())
+        .map(|(voter, _)| voter)
+        .collect();
+    let voted: u128 = voter_pages.iter()
+        .flat_map(|page| page.iter())
+        .filter(|voter| backing.contains(&voter.0))
+        .map(|voter| voter.1 as u128)
+        .sum();
+    let difference = supported.abs_diff(voted);
+    if difference as f64 <= voted as f64 * CONSERVATION_TOLERANCE {
+        return None;
+    }
+    Some(format!("stake not conserved: supports total {} but their voters hold {} (difference {})", supported, voted, difference))
+}
+
+// Gini coefficient of `values`: 0 when all are equal, approaching 1 as one value holds everything
+pub fn gini(values: &[u128]) -> f64 {
+    let total: f64 = values.iter().map(|v| *v as f64).sum();
+    if values.is_empty() || total == 0.0 {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len() as f64;
+    let weighted: f64 = sorted.iter().enumerate().map(|(i, v)| (i + 1) as f64 * *v as f64).sum();
+    2.0 * weighted / (n * total) - (n + 1.0) / n
+}
+
+// p50, p90 and p99 of `values` by nearest rank, all 0 for an empty set
+pub fn stake_percentiles(values: &[u128]) -> StakePercentiles {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let at = |percent: usize| match sorted.len() {
+        0 => 0,
+        len => sorted[(percent * len).div_ceil(100) - 1],
+    };
+    StakePercentiles { p50: at(50), p90: at(90), p99: at(99) }
+}
+
 #[cfg(target_os = "linux")]
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mockall::{mock};
+    use crate::multi_block_state_client::test_storage::MockDummyStorage;
     use mockall::predicate::*;
-    use subxt::utils::Yes;
-    use subxt::storage::Address;
     use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
     use crate::multi_block_state_client::{BlockDetails, ElectionSnapshotPage, MockMultiBlockClientTrait};
     use crate::multi_block_state_client::MockChainClientTrait;
     use crate::raw_state_client::StakingLedger;
-    use crate::models::StakingConfig;
+    use crate::models::{Exclusion, StakingConfig};
     use crate::snapshot::MockSnapshotService;
     use crate::primitives::Hash;
     use crate::multi_block_state_client::Phase;
-    use crate::miner_config::initialize_runtime_constants;
+    use crate::miner_config::{initialize_runtime_constants, ChainContext, MinerConstants};
+    use crate::models::Chain;
 
-    mock! {
-        pub DummyStorage {}
-        
-        #[async_trait::async_trait]
-        impl StorageTrait for DummyStorage {
-            async fn fetch<Addr>(
-                &self,
-                address: &Addr,
-            ) -> Result<Option<<Addr as Address>::Target>, Box<dyn std::error::Error + Send + Sync>>
-            where
-                Addr: Address<IsFetchable = Yes> + Sync + 'static;
-
-            async fn fetch_or_default<Addr>(
-                &self,
-                address: &Addr,
-            ) -> Result<<Addr as Address>::Target, Box<dyn std::error::Error + Send + Sync>>
-            where
-                Addr: Address<IsFetchable = Yes, IsDefaultable = Yes> + Sync + 'static;
-        }
+    type MockClient = MockMultiBlockClientTrait<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>;
+
+    // Client at `block`, the latest one resolving to the zero hash, with no active era. Storage
+    // and validator prefs are left to each test
+    fn mock_client_at(block: Option<Hash>, phase: Phase, desired_targets: u32) -> MockClient {
+        let mut mock_client = MockClient::new();
+        let block_details = BlockDetails {
+            block_hash: Some(block.unwrap_or(Hash::zero())),
+            phase,
+            round: 1,
+            n_pages: 1,
+            desired_targets,
+            _block_number: 100,
+        };
+        mock_client.expect_get_phase()
+            .returning(move |_storage: &MockDummyStorage| Ok(phase));
+        mock_client.expect_get_block_details()
+            .with(always(), eq(block))
+            .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details.clone()));
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
+        mock_client
     }
 
-    // Implement Clone for MockDummyStorage
-    impl Clone for MockDummyStorage {
-        fn clone(&self) -> Self {
-            MockDummyStorage::new()
-        }
+    fn no_commission(_storage: &MockDummyStorage, _validator: AccountId) -> Result<ValidatorPrefs, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(ValidatorPrefs {
+            commission: Perbill::from_parts(0),
+            blocked: false,
+        })
     }
 
-    // Implement From<Storage> for DummyStorage to satisfy the trait bound in tests
-    // This allows MockDummyStorage to be used with get_storage/get_block_details
-    impl From<crate::primitives::Storage> for MockDummyStorage {
-        fn from(_storage: crate::primitives::Storage) -> Self {
-            MockDummyStorage::new()
-        }
+    // Single page snapshot in which one voter bonding 100 nominates every target
+    fn single_voter_snapshot(
+        targets: Vec<AccountId>,
+        desired_validators: u32,
+    ) -> Result<(ElectionSnapshotPage<PolkadotMinerConfig>, StakingConfig, ReconstructionCounts), Box<dyn std::error::Error + Send + Sync>> {
+        Ok((ElectionSnapshotPage::<PolkadotMinerConfig> {
+            voters: vec![BoundedVec::try_from(vec![(
+                AccountId::from([10u8; 32]),
+                100,
+                BoundedVec::try_from(targets.clone()).unwrap()
+            )]).unwrap()],
+            targets: BoundedVec::try_from(targets).unwrap()
+        }, StakingConfig {
+            desired_validators,
+            max_nominations: 16,
+            ..StakingConfig::default()
+        }, ReconstructionCounts::default()))
     }
 
     #[tokio::test]
@@ -418,6 +1182,7 @@ mod tests {
             .with(always(), eq(None))
             .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details_clone.clone()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -437,24 +1202,45 @@ mod tests {
             }, StakingConfig {
                 desired_validators: 10,
                 max_nominations: 16,
-                min_nominator_bond: 0,
-                min_validator_bond: 0,
-            }))
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| {
+            Ok(BTreeMap::from([(AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap(), 3)]))
         });
         let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
-        let result = simulate_service.simulate(None, None, false, None, None, None).await;
+        let result = simulate_service.simulate(None, None, false, None, None, None, None).await;
         assert!(result.is_ok());
         let simulation_result = result.unwrap();
+        assert_eq!(simulation_result.staking_stats.total_self_stake, 0);
+        assert_eq!(simulation_result.staking_stats.total_nominated_stake, 100);
+        assert_eq!(simulation_result.warnings, vec!["requested 10 validators but only 1 candidates available; electing all 1".to_string()]);
+        assert_eq!(simulation_result.assignments, vec![VoterAssignment {
+            voter: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(),
+            edges: vec![AssignmentEdge { validator: "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string(), stake: 100 }],
+        }]);
+        assert_eq!(simulation_result.supports, vec![RawSupport {
+            validator: "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string(),
+            total: "100".to_string(),
+            voters: vec![("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(), "100".to_string())],
+        }]);
+        assert_eq!(simulation_result.page_supports, vec![PageSupports {
+            page: 0,
+            winner_count: 1,
+            winners: simulation_result.supports.clone(),
+        }]);
         assert_eq!(simulation_result.active_validators, vec![Validator {
             stash: "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string(),
             self_stake: 0,
             total_stake: 100,
             commission: 0.0,
+            commission_parts: 0,
             blocked: false,
             nominations_count: 1,
             nominations: vec![ValidatorNomination {
                 nominator: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(),
                 stake: 100,
+                pool_id: Some(3),
             }],
         }]);
     }
@@ -483,30 +1269,16 @@ mod tests {
             .with(always(), eq(None))
             .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details_clone.clone()));
 
-        // Validator 1
-        mock_client.expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2").unwrap())));
-
-        mock_client.expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2").unwrap(),
-                total: 100,
-                active: 100,
-                unlocking: vec![],
-            })));
-
-        // Validator 2
-        mock_client.expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("3DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2").unwrap())));
-
-        mock_client.expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("3DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2").unwrap(),
-                total: 0,
-                active: 0,
-                unlocking: vec![],
-            })));
+        // Only the first validator has enough bond
+        let bonded = AccountId::from_ss58check("5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2").unwrap();
+        mock_client.expect_ledgers_batch()
+            .returning(move |_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let active = if stash == bonded { 100 } else { 0 };
+                let ledger = StakingLedger { active, total: active, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -528,22 +1300,28 @@ mod tests {
                 max_nominations: 16,
                 min_nominator_bond: 100,
                 min_validator_bond: 100,
-            }))
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
         });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
         let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
-        let result = simulate_service.simulate(None, None, false, None, Some(100), Some(100)).await;
+        let result = simulate_service.simulate(None, None, false, None, Some(100), Some(100), None).await;
         assert!(result.is_ok());
         let simulation_result = result.unwrap();
+        assert_eq!(simulation_result.staking_stats.total_self_stake, 0);
+        assert_eq!(simulation_result.staking_stats.total_nominated_stake, 100);
         assert_eq!(simulation_result.active_validators, vec![Validator {
             stash: "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string(),
             self_stake: 0,
             total_stake: 100,
             commission: 0.0,
+            commission_parts: 0,
             blocked: false,
             nominations_count: 1,
             nominations: vec![ValidatorNomination {
                 nominator: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(),
                 stake: 100,
+                pool_id: None,
             }],
         }]);
     }
@@ -572,6 +1350,7 @@ mod tests {
             .with(always(), eq(None))
             .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details_clone.clone()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -602,12 +1381,12 @@ mod tests {
             }, StakingConfig {
                 desired_validators: 10,
                 max_nominations: 16,
-                min_nominator_bond: 0,
-                min_validator_bond: 0,
-            }))
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
         });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
         let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
-        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None).await;
+        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None, None).await;
         assert!(result.is_ok());
         let simulation_result = result.unwrap();
         assert_eq!(simulation_result.active_validators, vec![Validator {
@@ -615,11 +1394,13 @@ mod tests {
             self_stake: 0,
             total_stake: 100,
             commission: 0.0,
+            commission_parts: 0,
             blocked: false,
             nominations_count: 1,
             nominations: vec![ValidatorNomination {
                 nominator: "5GE5XFDHirGGeYNNUCwCBks1rsSWMomj2AqNyZVFsKVUqWZD".to_string(),
                 stake: 100,
+                pool_id: None,
             }],
         }]);
     }
@@ -648,6 +1429,7 @@ mod tests {
             .with(always(), eq(None))
             .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details_clone.clone()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -675,12 +1457,12 @@ mod tests {
             }, StakingConfig {
                 desired_validators: 10,
                 max_nominations: 16,
-                min_nominator_bond: 0,
-                min_validator_bond: 0,
-            }))
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
         });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
         let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
-        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None).await;
+        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None, None).await;
         assert!(result.is_ok());
         let simulation_result = result.unwrap();
         assert_eq!(simulation_result.active_validators.len(), 1);
@@ -711,6 +1493,7 @@ mod tests {
             .with(always(), eq(None))
             .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(block_details_clone.clone()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -745,12 +1528,12 @@ mod tests {
             }, StakingConfig {
                 desired_validators: 10,
                 max_nominations: 16,
-                min_nominator_bond: 0,
-                min_validator_bond: 0,
-            }))
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
         });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
         let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
-        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None).await;
+        let result = simulate_service.simulate(None, None, false, Some(manual_override), None, None, None).await;
         assert!(result.is_ok());
         let simulation_result = result.unwrap();
         assert!(!simulation_result.active_validators.is_empty());
@@ -758,4 +1541,498 @@ mod tests {
         assert_eq!(validator_5e9y.total_stake, 200);
         assert_eq!(validator_5e9y.nominations_count, 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unelected_candidates() {
+        let winner = AccountId::from([1u8; 32]);
+        let backed = AccountId::from([2u8; 32]);
+        let unbacked = AccountId::from([3u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (AccountId::from([10u8; 32]), 100, BoundedVec::try_from(vec![winner.clone(), backed.clone()]).unwrap()),
+            (AccountId::from([11u8; 32]), 50, BoundedVec::try_from(vec![backed.clone()]).unwrap()),
+        ]).unwrap()];
+        let mut winners = BTreeMap::new();
+        winners.insert(winner.clone(), Support { total: 100, voters: vec![(AccountId::from([10u8; 32]), 100)] });
+
+        let unelected = unelected_candidates::<PolkadotMinerConfig>(&[winner, unbacked.clone(), backed.clone()], &voter_pages, &winners);
+        assert_eq!(unelected, vec![
+            UnelectedCandidate {
+                stash: backed.to_ss58check(),
+                backing_stake: 150,
+                backers_count: 2,
+                reason: UnelectedReason::InsufficientSupport,
+            },
+            UnelectedCandidate {
+                stash: unbacked.to_ss58check(),
+                backing_stake: 0,
+                backers_count: 0,
+                reason: UnelectedReason::NoSupport,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_elected_count_warning() {
+        let backed = AccountId::from([1u8; 32]);
+        let zero_backed = AccountId::from([2u8; 32]);
+        let unbacked = AccountId::from([3u8; 32]);
+        let targets = [backed.clone(), zero_backed.clone(), unbacked.clone()];
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (AccountId::from([10u8; 32]), 100, BoundedVec::try_from(vec![backed.clone()]).unwrap()),
+            (AccountId::from([11u8; 32]), 0, BoundedVec::try_from(vec![zero_backed.clone()]).unwrap()),
+        ]).unwrap()];
+
+        assert_eq!(elected_count_warning::<PolkadotMinerConfig>(1, 1, &targets, &voter_pages, 10), None);
+        // Fewer candidates than requested is reported when preparing the election
+        assert_eq!(elected_count_warning::<PolkadotMinerConfig>(3, 5, &targets, &voter_pages, 10), None);
+        assert_eq!(
+            elected_count_warning::<PolkadotMinerConfig>(1, 2, &targets, &voter_pages, 10),
+            Some("elected 1 of 2 requested validators: only 1 of 3 candidates are backed by a voter with stake".to_string()),
+        );
+        assert_eq!(
+            elected_count_warning::<PolkadotMinerConfig>(0, 1, &targets, &voter_pages, 0),
+            Some("elected 0 of 1 requested validators: the solution holds at most 0 winners (Pages x MaxWinnersPerPage)".to_string()),
+        );
+        assert_eq!(
+            elected_count_warning::<PolkadotMinerConfig>(0, 1, &targets, &voter_pages, 10),
+            Some("elected 0 of 1 requested validators: the other winners lost their backing when the solution was trimmed to MaxBackersPerWinner or MaxLength".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_stake_conservation_warning() {
+        let winner = AccountId::from([1u8; 32]);
+        let voter = AccountId::from([10u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (voter.clone(), 300, BoundedVec::try_from(vec![winner.clone()]).unwrap()),
+            (AccountId::from([11u8; 32]), 50, BoundedVec::try_from(vec![AccountId::from([2u8; 32])]).unwrap()),
+        ]).unwrap()];
+
+        // The unused stake of the voter without an elected target is not expected in the supports
+        let mut winners = BTreeMap::new();
+        winners.insert(winner.clone(), Support { total: 300, voters: vec![(voter.clone(), 300)] });
+        assert_eq!(stake_conservation_warning::<PolkadotMinerConfig>(&voter_pages, &winners), None);
+
+        winners.insert(winner.clone(), Support { total: 200, voters: vec![(voter.clone(), 200)] });
+        assert_eq!(
+            stake_conservation_warning::<PolkadotMinerConfig>(&voter_pages, &winners),
+            Some("stake not conserved: supports total 200 but their voters hold 300 (difference 100)".to_string()),
+        );
+    }
+
+    #[test]
+    fn test_min_active_nominator_stake() {
+        let winner = AccountId::from([1u8; 32]);
+        let loser = AccountId::from([2u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (winner.clone(), 10, BoundedVec::try_from(vec![winner.clone()]).unwrap()),
+            (AccountId::from([10u8; 32]), 300, BoundedVec::try_from(vec![winner.clone(), loser.clone()]).unwrap()),
+        ]).unwrap(), BoundedVec::try_from(vec![
+            (AccountId::from([11u8; 32]), 50, BoundedVec::try_from(vec![loser.clone()]).unwrap()),
+        ]).unwrap()];
+        let mut winners = BTreeMap::new();
+        winners.insert(winner.clone(), Support {
+            total: 310,
+            voters: vec![(winner.clone(), 10), (AccountId::from([10u8; 32]), 300)],
+        });
+
+        // The winner's self vote and the voter backing only the loser do not count
+        assert_eq!(min_active_nominator_stake::<PolkadotMinerConfig>(&voter_pages, &winners), 300);
+        assert_eq!(min_active_nominator_stake::<PolkadotMinerConfig>(&voter_pages, &BTreeMap::new()), 0);
+    }
+
+    #[test]
+    fn test_unused_nominator_stake() {
+        let winner = AccountId::from([1u8; 32]);
+        let loser = AccountId::from([2u8; 32]);
+        let split = AccountId::from([10u8; 32]);
+        let stranded = AccountId::from([11u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (winner.clone(), 10, BoundedVec::try_from(vec![winner.clone()]).unwrap()),
+            (loser.clone(), 20, BoundedVec::try_from(vec![loser.clone()]).unwrap()),
+            (split.clone(), 300, BoundedVec::try_from(vec![winner.clone(), loser.clone()]).unwrap()),
+            (AccountId::from([12u8; 32]), 40, BoundedVec::try_from(vec![winner.clone()]).unwrap()),
+        ]).unwrap(), BoundedVec::try_from(vec![
+            (stranded.clone(), 50, BoundedVec::try_from(vec![loser.clone()]).unwrap()),
+        ]).unwrap()];
+        let mut winners = BTreeMap::new();
+        winners.insert(winner.clone(), Support {
+            total: 310,
+            voters: vec![(winner.clone(), 10), (split.clone(), 260), (AccountId::from([12u8; 32]), 40)],
+        });
+
+        // Validators, elected or not, and fully assigned nominators are left out
+        let unused = unused_nominator_stake::<PolkadotMinerConfig>(&[winner, loser], &voter_pages, &winners);
+        assert_eq!(unused, vec![
+            NominatorUnusedStake {
+                nominator: stranded.to_ss58check(),
+                bonded_stake: 50,
+                assigned_stake: 0,
+                unused_stake: 50,
+            },
+            NominatorUnusedStake {
+                nominator: split.to_ss58check(),
+                bonded_stake: 300,
+                assigned_stake: 260,
+                unused_stake: 40,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_wasted_voters() {
+        let winner = AccountId::from([1u8; 32]);
+        let loser = AccountId::from([2u8; 32]);
+        let other_loser = AccountId::from([3u8; 32]);
+        let stranded = AccountId::from([10u8; 32]);
+        let small = AccountId::from([11u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![BoundedVec::try_from(vec![
+            (winner.clone(), 10, BoundedVec::try_from(vec![winner.clone()]).unwrap()),
+            (loser.clone(), 20, BoundedVec::try_from(vec![loser.clone()]).unwrap()),
+            (AccountId::from([12u8; 32]), 300, BoundedVec::try_from(vec![loser.clone(), winner.clone()]).unwrap()),
+            (small.clone(), 40, BoundedVec::try_from(vec![loser.clone()]).unwrap()),
+        ]).unwrap(), BoundedVec::try_from(vec![
+            (stranded.clone(), 50, BoundedVec::try_from(vec![loser.clone(), other_loser.clone()]).unwrap()),
+        ]).unwrap()];
+        let mut winners = BTreeMap::new();
+        winners.insert(winner.clone(), Support {
+            total: 310,
+            voters: vec![(winner.clone(), 10), (AccountId::from([12u8; 32]), 300)],
+        });
+
+        // A single elected target is enough, and the unelected validator's self vote is not counted
+        let wasted = wasted_voters::<PolkadotMinerConfig>(&[winner, loser.clone(), other_loser.clone()], &voter_pages, &winners);
+        assert_eq!(wasted, vec![
+            WastedVoter { nominator: stranded.to_ss58check(), stake: 50, targets: vec![loser.to_ss58check(), other_loser.to_ss58check()] },
+            WastedVoter { nominator: small.to_ss58check(), stake: 40, targets: vec![loser.to_ss58check()] },
+        ]);
+    }
+
+    #[test]
+    fn test_gini() {
+        assert_eq!(gini(&[]), 0.0);
+        assert_eq!(gini(&[0, 0]), 0.0);
+        assert_eq!(gini(&[5, 5, 5, 5]), 0.0);
+        assert!((gini(&[0, 0, 0, 100]) - 0.75).abs() < 1e-12);
+        assert!((gini(&[3, 1, 2]) - 2.0 / 9.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_stake_percentiles() {
+        assert_eq!(stake_percentiles(&[]), StakePercentiles::default());
+        assert_eq!(stake_percentiles(&[7]), StakePercentiles { p50: 7, p90: 7, p99: 7 });
+        let stakes: Vec<u128> = (1..=100).rev().collect();
+        assert_eq!(stake_percentiles(&stakes), StakePercentiles { p50: 50, p90: 90, p99: 99 });
+        assert_eq!(stake_percentiles(&[1, 2, 3, 1000]), StakePercentiles { p50: 2, p90: 1000, p99: 1000 });
+    }
+
+    #[test]
+    fn test_sample_voters() {
+        let voter = |n: u8| (AccountId::from([n; 32]), n as u64, BoundedVec::try_from(vec![AccountId::from([0u8; 32])]).unwrap());
+        let voter_pages = || -> Vec<VoterSnapshotPage<PolkadotMinerConfig>> {
+            (0..5u8).map(|page| BoundedVec::try_from(vec![voter(page * 2 + 1), voter(page * 2 + 2)]).unwrap()).collect()
+        };
+        let sampled_ids = |pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>>| -> Vec<u64> {
+            pages.iter().flat_map(|page| page.iter().map(|voter| voter.1)).collect()
+        };
+
+        let sample = VoterSample { size: 4, seed: 42 };
+        let first = sampled_ids(sample_voters::<PolkadotMinerConfig>(voter_pages(), sample).unwrap());
+        let second = sampled_ids(sample_voters::<PolkadotMinerConfig>(voter_pages(), sample).unwrap());
+        // Same seed, same sample, still in snapshot order
+        assert_eq!(first.len(), 4);
+        assert_eq!(first, second);
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let all = sampled_ids(sample_voters::<PolkadotMinerConfig>(voter_pages(), VoterSample { size: 20, seed: 1 }).unwrap());
+        assert_eq!(all, (1..=10).collect::<Vec<u64>>());
+    }
+
+    #[tokio::test]
+    async fn test_simulate_excluded_candidate_not_elected() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Snapshot(0), 10);
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let kept = "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2";
+        let excluded = "5E9yWMxT1CoRPo7CxXQ4uLpHBmwzjFfJDV87dDMGxDo6WuMa";
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(move |_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from_ss58check(kept).unwrap(), AccountId::from_ss58check(excluded).unwrap()], 10)
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+
+        let manual_override = Override::with_exclusions(None, vec![], vec![excluded.to_string()]);
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
+        let (simulation_result, exclusions) = explain::with_explain(
+            simulate_service.simulate(None, None, false, manual_override, None, None, None)
+        ).await;
+        let elected: Vec<String> = simulation_result.unwrap().active_validators.into_iter().map(|v| v.stash).collect();
+        assert_eq!(elected, vec![kept.to_string()]);
+        assert_eq!(exclusions, vec![Exclusion {
+            account: excluded.to_string(),
+            role: ExcludedRole::Candidate,
+            reason: ExclusionReason::ManuallyRemoved,
+            detail: None,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_sweep_fetches_snapshot_once() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Snapshot(0), 10);
+        mock_client.expect_get_storage().with(eq(None)).times(1).returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().times(1).returning(|_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from([1u8; 32])], 10)
+        });
+        snapshot_service.expect_get_pool_accounts().times(1).returning(|_| Ok(BTreeMap::new()));
+
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
+        let results = simulate_service.simulate_sweep(None, vec![0, 5], None, false, None, None, None, None).await.unwrap();
+        let iterations: Vec<usize> = results.iter().map(|r| r.run_parameters.iterations).collect();
+        assert_eq!(iterations, vec![0, 5]);
+        assert_eq!(results[0].score, results[1].score);
+        assert_eq!(results[0].score.sum_stake, 100);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_reduce_pair_fetches_snapshot_once() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Snapshot(0), 10);
+        mock_client.expect_get_storage().with(eq(None)).times(1).returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().times(1).returning(|_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from([1u8; 32])], 10)
+        });
+        snapshot_service.expect_get_pool_accounts().times(1).returning(|_| Ok(BTreeMap::new()));
+
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
+        let (without_reduce, with_reduce) = simulate_service.simulate_reduce_pair(None, None, None, None, None, None).await.unwrap();
+        assert!(!without_reduce.run_parameters.reduce);
+        assert!(with_reduce.run_parameters.reduce);
+        assert_eq!(without_reduce.score, with_reduce.score);
+        assert_eq!(with_reduce.score.sum_stake, 100);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_unsigned_mode_always_reduces() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Unsigned(10), 1);
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(|_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from([1u8; 32])], 1)
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service))
+            .with_unsigned_pages(Some(1));
+        let result = simulate_service.simulate(None, None, false, None, None, None, None).await.unwrap();
+        assert!(result.run_parameters.reduce);
+        assert_eq!(result.run_parameters.unsigned_pages, Some(1));
+        assert_eq!(result.solution_pages.len(), 1);
+        assert_eq!(result.score.sum_stake, 100);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_unsigned_mode_mines_most_significant_pages() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Unsigned(10), 1);
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let target = AccountId::from([1u8; 32]);
+        let low_voter = AccountId::from([10u8; 32]);
+        let high_voter = AccountId::from([11u8; 32]);
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(move |_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            let (mut snapshot, config, reconstruction) = single_voter_snapshot(vec![target.clone()], 1)?;
+            // Page 0 is the least significant, the last page the most significant
+            snapshot.voters = BoundedVec::try_from(vec![
+                BoundedVec::try_from(vec![(low_voter.clone(), 100, BoundedVec::try_from(vec![target.clone()]).unwrap())]).unwrap(),
+                BoundedVec::try_from(vec![(high_voter.clone(), 300, BoundedVec::try_from(vec![target.clone()]).unwrap())]).unwrap(),
+            ]).unwrap();
+            Ok((snapshot, config, reconstruction))
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+
+        let constants = MinerConstants {
+            election_pallet: "MultiBlockElection".to_string(),
+            pages: 2,
+            chain_pages: 2,
+            max_winners_per_page: 1,
+            max_backers_per_winner: 2,
+            voter_snapshot_per_block: 2,
+            target_snapshot_per_block: 2,
+            max_length: 100000000,
+        };
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service))
+            .with_unsigned_pages(Some(1));
+        let result = ChainContext::new(Chain::Polkadot, constants)
+            .scope(simulate_service.simulate(None, None, false, None, None, None, None))
+            .await
+            .unwrap();
+        assert_eq!(result.solution_pages.len(), 1);
+        assert_eq!(result.solution_pages[0].page, 1);
+        assert_eq!(result.score.sum_stake, 300);
+        let assigned: Vec<&str> = result.assignments.iter().map(|assignment| assignment.voter.as_str()).collect();
+        assert_eq!(assigned, vec![to_ss58(&AccountId::from([11u8; 32])).as_str()]);
+        assert_eq!(result.unused_stake, vec![NominatorUnusedStake {
+            nominator: to_ss58(&AccountId::from([10u8; 32])),
+            bonded_stake: 100,
+            assigned_stake: 0,
+            unused_stake: 100,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_dumps_snapshot_before_mining() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Signed(10), 1);
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(|_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from([1u8; 32])], 1)
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+
+        let dumped = Arc::new(std::sync::Mutex::new(None));
+        let sink = dumped.clone();
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service))
+            .with_dump_snapshot(Some(Box::new(move |snapshot: &Snapshot| {
+                *sink.lock().unwrap() = Some((snapshot.validators.len(), snapshot.nominators.len()));
+                Ok(())
+            })));
+        // A zero budget makes mining fail, after the input was prepared
+        let (result, _) = budget::with_max_duration(
+            Some(std::time::Duration::ZERO),
+            simulate_service.simulate(None, None, false, None, None, None, None),
+        ).await;
+        assert_eq!(result.unwrap_err().to_string(), "Mining exceeded the --max-duration budget of 0s");
+        assert_eq!(*dumped.lock().unwrap(), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_elect() {
+        initialize_runtime_constants();
+        let alice = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string();
+        let bob = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y".to_string();
+        let validator = "5DLAjiZbVGBG1w5xNTaPuHXXVpvzEqWFhw4kwWt7YcNQnKQ2".to_string();
+        let other = "5GNJqTPyNqANBkUVMN1LPPrxXnFouWXoe2wNSmmEoLctxiZY".to_string();
+        let voters = vec![
+            (alice.clone(), 100, vec![validator.clone()]),
+            (bob.clone(), 50, vec![other.clone()]),
+        ];
+        let targets = vec![validator.clone(), other.clone()];
+
+        let result = elect::<PolkadotMinerConfig>(&voters, &targets, 1, false).unwrap();
+        assert_eq!(result.winners, vec![RawSupport {
+            validator: validator.clone(),
+            total: "100".to_string(),
+            voters: vec![(alice.clone(), "100".to_string())],
+        }]);
+        assert_eq!(result.score.minimal_stake, 100);
+
+        assert!(elect::<PolkadotMinerConfig>(&[], &targets, 1, false).is_err());
+        let too_many = vec![voters[0].clone(), voters[1].clone(), (validator.clone(), 10, vec![other.clone()])];
+        assert!(elect::<PolkadotMinerConfig>(&too_many, &targets, 1, false).is_err());
+        let invalid = vec![(alice, 100, vec!["not-an-address".to_string()])];
+        assert!(elect::<PolkadotMinerConfig>(&invalid, &targets, 1, false).is_err());
+    }
+
+    #[test]
+    fn test_override_check_votes_per_voter() {
+        let manual = Override {
+            voters: vec![
+                ("a".to_string(), 100, vec!["x".to_string(), "y".to_string()]),
+                ("b".to_string(), 100, vec!["x".to_string(), "y".to_string(), "z".to_string()]),
+            ],
+            ..Default::default()
+        };
+        assert!(manual.check_votes_per_voter(3).is_ok());
+        let error = manual.check_votes_per_voter(1).unwrap_err().to_string();
+        assert_eq!(error, "Manual override voters exceed MaxVotesPerVoter (1): a nominates 2 targets, b nominates 3 targets");
+    }
+
+    #[test]
+    fn test_override_with_exclusions() {
+        assert!(Override::with_exclusions(None, vec![], vec![]).is_none());
+        let file_override = Override { candidates_remove: vec!["a".to_string()], ..Default::default() };
+        let merged = Override::with_exclusions(Some(file_override), vec!["v".to_string()], vec!["b".to_string()]).unwrap();
+        assert_eq!(merged.voters_remove, vec!["v".to_string()]);
+        assert_eq!(merged.candidates_remove, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_reports_reconstruction() {
+        initialize_runtime_constants();
+        let mut mock_client = mock_client_at(None, Phase::Snapshot(0), 1);
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client.expect_get_validator_prefs().returning(no_commission);
+
+        let reconstruction = ReconstructionCounts { truncated_voters: 1, dropped_nominations: 4, skipped_accounts: 2 };
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(move |_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            let (snapshot, config, _) = single_voter_snapshot(vec![AccountId::from([1u8; 32])], 1)?;
+            Ok((snapshot, config, reconstruction))
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
+        let simulation_result = simulate_service.simulate(None, None, false, None, None, None, None).await.unwrap();
+        assert_eq!(simulation_result.reconstruction, reconstruction);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_caches_validator_prefs_per_block() {
+        initialize_runtime_constants();
+        let block = Some(Hash::repeat_byte(1));
+        let mut mock_client = mock_client_at(block, Phase::Snapshot(0), 10);
+        mock_client.expect_get_storage().with(eq(block)).returning(|_| Ok(MockDummyStorage::new()));
+        // Second simulation at the same block must be served from the cache
+        mock_client
+            .expect_get_validator_prefs()
+            .times(1)
+            .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
+                commission: Perbill::from_percent(5),
+                blocked: false,
+            }));
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(|_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            single_voter_snapshot(vec![AccountId::from([1u8; 32])], 10)
+        });
+        snapshot_service.expect_get_pool_accounts().returning(|_| Ok(BTreeMap::new()));
+        let simulate_service = SimulateServiceImpl::new(Arc::new(mock_client), Arc::new(snapshot_service));
+        for _ in 0..2 {
+            let simulation_result = simulate_service.simulate(block, None, false, None, None, None, None).await.unwrap();
+            assert_eq!(simulation_result.active_validators[0].commission, 0.05);
+        }
+    }
+
+    #[test]
+    fn test_parse_account_ss58_and_hex() {
+        let ss58 = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+        let raw: [u8; 32] = AccountId::from_ss58check(ss58).unwrap().into();
+        let hex_address = format!("0x{}", hex::encode(raw));
+        assert_eq!(parse_account(ss58).unwrap(), parse_account(&hex_address).unwrap());
+    }
+
+    #[test]
+    fn test_parse_account_malformed_hex() {
+        let short = parse_account("0x1234").unwrap_err().to_string();
+        assert!(short.contains("expected 32 bytes, got 2"));
+        let invalid = parse_account("0xzz").unwrap_err().to_string();
+        assert!(invalid.starts_with("Invalid hex account '0xzz'"));
+        assert!(parse_account("not-an-address").unwrap_err().to_string().starts_with("Invalid SS58 account"));
+    }
+}
@@ -1,20 +1,28 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 
 use mockall::automock;
 use pallet_election_provider_multi_block::unsigned::miner::MinerConfig;
 use sp_core::H256;
-use sp_core::crypto::{Ss58Codec};
 use sp_core::Get;
 use futures::future::join_all;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::multi_block_state_client::{BlockDetails, ChainClientTrait, ElectionSnapshotPage, MultiBlockClientTrait, StorageTrait, TargetSnapshotPage, VoterData, VoterSnapshotPage};
-use crate::primitives::{AccountId, Storage};
+use crate::multi_block_state_client::{BlockDetails, ChainClientTrait, ElectionSnapshotPage, LedgerResults, MultiBlockClientTrait, Phase, StorageTrait, TargetSnapshotPage, VoterData, VoterSnapshotPage};
+use crate::primitives::{AccountId, EraIndex, Storage};
+use pallet_staking::ValidatorPrefs;
 use crate::raw_state_client::RawClientTrait;
-use frame_support::BoundedVec;
+use frame_support::{BoundedVec, PalletId};
+use parity_scale_codec::Encode;
+use sp_runtime::traits::AccountIdConversion;
+use sp_staking::currency_to_vote::{CurrencyToVote, U128CurrencyToVote};
+use crate::budget;
+use crate::explain;
+use crate::progress;
+use crate::miner_config::{self, to_ss58};
 use crate::{
-    models::{Snapshot, SnapshotNominator, SnapshotValidator, StakingConfig}, 
+    models::{EraInfo, ExcludedRole, ExclusionReason, ReconstructionCounts, ScaleSnapshot, Snapshot, SnapshotBlock, SnapshotNominator, SnapshotValidator, NominatorSlots, StakingConfig, ValidatorBackers, ValidatorNomination}, 
     raw_state_client::RpcClient
 };
 
@@ -29,11 +37,37 @@ where
         &self,
         block: Option<H256>,
     ) -> Result<Snapshot, Box<dyn std::error::Error + Send + Sync>>;
+    async fn build_validators(
+        &self,
+        block: Option<H256>,
+    ) -> Result<Snapshot, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_snapshot_data_from_multi_block(
         &self,
         block_details: &BlockDetails,
         storage: &S,
-    ) -> Result<(ElectionSnapshotPage<MC>, StakingConfig), Box<dyn std::error::Error + Send + Sync>>;
+    ) -> Result<(ElectionSnapshotPage<MC>, StakingConfig, ReconstructionCounts), Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_pool_accounts(
+        &self,
+        block: Option<H256>,
+    ) -> Result<BTreeMap<AccountId, u32>, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_backers(
+        &self,
+        block: Option<H256>,
+        validator: AccountId,
+    ) -> Result<ValidatorBackers, Box<dyn std::error::Error + Send + Sync>>;
+    async fn get_era(
+        &self,
+        block: Option<H256>,
+    ) -> Result<EraInfo, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// Pallet id of NominationPools on Polkadot and Kusama, used to derive the pool accounts
+const NOMINATION_POOLS_PALLET_ID: PalletId = PalletId(*b"py/nopls");
+
+// Bonded account of a nomination pool, i.e. the account that votes on behalf of the pool members
+pub fn pool_bonded_account(pool_id: u32) -> AccountId {
+    // AccountType::Bonded is the first variant, so it encodes as 0
+    NOMINATION_POOLS_PALLET_ID.into_sub_account_truncating((0u8, pool_id))
 }
 
 pub struct SnapshotServiceImpl<
@@ -48,6 +82,8 @@ where
 {
     pub raw_state_client: Arc<RawC>,
     pub multi_block_state_client: Arc<MBC>,
+    skip_errors: bool,
+    round: Option<u32>,
     _phantom: std::marker::PhantomData<(RC, CC, S, MC)>,
 }
 
@@ -63,9 +99,113 @@ impl<
         Self {
             multi_block_state_client,
             raw_state_client,
+            skip_errors: false,
+            round: None,
             _phantom: std::marker::PhantomData,
         }
     }
+
+    // Drop the accounts that fail to resolve instead of failing the whole snapshot
+    pub fn with_skip_errors(mut self, skip_errors: bool) -> Self {
+        self.skip_errors = skip_errors;
+        self
+    }
+
+    // Read the paged snapshot stored under this round instead of the round the chain reports
+    pub fn with_round(mut self, round: Option<u32>) -> Self {
+        self.round = round;
+        self
+    }
+
+    // Round whose paged snapshot is read, or `None` when the snapshot has to be reconstructed.
+    // A forced round is always read, whatever the phase
+    fn snapshot_round(&self, block_details: &BlockDetails) -> Option<u32> {
+        match self.round {
+            Some(round) => {
+                if round != block_details.round {
+                    info!("Reading the snapshot of round {} instead of the current round {}", round, block_details.round);
+                }
+                Some(round)
+            }
+            None => block_details.phase.has_snapshot().then_some(block_details.round),
+        }
+    }
+
+    fn page_error(&self, block_details: &BlockDetails, kind: &str, page: u32, e: Box<dyn std::error::Error + Send + Sync>) -> Box<dyn std::error::Error + Send + Sync> {
+        match self.round {
+            Some(round) => format!("{} snapshot page {} does not exist for round {} given with --round ({})", kind, page, round, e).into(),
+            None => snapshot_page_error(&block_details.phase, kind, page, e),
+        }
+    }
+}
+
+impl<
+    RC: RpcClient + Send + Sync + 'static,
+    CC: ChainClientTrait + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + Clone + 'static,
+    MC: MinerConfig<AccountId = AccountId> + Send + Sync + Clone + 'static,
+    MBC: MultiBlockClientTrait<CC, MC, S> + Send + Sync + 'static,
+    RawC: RawClientTrait<RC> + Send + Sync + 'static,
+> SnapshotServiceImpl<RC, CC, S, MC, MBC, RawC> {
+    // Commission and blocked flag of each target, in target order, as recorded for the era
+    // active at the snapshot block, and the number of targets skipped by --skip-errors
+    async fn snapshot_validators(
+        &self,
+        storage: &S,
+        targets: Vec<AccountId>,
+    ) -> Result<(Vec<SnapshotValidator>, usize), Box<dyn std::error::Error + Send + Sync>> {
+        let multi_block_state_client = self.multi_block_state_client.as_ref();
+        let era = prefs_era(multi_block_state_client, storage).await?;
+        let validator_futures: Vec<_> = targets.iter().map(|target| {
+            let target = target.clone();
+            let storage = storage.clone();
+            async move {
+                let validator_prefs = validator_prefs_at(multi_block_state_client, &storage, era, target.clone())
+                    .await
+                    .map_err(|e| format!("Error getting validator prefs: {}", e))?;
+                
+                Ok::<SnapshotValidator, String>(SnapshotValidator {
+                    stash: to_ss58(&target),
+                    commission: validator_prefs.commission.deconstruct() as f64 / 1_000_000_000.0,
+                    commission_parts: validator_prefs.commission.deconstruct(),
+                    commission_bps: None,
+                    blocked: validator_prefs.blocked,
+                })
+            }
+        }).collect();
+        
+        let results = join_all(validator_futures).await;
+        collect_resolved(targets.iter().zip(results), "validator", self.skip_errors).map_err(|e| e.into())
+    }
+
+    // Keep the validators whose ledger holds at least `min_validator_bond` active stake, also
+    // returning how many were skipped by --skip-errors
+    async fn filter_targets_by_bond(
+        &self,
+        storage: &S,
+        validators: Vec<AccountId>,
+        min_validator_bond: u128,
+    ) -> Result<(Vec<AccountId>, usize), Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.multi_block_state_client.as_ref();
+        let mut ledgers = client.ledgers_batch(storage, validators.clone()).await?;
+        let results: Vec<_> = validators.iter().map(|validator| {
+            let validator_ledger = ledgers.remove(validator).unwrap_or(Ok(None))?;
+            if validator_ledger.is_none() {
+                explain::record(validator, ExcludedRole::Candidate, ExclusionReason::NotBonded, None);
+                return Ok(None);
+            }
+            let active_stake = validator_ledger.clone().map_or(0, |l| l.active);
+            let has_sufficient_bond = validator_ledger.is_some_and(|l| l.active >= min_validator_bond);
+            if !has_sufficient_bond {
+                explain::record(validator, ExcludedRole::Candidate, ExclusionReason::BelowMinBond,
+                    Some(format!("active stake {} below {}", active_stake, min_validator_bond)));
+            }
+
+            Ok::<Option<AccountId>, String>(has_sufficient_bond.then_some(validator.clone()))
+        }).collect();
+        let (results, skipped) = collect_resolved(validators.iter().zip(results), "validator", self.skip_errors)?;
+        Ok((results.into_iter().flatten().collect(), skipped))
+    }
 }
 
 #[async_trait::async_trait]
@@ -91,74 +231,101 @@ where
         let multi_block_state_client = self.multi_block_state_client.as_ref();
         let storage = multi_block_state_client.get_storage(block).await?;
         let block_details = multi_block_state_client.get_block_details(&storage, block).await?;
-        let (snapshot, staking_config) = self.get_snapshot_data_from_multi_block(&block_details, &storage)
+        let (snapshot, mut staking_config, mut reconstruction) = self.get_snapshot_data_from_multi_block(&block_details, &storage)
             .await
             .map_err(|e| format!("Error getting snapshot data: {}", e))?;
+        staking_config.nominator_slots = Some(get_nominator_slots(multi_block_state_client, &storage).await?);
 
         let voters = snapshot.voters;
-        let targets = snapshot.targets;
-        
-        let validator_futures: Vec<_> = targets.into_iter().map(|target| {
-            {
-            let storage = storage.clone();
-            async move {
-                let validator_prefs = multi_block_state_client.get_validator_prefs(&storage, target.clone())
-                    .await
-                    .map_err(|e| format!("Error getting validator prefs: {}", e))?;
-                
-                Ok::<SnapshotValidator, String>(SnapshotValidator {
-                    stash: target.to_ss58check(),
-                    commission: validator_prefs.commission.deconstruct() as f64 / 1_000_000_000.0,
-                    blocked: validator_prefs.blocked,
-                })
-            }
-            }
-        }).collect();
-        
-        let validators: Vec<SnapshotValidator> = join_all(validator_futures)
-            .await
-            .into_iter()
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
+        progress::stage("reading validator prefs");
+        let (validators, skipped_validators) = self.snapshot_validators(&storage, snapshot.targets.into_inner()).await?;
+        reconstruction.skipped_accounts += skipped_validators;
         
+        let pool_accounts = self.get_pool_accounts(block_details.block_hash).await?;
+
         let mut nominators: Vec<SnapshotNominator> = Vec::new();
         for voter_page in voters {
             for voter in voter_page {
                 let nominator = SnapshotNominator {
-                    stash: voter.0.to_ss58check(),
+                    stash: to_ss58(&voter.0),
                     stake: voter.1 as u128,
-                    nominations: voter.2.iter().map(|nomination| nomination.to_ss58check()).collect(),
+                    nominations: voter.2.iter().map(to_ss58).collect(),
+                    pool_id: pool_accounts.get(&voter.0).copied(),
                 };
                 nominators.push(nominator);
             }
         }
         
-        Ok(Snapshot { validators, nominators, config: staking_config })
+        Ok(Snapshot { validators, nominators, config: staking_config, reconstruction })
+    }
+
+    // Candidates and their prefs only; the voters are never read, which skips the nominator walk
+    async fn build_validators(
+        &self,
+        block: Option<H256>,
+    ) -> Result<Snapshot, Box<dyn std::error::Error + Send + Sync>> {
+        let client = self.multi_block_state_client.as_ref();
+        let storage = client.get_storage(block).await?;
+        let block_details = client.get_block_details(&storage, block).await?;
+        let mut staking_config = get_staking_config_from_multi_block(client, &block_details, &storage).await?;
+        staking_config.nominator_slots = Some(get_nominator_slots(client, &storage).await?);
+        let (targets, skipped_targets) = if let Some(round) = self.snapshot_round(&block_details) {
+            let page = chain_page(block_details.n_pages, block_details.n_pages - 1);
+            let targets = client.fetch_paged_target_snapshot(&storage, round, page).await
+                .map_err(|e| self.page_error(&block_details, "Target", page, e))?
+                .into_inner();
+            (targets, 0)
+        } else {
+            info!("No snapshot found, getting validators from staking storage");
+            let validators = self.raw_state_client.get_validators(block_details.block_hash).await?;
+            self.filter_targets_by_bond(&storage, validators, staking_config.min_validator_bond).await?
+        };
+        let (validators, skipped_validators) = self.snapshot_validators(&storage, targets).await?;
+        let reconstruction = ReconstructionCounts { skipped_accounts: skipped_targets + skipped_validators, ..ReconstructionCounts::default() };
+        Ok(Snapshot { validators, nominators: Vec::new(), config: staking_config, reconstruction })
     }
 
     async fn get_snapshot_data_from_multi_block(
         &self,
         block_details: &BlockDetails,
         storage: &S,
-    ) -> Result<(ElectionSnapshotPage<MC>, StakingConfig), Box<dyn std::error::Error + Send + Sync>>
+    ) -> Result<(ElectionSnapshotPage<MC>, StakingConfig, ReconstructionCounts), Box<dyn std::error::Error + Send + Sync>>
     {
         let client = self.multi_block_state_client.as_ref();
         let staking_config = get_staking_config_from_multi_block(client, block_details, storage).await?;
-        if block_details.phase.has_snapshot() {
+        if let Some(round) = self.snapshot_round(block_details) {
+            // Most significant page first, so a fetch cut short by --max-duration keeps the largest voters
             let mut voters = Vec::new();
-            for page in 0..block_details.n_pages {
-                let voters_page = client.fetch_paged_voter_snapshot(storage, block_details.round, page).await?;
+            for (fetched, page) in (0..block_details.n_pages).rev().map(|page| chain_page(block_details.n_pages, page)).enumerate() {
+                if fetched > 0 && budget::exceeded() {
+                    budget::cut(format!("fetched {} of {} voter snapshot pages, most significant first", fetched, block_details.n_pages));
+                    break;
+                }
+                progress::step("fetching voter snapshot page", fetched as u32 + 1, block_details.n_pages);
+                let voters_page = client.fetch_paged_voter_snapshot(storage, round, page).await
+                    .map_err(|e| self.page_error(block_details, "Voter", page, e))?;
                 voters.push(voters_page);
             }
+            voters.reverse();
 
-            let target_snapshot = client.fetch_paged_target_snapshot(storage, block_details.round, block_details.n_pages - 1).await?;
+            let target_page = chain_page(block_details.n_pages, block_details.n_pages - 1);
+            progress::stage("fetching target snapshot");
+            let target_snapshot = client.fetch_paged_target_snapshot(storage, round, target_page).await
+                .map_err(|e| self.page_error(block_details, "Target", target_page, e))?;
 
             return Ok((
                 ElectionSnapshotPage::<MC> {
                     voters,
                     targets: target_snapshot,
                 },
-                staking_config));
+                staking_config,
+                ReconstructionCounts::default()));
+        }
+        if block_details.phase == Phase::Emergency {
+            warn!(
+                "Election is in the Emergency phase: only governance can set the next solution. \
+                The snapshot is reconstructed from staking storage and the simulated result may not match the emergency solution."
+            );
         }
         info!("No snapshot found, getting validators and nominators from staking storage");
 
@@ -171,8 +338,14 @@ where
         list_bags.sort_by(|a, b| b.cmp(a));
 
         // Traverse bags       
+        let bags_total = list_bags.len() as u32;
+        let bags_done = AtomicU32::new(0);
+        let walk_cut = AtomicBool::new(false);
+        progress::step("traversing voter bags", 0, bags_total);
         let bag_futures: Vec<_> = list_bags.iter().map(|&bag_threshold| {
             let storage = storage.clone();
+            let bags_done = &bags_done;
+            let walk_cut = &walk_cut;
             async move {
                 let mut bag_accounts: Vec<AccountId> = Vec::new();
                 
@@ -183,12 +356,17 @@ where
                 
                 let mut current_node = list_bag.head;
                 while let Some(voter) = current_node {
+                    if budget::exceeded() {
+                        walk_cut.store(true, Ordering::Relaxed);
+                        break;
+                    }
                     bag_accounts.push(voter.clone());
                     
                     let current_list_node = client.list_nodes(&storage, voter).await
                         .map_err(|e| e.to_string())?;
                     current_node = current_list_node.and_then(|n| n.next);
                 }
+                progress::step("traversing voter bags", bags_done.fetch_add(1, Ordering::Relaxed) + 1, bags_total);
                 
                 Ok(bag_accounts)
             }
@@ -203,65 +381,115 @@ where
                 Err(e) => return Err(format!("Error traversing bag: {}", e).into()),
             }
         }
+        if walk_cut.load(Ordering::Relaxed) {
+            budget::cut(format!("voter list walk stopped after {} voters, the rest of each bag was not read", ordered_accounts.len()));
+        }
         
         let mut voters: Vec<VoterData<MC>> = Vec::new();
+        // Voters whose targets exceed MaxVotesPerVoter and the nominations cut from them
+        let truncated_voters = AtomicUsize::new(0);
+        let dropped_nominations = AtomicUsize::new(0);
         
+        // Stake becomes vote weight like the chain converts it, downscaled once issuance exceeds u64
+        let total_issuance = client.get_total_issuance(storage).await?;
+        if U128CurrencyToVote::will_downscale(total_issuance) == Some(true) {
+            info!("Total issuance {} exceeds u64, voter stakes are downscaled to vote weight", total_issuance);
+        }
+
+        progress::stage("reading voter ledgers");
+        let mut ledgers = client.ledgers_batch(storage, ordered_accounts.clone()).await?;
+        let ordered_accounts = attribute_controllers(client, storage, ordered_accounts, &mut ledgers).await?;
+        let voters_total = ordered_accounts.len() as u32;
+        let voters_done = AtomicU32::new(0);
         let voter_futures: Vec<_> = ordered_accounts.iter().map(|voter| {
             let voter = voter.clone();
+            let ledger = ledgers.remove(&voter).unwrap_or(Ok(None));
             let storage = storage.clone();
             let validator_set = &validator_set;
+            let truncated_voters = &truncated_voters;
+            let dropped_nominations = &dropped_nominations;
             
             async move {
-                let controller = match client.get_controller_from_stash(&storage, voter.clone()).await {
-                    Ok(Some(c)) => c,
-                    Ok(None) => return Ok::<Option<VoterData<MC>>, String>(None),
-                    Err(e) => return Err(e.to_string()),
-                };
-                
-                let stake = match client.ledger(&storage, controller).await {
+                let stake = match ledger {
                     Ok(Some(l)) if l.active > 0 => l,
-                    Ok(_) => return Ok(None),
-                    Err(e) => return Err(e.to_string()),
+                    Ok(Some(_)) => {
+                        explain::record(&voter, ExcludedRole::Voter, ExclusionReason::NoActiveStake, None);
+                        return Ok(None);
+                    }
+                    Ok(None) => {
+                        explain::record(&voter, ExcludedRole::Voter, ExclusionReason::NotBonded, None);
+                        return Ok::<Option<VoterData<MC>>, String>(None);
+                    }
+                    Err(e) => return Err(e),
                 };
                 
                 let nominations = client.get_nominator(&storage, voter.clone()).await
                     .map_err(|e| e.to_string())?;
                 
                 if let Some(nominations) = nominations {
-                    if !nominations.suppressed && !nominations.targets.is_empty() {
+                    if nominations.suppressed {
+                        explain::record(&voter, ExcludedRole::Voter, ExclusionReason::Suppressed, None);
+                    } else if nominations.targets.is_empty() {
+                        explain::record(&voter, ExcludedRole::Voter, ExclusionReason::NotNominating, None);
+                    } else {
                         let max_nominations = MC::MaxVotesPerVoter::get();
                         let mut targets = nominations.targets.clone();
+                        if targets.len() > max_nominations as usize {
+                            truncated_voters.fetch_add(1, Ordering::Relaxed);
+                            dropped_nominations.fetch_add(targets.len() - max_nominations as usize, Ordering::Relaxed);
+                            explain::record(&voter, ExcludedRole::Voter, ExclusionReason::TruncatedTargets,
+                                Some(format!("kept {} of {} nominations", max_nominations, targets.len())));
+                        }
                         targets.truncate(max_nominations as usize);
                         let targets_mc = BoundedVec::try_from(
-                            targets.into_iter().map(|t| t.into()).collect::<Vec<AccountId>>()
+                            targets.into_iter().collect::<Vec<AccountId>>()
                         ).map_err(|_| "Too many targets in voter".to_string())?;
-                        return Ok(Some((voter, stake.active as u64, targets_mc)));
+                        return Ok(Some((voter, U128CurrencyToVote::to_vote(stake.active, total_issuance), targets_mc)));
                     }
                 } else if validator_set.contains(&voter) {
                     return Ok(Some((
                         voter.clone(),
-                        stake.active as u64,
+                        U128CurrencyToVote::to_vote(stake.active, total_issuance),
                         BoundedVec::try_from(vec![voter]).map_err(|_| "Too many targets")?
                     )));
+                } else {
+                    explain::record(&voter, ExcludedRole::Voter, ExclusionReason::NotNominating, None);
                 }
                 
                 Ok(None)
             }
         }).collect();
 
-        let results = join_all(voter_futures).await;
-        // limit to snapshot capacity (per-page slots * pages) to match real snapshot size
+        // Counted as each voter resolves, since all of them are polled at once
+        let results = join_all(voter_futures.into_iter().map(|voter_future| {
+            let voters_done = &voters_done;
+            async move {
+                let result = voter_future.await;
+                progress::step("reading nominations", voters_done.fetch_add(1, Ordering::Relaxed) + 1, voters_total);
+                result
+            }
+        })).await;
+        let (results, skipped_voters) = collect_resolved(ordered_accounts.iter().zip(results), "voter", self.skip_errors)
+            .map_err(|e| format!("Error processing voter: {}", e))?;
+        let results = dedup_voters::<MC>(results.into_iter().flatten().collect());
+        // limit to snapshot capacity (per-page slots * pages) to match real snapshot size. The
+        // voters are in VoterList order, so like the chain the ones in the lowest bags are left out
         let max_voters = MC::VoterSnapshotPerBlock::get() as usize * block_details.n_pages as usize;
-        for result in results {
+        let mut over_capacity = 0;
+        for voter_data in results {
             if voters.len() >= max_voters {
-                break;
-            }
-            match result {
-                Ok(Some(voter_data)) => voters.push(voter_data.clone()),
-                Ok(None) => {},
-                Err(e) => return Err(format!("Error processing voter: {}", e).into()),
+                explain::record(&voter_data.0, ExcludedRole::Voter, ExclusionReason::SnapshotCapacity, None);
+                over_capacity += 1;
+            } else {
+                voters.push(voter_data);
             }
         }
+        if over_capacity > 0 {
+            info!(
+                "{} voters from the lowest VoterList bags left out, the snapshot holds {} ({} pages of {})",
+                over_capacity, max_voters, block_details.n_pages, MC::VoterSnapshotPerBlock::get(),
+            );
+        }
 
         // Reorder voters to match real snapshot pages
         let total_voters = voters.len();
@@ -272,42 +500,24 @@ where
             .collect();
         
         info!("Completed voter data fetching. Total voters: {}", total_voters);
-
-        // Filter validators by min validator bond if > 0 requesting for ledger
-        let min_validator_bond = staking_config.min_validator_bond;
-        
-        let storage = storage.clone();
-        let validators_futures: Vec<_> = validators.into_iter().map(|validator| {
-            let client = client;
-            let storage = storage.clone();
-            async move {
-                let controller = client.get_controller_from_stash(&storage, validator.clone()).await
-                    .map_err(|e| e.to_string())?;
-                if controller.is_none() {
-                    return Ok(None);
-                }
-                let controller = controller.unwrap();
-                let validator_ledger = client.ledger(&storage, controller).await
-                    .map_err(|e| e.to_string())?;
-                let active_stake = validator_ledger.clone().map_or(0, |l| l.active as u64);
-                let has_sufficient_bond = validator_ledger.clone().map_or(false, |l| l.active >= min_validator_bond);
-
-                Ok::<Option<(AccountId, u64)>, String>(has_sufficient_bond.then_some((validator, active_stake)))
-            }
-        }).collect();
-        let results = join_all(validators_futures)
-            .await
-            .into_iter()
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| e.to_string())?;
-        
-        let mut targets_with_stake: Vec<(AccountId, u64)> = Vec::new();
-        for validator in results {
-            if let Some((validator, stake)) = validator {
-                targets_with_stake.push((validator, stake));
-            }
+        let mut reconstruction = ReconstructionCounts {
+            truncated_voters: truncated_voters.into_inner(),
+            dropped_nominations: dropped_nominations.into_inner(),
+            skipped_accounts: skipped_voters,
+        };
+        if reconstruction.truncated_voters > 0 {
+            warn!(
+                "Truncated the targets of {} voters to MaxVotesPerVoter ({}), dropping {} nominations",
+                reconstruction.truncated_voters,
+                MC::MaxVotesPerVoter::get(),
+                reconstruction.dropped_nominations,
+            );
         }
 
+        progress::stage("filtering validators by bond");
+        let (targets, skipped_targets) = self.filter_targets_by_bond(storage, validators, staking_config.min_validator_bond).await?;
+        reconstruction.skipped_accounts += skipped_targets;
+
         // Prepare data for ElectionSnapshotPage
         // divide in pages
         let voters: Vec<VoterSnapshotPage<MC>> = voters
@@ -315,17 +525,326 @@ where
             .map(|page| BoundedVec::try_from(page).map_err(|_| "Too many voters in chunk"))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let targets = TargetSnapshotPage::<MC>::try_from(
-            targets_with_stake.into_iter().map(|(v, _)| v.into()).collect::<Vec<AccountId>>()
-        ).map_err(|_| "Too many targets")?;
+        let targets = TargetSnapshotPage::<MC>::try_from(targets).map_err(|_| "Too many targets")?;
 
         let election_snapshot_page = ElectionSnapshotPage::<MC> {
             voters,
             targets,
         };
 
-        Ok((election_snapshot_page, staking_config))
+        Ok((election_snapshot_page, staking_config, reconstruction))
+    }
+
+    async fn get_pool_accounts(
+        &self,
+        block: Option<H256>,
+    ) -> Result<BTreeMap<AccountId, u32>, Box<dyn std::error::Error + Send + Sync>> {
+        let pool_ids = self.raw_state_client.get_bonded_pool_ids(block)
+            .await
+            .map_err(|e| format!("Error getting bonded pools: {}", e))?;
+        Ok(pool_ids.into_iter().map(|pool_id| (pool_bonded_account(pool_id), pool_id)).collect())
+    }
+
+    // Scan the voter snapshot for everyone nominating `validator`, skipping its own self vote
+    async fn get_backers(
+        &self,
+        block: Option<H256>,
+        validator: AccountId,
+    ) -> Result<ValidatorBackers, Box<dyn std::error::Error + Send + Sync>> {
+        let multi_block_state_client = self.multi_block_state_client.as_ref();
+        let storage = multi_block_state_client.get_storage(block).await?;
+        let block_details = multi_block_state_client.get_block_details(&storage, block).await?;
+        let (snapshot, _, _) = self.get_snapshot_data_from_multi_block(&block_details, &storage)
+            .await
+            .map_err(|e| format!("Error getting snapshot data: {}", e))?;
+        let pool_accounts = self.get_pool_accounts(block_details.block_hash).await?;
+
+        let mut backers: Vec<ValidatorNomination> = snapshot.voters.into_iter()
+            .flatten()
+            .filter(|voter| voter.0 != validator && voter.2.contains(&validator))
+            .map(|voter| ValidatorNomination {
+                nominator: to_ss58(&voter.0),
+                stake: voter.1 as u128,
+                pool_id: pool_accounts.get(&voter.0).copied(),
+            })
+            .collect();
+        backers.sort_by_key(|b| std::cmp::Reverse(b.stake));
+
+        Ok(ValidatorBackers { validator: to_ss58(&validator), backers })
+    }
+
+    async fn get_era(
+        &self,
+        block: Option<H256>,
+    ) -> Result<EraInfo, Box<dyn std::error::Error + Send + Sync>> {
+        let multi_block_state_client = self.multi_block_state_client.as_ref();
+        let storage = multi_block_state_client.get_storage(block).await?;
+        let active_era = multi_block_state_client.get_active_era(&storage)
+            .await?
+            .ok_or("Staking::ActiveEra not found")?;
+        let start_session_index = multi_block_state_client.get_bonded_eras(&storage)
+            .await?
+            .into_iter()
+            .find(|(era, _)| *era == active_era.index)
+            .map(|(_, session)| session);
+        Ok(EraInfo { index: active_era.index, start: active_era.start, start_session_index })
+    }
+}
+
+/// Fail when the block has no election snapshot, so callers can skip the expensive
+/// reconstruction from staking storage.
+pub async fn require_snapshot<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    block: Option<H256>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+{
+    let storage = client.get_storage(block).await?;
+    let block_details = client.get_block_details(&storage, block).await?;
+    if !block_details.phase.has_snapshot() {
+        return Err(format!("No election snapshot at this block (phase {:?}) and reconstruction is disabled", block_details.phase).into());
+    }
+    Ok(())
+}
+
+/// Snapshot pages SCALE encoded as the miner takes them: every voter page as `AllVoterPagesOf`
+/// and the single target page as `TargetPageOf`. Reconstructed from staking storage like any
+/// other snapshot when the block has none. `round` labels the pages when the snapshot service
+/// reads a forced round.
+pub async fn scale_snapshot<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static,
+    Snap: SnapshotService<MC, S>,
+>(
+    client: &MBC,
+    snapshot_service: &Snap,
+    block: Option<H256>,
+    round: Option<u32>,
+) -> Result<ScaleSnapshot, Box<dyn std::error::Error + Send + Sync>> {
+    let storage = client.get_storage(block).await?;
+    let block_details = client.get_block_details(&storage, block).await?;
+    let (snapshot, _, _) = snapshot_service.get_snapshot_data_from_multi_block(&block_details, &storage)
+        .await
+        .map_err(|e| format!("Error getting snapshot data: {}", e))?;
+    Ok(ScaleSnapshot {
+        round: round.unwrap_or(block_details.round),
+        pages: snapshot.voters.len() as u32,
+        voter_pages: snapshot.voters.encode(),
+        targets: snapshot.targets.encode(),
+    })
+}
+
+// Upper bound on the blocks a single find-snapshots scan reads
+pub const MAX_SCANNED_BLOCKS: u32 = 1_000;
+
+// Reject ranges that are reversed or would read more than MAX_SCANNED_BLOCKS blocks
+pub fn check_scan_range(from: u32, to: u32, step: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if from > to {
+        return Err(format!("--from {} is after --to {}", from, to).into());
+    }
+    let scanned = (to - from) / step + 1;
+    if scanned > MAX_SCANNED_BLOCKS {
+        return Err(format!("Scanning {} blocks exceeds the limit of {}; narrow the range or raise --step", scanned, MAX_SCANNED_BLOCKS).into());
+    }
+    Ok(())
+}
+
+// Blocks from `from` to `to` (every `step`-th) whose election phase carries a snapshot
+pub async fn find_snapshots<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static,
+    RC: RpcClient + Send + Sync + 'static,
+    R: RawClientTrait<RC>>(
+    client: &MBC,
+    raw_client: &R,
+    from: u32,
+    to: u32,
+    step: u32,
+) -> Result<Vec<SnapshotBlock>, Box<dyn std::error::Error + Send + Sync>>
+{
+    check_scan_range(from, to, step)?;
+    let mut blocks = Vec::new();
+    for number in (from..=to).step_by(step as usize) {
+        let hash = raw_client.get_block_hash(number).await?
+            .ok_or_else(|| format!("Block {} not found", number))?;
+        let storage = client.get_storage(Some(hash)).await?;
+        let phase = client.get_phase(&storage).await?;
+        if phase.has_snapshot() {
+            let round = client.get_round(&storage).await?;
+            blocks.push(SnapshotBlock { number, hash: format!("{:?}", hash), phase: format!("{:?}", phase), round });
+        }
+    }
+    info!("Found {} of {} scanned blocks with a snapshot", blocks.len(), (to - from) / step + 1);
+    Ok(blocks)
+}
+
+// Chain page holding the `page`-th of the `n_pages` pages used. With --pages below the chain's
+// Pages, the most significant pages of a stored snapshot are read
+fn chain_page(n_pages: u32, page: u32) -> u32 {
+    miner_config::chain_pages().saturating_sub(n_pages) + page
+}
+
+// Snapshot pages can already be cleared while the result is exported, so a failed read in the
+// Export phase points at an earlier block instead of surfacing a bare missing page
+fn snapshot_page_error(phase: &Phase, kind: &str, page: u32, e: Box<dyn std::error::Error + Send + Sync>) -> Box<dyn std::error::Error + Send + Sync> {
+    if !matches!(phase, Phase::Export(_)) {
+        return e;
+    }
+    format!(
+        "{} snapshot page {} is unavailable in the Export phase ({}); pages may have been cleared while the result is exported, use a block from the Signed or Unsigned phase",
+        kind, page, e
+    ).into()
+}
+
+// Values of the accounts that resolved, in order, and how many were dropped. A failed account
+// aborts with its error unless `skip_errors` is set, in which case it is logged and dropped
+fn collect_resolved<'a, T>(
+    results: impl IntoIterator<Item = (&'a AccountId, Result<T, String>)>,
+    kind: &str,
+    skip_errors: bool,
+) -> Result<(Vec<T>, usize), String> {
+    let mut resolved = Vec::new();
+    let mut skipped = 0;
+    for (account, result) in results {
+        match result {
+            Ok(value) => resolved.push(value),
+            Err(e) if skip_errors => {
+                warn!("Skipping {} {}: {}", kind, to_ss58(account), e);
+                skipped += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if skipped > 0 {
+        warn!("Skipped {} {} accounts that failed to resolve", skipped, kind);
+    }
+    Ok((resolved, skipped))
+}
+
+// Keep the first entry of every voter account. The miner takes each voter once, so an account
+// listed twice (e.g. a validator that also nominates) would corrupt its input
+fn dedup_voters<MC: MinerConfig>(voters: Vec<VoterData<MC>>) -> Vec<VoterData<MC>> {
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let voters: Vec<VoterData<MC>> = voters.into_iter().filter(|voter| {
+        let first = seen.insert(voter.0.clone());
+        if !first {
+            explain::record(&voter.0, ExcludedRole::Voter, ExclusionReason::DuplicateVoter, None);
+            duplicates += 1;
+        }
+        first
+    }).collect();
+    if duplicates > 0 {
+        warn!("Dropped {} duplicate voter entries, keeping the first entry of each account", duplicates);
+    }
+    voters
+}
+
+// Era whose recorded prefs apply at the block `storage` was read at, so a run at the latest block
+// and one at the same block given by hash read the same prefs
+pub async fn prefs_era<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    storage: &S,
+) -> Result<Option<EraIndex>, Box<dyn std::error::Error + Send + Sync>>
+{
+    Ok(client.get_active_era(storage).await?.map(|era| era.index))
+}
+
+// Prefs of `validator` recorded for `era` in `Staking::ErasValidatorPrefs`, falling back to the
+// `Staking::Validators` entry when there is no era or the era has no entry for it
+pub async fn validator_prefs_at<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    storage: &S,
+    era: Option<EraIndex>,
+    validator: AccountId,
+) -> Result<ValidatorPrefs, Box<dyn std::error::Error + Send + Sync>>
+{
+    if let Some(era) = era && let Some(prefs) = client.get_eras_validator_prefs(storage, era, validator.clone()).await? {
+        return Ok(prefs);
+    }
+    client.get_validator_prefs(storage, validator).await
+}
+
+// Candidates of `targets` that chilled after the snapshot was taken, read concurrently
+pub async fn chilled_targets<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    storage: &S,
+    targets: &[AccountId],
+) -> Result<HashSet<AccountId>, Box<dyn std::error::Error + Send + Sync>>
+{
+    let checks = join_all(targets.iter().map(|target| async move {
+        client.is_validator(storage, target.clone()).await.map(|validating| (!validating).then(|| target.clone()))
+    })).await;
+    let mut chilled = HashSet::new();
+    for check in checks {
+        if let Some(target) = check.map_err(|e| format!("Error checking validator intent: {}", e))? {
+            chilled.insert(target);
+        }
+    }
+    Ok(chilled)
 }
+
+// Accounts without a ledger of their own that control a stash missing from `accounts` are
+// replaced by that stash, and its ledger is added to `ledgers`, so they are not dropped as unbonded.
+// A failed controller lookup is stored as the account's ledger error, to be skipped or reported
+// like any other account that fails to resolve
+pub async fn attribute_controllers<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    storage: &S,
+    accounts: Vec<AccountId>,
+    ledgers: &mut LedgerResults,
+) -> Result<Vec<AccountId>, Box<dyn std::error::Error + Send + Sync>>
+{
+    let unresolved: Vec<AccountId> = accounts.iter()
+        .filter(|account| matches!(ledgers.get(*account), None | Some(Ok(None))))
+        .cloned()
+        .collect();
+    if unresolved.is_empty() {
+        return Ok(accounts);
+    }
+    let listed: HashSet<&AccountId> = accounts.iter().collect();
+    let stashes = join_all(unresolved.iter().map(|account| client.get_stash_from_controller(storage, account.clone()))).await;
+    let mut attributed = BTreeMap::new();
+    for (controller, stash) in unresolved.into_iter().zip(stashes) {
+        match stash {
+            Ok(Some(stash)) if !listed.contains(&stash) => {
+                attributed.insert(controller, stash);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                ledgers.insert(controller, Err(format!("Error reading the stash it controls: {}", e)));
+            }
+        }
+    }
+    if attributed.is_empty() {
+        return Ok(accounts);
+    }
+    info!("Attributed {} controller accounts to the stash they control", attributed.len());
+    ledgers.extend(client.ledgers_batch(storage, attributed.values().cloned().collect()).await?);
+    Ok(accounts.into_iter().map(|account| attributed.remove(&account).unwrap_or(account)).collect())
 }
 
 pub async fn get_staking_config_from_multi_block<
@@ -337,66 +856,43 @@ pub async fn get_staking_config_from_multi_block<
     block_details: &BlockDetails,
     storage: &S,
 ) -> Result<StakingConfig, Box<dyn std::error::Error + Send + Sync>>
-where
-    MC: Send + Sync + 'static,
 {
     let max_nominations = MC::MaxVotesPerVoter::get();
     let min_nominator_bond = client.get_min_nominator_bond(storage).await?;
     let min_validator_bond = client.get_min_validator_bond(storage).await?;
-    Ok(StakingConfig { desired_validators: block_details.desired_targets, max_nominations, min_nominator_bond, min_validator_bond: min_validator_bond })
+    let minimum_active_stake = client.get_minimum_active_stake(storage).await?;
+    Ok(StakingConfig { desired_validators: block_details.desired_targets, max_nominations, min_nominator_bond, min_validator_bond, minimum_active_stake, nominator_slots: None })
+}
+
+// Nominators registered against the chain's cap, i.e. whether new nominators can still join
+pub async fn get_nominator_slots<
+    C: ChainClientTrait + Send + Sync + 'static,
+    MC: MinerConfig + Send + Sync + 'static,
+    S: StorageTrait + From<Storage> + 'static,
+    MBC: MultiBlockClientTrait<C, MC, S> + Send + Sync + 'static>(
+    client: &MBC,
+    storage: &S,
+) -> Result<NominatorSlots, Box<dyn std::error::Error + Send + Sync>>
+{
+    let count = client.get_nominator_count(storage).await?;
+    let cap = client.get_max_nominators_count(storage).await?;
+    Ok(NominatorSlots::new(count, cap))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mockall::{mock};
+    use std::time::Duration;
+    use crate::multi_block_state_client::test_storage::MockDummyStorage;
+    use sp_core::crypto::Ss58Codec;
     use mockall::predicate::{always, eq};
     use crate::miner_config::polkadot::MinerConfig as PolkadotMinerConfig;
-    use crate::multi_block_state_client::{ListBag, ListNode, MockChainClientTrait, MockMultiBlockClientTrait, Phase, StorageTrait};
+    use crate::multi_block_state_client::{ActiveEraInfo, ListBag, ListNode, MockChainClientTrait, MockMultiBlockClientTrait, Phase};
     use crate::primitives::{AccountId, Hash};
     use crate::raw_state_client::{MockRawClientTrait, MockRpcClient, NominationsLight, StakingLedger};
-    use crate::miner_config::initialize_runtime_constants;
+    use crate::miner_config::{initialize_runtime_constants, ChainContext, MinerConstants};
+    use crate::models::Chain;
     use sp_runtime::Perbill;
-    use pallet_staking::ValidatorPrefs;
-    use subxt::utils::Yes;
-    use subxt::storage::Address;
-    
-    mock! {
-        pub DummyStorage {}
-        
-        #[async_trait::async_trait]
-        impl StorageTrait for DummyStorage {
-            async fn fetch<Addr>(
-                &self,
-                address: &Addr,
-            ) -> Result<Option<<Addr as Address>::Target>, Box<dyn std::error::Error + Send + Sync>>
-            where
-                Addr: Address<IsFetchable = Yes> + Sync + 'static;
-
-            async fn fetch_or_default<Addr>(
-                &self,
-                address: &Addr,
-            ) -> Result<<Addr as Address>::Target, Box<dyn std::error::Error + Send + Sync>>
-            where
-                Addr: Address<IsFetchable = Yes, IsDefaultable = Yes> + Sync + 'static;
-        }
-    }
-
-    // Implement From<Storage> for DummyStorage to satisfy the trait bound in tests
-    // This allows MockDummyStorage to be used with get_storage/get_block_details
-    impl From<crate::primitives::Storage> for MockDummyStorage {
-        fn from(_storage: crate::primitives::Storage) -> Self {
-            MockDummyStorage::new()
-        }
-    }
-
-    impl Clone for MockDummyStorage {
-        fn clone(&self) -> Self {
-            MockDummyStorage::new()
-        }
-    }
-
-    
 
     #[tokio::test]
     async fn test_get_staking_config() {
@@ -410,6 +906,10 @@ mod tests {
             .expect_get_min_validator_bond()
             .returning(|_storage: &MockDummyStorage| Ok(200));
 
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(Some(150)));
+
         let result = get_staking_config_from_multi_block(&mock_client, &BlockDetails {
             block_hash: Some(Hash::zero()),
             phase: Phase::Snapshot(0),
@@ -423,6 +923,7 @@ mod tests {
         let config = result.unwrap();
         assert_eq!(config.min_nominator_bond, 100);
         assert_eq!(config.min_validator_bond, 200);
+        assert_eq!(config.minimum_active_stake, Some(150));
         assert_eq!(config.desired_validators, 10);
         assert_eq!(config.max_nominations, 16);
     }
@@ -439,6 +940,10 @@ mod tests {
             .expect_get_min_validator_bond()
             .returning(|_storage: &MockDummyStorage| Ok(200));
 
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(None));
+
         mock_client
             .expect_fetch_paged_voter_snapshot()
             .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(VoterSnapshotPage::<PolkadotMinerConfig>::new()));
@@ -461,7 +966,7 @@ mod tests {
         }, &MockDummyStorage::new()).await;
 
         assert!(result.is_ok());
-        let (snapshot, config) = result.unwrap();
+        let (snapshot, config, _) = result.unwrap();
         
         assert_eq!(snapshot.voters, vec![VoterSnapshotPage::<PolkadotMinerConfig>::new()]);
         assert_eq!(snapshot.targets, TargetSnapshotPage::<PolkadotMinerConfig>::new());
@@ -472,24 +977,176 @@ mod tests {
     }   
 
     #[tokio::test]
-    async fn test_get_snapshot_data_from_multi_block_no_snapshot() {
-        initialize_runtime_constants();
+    async fn test_get_snapshot_data_from_multi_block_export_missing_page() {
         let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
-
-        mock_client
-            .expect_get_min_nominator_bond()
-            .returning(|_storage: &MockDummyStorage| Ok(0));
-
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
         mock_client
-            .expect_get_min_validator_bond()
-            .returning(|_storage: &MockDummyStorage| Ok(0));
-
-        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+            .expect_fetch_paged_voter_snapshot()
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Err("Voter snapshot not found".into()));
 
-        raw_client
-            .expect_get_validators()
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(MockRawClientTrait::<MockRpcClient>::new()));
+        let result = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Export(2),
+            round: 1,
+            n_pages: 1,
+            desired_targets: 10,
+            _block_number: 100,
+        }, &MockDummyStorage::new()).await;
+
+        let error = result.err().unwrap().to_string();
+        assert!(error.starts_with("Voter snapshot page 0 is unavailable in the Export phase (Voter snapshot not found)"));
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_forced_round() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client
+            .expect_fetch_paged_voter_snapshot()
+            .with(always(), eq(4u32), eq(0u32))
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(VoterSnapshotPage::<PolkadotMinerConfig>::new()));
+        mock_client
+            .expect_fetch_paged_voter_snapshot()
+            .with(always(), eq(9u32), eq(0u32))
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Err("Voter snapshot not found".into()));
+        mock_client
+            .expect_fetch_paged_target_snapshot()
+            .with(always(), eq(4u32), eq(0u32))
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(TargetSnapshotPage::<PolkadotMinerConfig>::new()));
+
+        let mock_client = Arc::new(mock_client);
+        // The phase has moved on to the next round, which has no snapshot yet
+        let block_details = BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Off,
+            round: 5,
+            n_pages: 1,
+            desired_targets: 10,
+            _block_number: 100,
+        };
+
+        let snapshot_service = SnapshotServiceImpl::new(mock_client.clone(), Arc::new(MockRawClientTrait::<MockRpcClient>::new()))
+            .with_round(Some(4));
+        let (snapshot, _, _) = snapshot_service.get_snapshot_data_from_multi_block(&block_details, &MockDummyStorage::new()).await.unwrap();
+        assert_eq!(snapshot.voters.len(), 1);
+
+        let snapshot_service = SnapshotServiceImpl::new(mock_client, Arc::new(MockRawClientTrait::<MockRpcClient>::new()))
+            .with_round(Some(9));
+        let error = snapshot_service.get_snapshot_data_from_multi_block(&block_details, &MockDummyStorage::new()).await.err().unwrap().to_string();
+        assert_eq!(error, "Voter snapshot page 0 does not exist for round 9 given with --round (Voter snapshot not found)");
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_max_duration() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        // Only the most significant page is read once the budget has run out
+        mock_client
+            .expect_fetch_paged_voter_snapshot()
+            .with(always(), eq(1u32), eq(2u32))
+            .times(1)
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(VoterSnapshotPage::<PolkadotMinerConfig>::new()));
+        mock_client
+            .expect_fetch_paged_target_snapshot()
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(TargetSnapshotPage::<PolkadotMinerConfig>::new()));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(MockRawClientTrait::<MockRpcClient>::new()));
+        let block_details = BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Signed(10),
+            round: 1,
+            n_pages: 3,
+            desired_targets: 10,
+            _block_number: 100,
+        };
+        let (result, cuts) = budget::with_max_duration(
+            Some(Duration::ZERO),
+            snapshot_service.get_snapshot_data_from_multi_block(&block_details, &MockDummyStorage::new()),
+        ).await;
+        let (snapshot, _, _) = result.unwrap();
+        assert_eq!(snapshot.voters.len(), 1);
+        assert_eq!(cuts, vec!["fetched 1 of 3 voter snapshot pages, most significant first".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_pages_override() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        // Two of the chain's four pages: the most significant ones, 2 and 3
+        for page in [2u32, 3] {
+            mock_client
+                .expect_fetch_paged_voter_snapshot()
+                .with(always(), eq(1u32), eq(page))
+                .times(1)
+                .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(VoterSnapshotPage::<PolkadotMinerConfig>::new()));
+        }
+        mock_client
+            .expect_fetch_paged_target_snapshot()
+            .with(always(), eq(1u32), eq(3u32))
+            .times(1)
+            .returning(|_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(TargetSnapshotPage::<PolkadotMinerConfig>::new()));
+
+        let constants = MinerConstants {
+            election_pallet: "MultiBlockElection".to_string(),
+            pages: 4,
+            chain_pages: 4,
+            max_winners_per_page: 1,
+            max_backers_per_winner: 1,
+            voter_snapshot_per_block: 2,
+            target_snapshot_per_block: 2,
+            max_length: 100000000,
+        }.with_pages(Some(2)).unwrap();
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(MockRawClientTrait::<MockRpcClient>::new()));
+        let block_details = BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Signed(10),
+            round: 1,
+            n_pages: 2,
+            desired_targets: 10,
+            _block_number: 100,
+        };
+        let (snapshot, _, _) = ChainContext::new(Chain::Polkadot, constants)
+            .scope(snapshot_service.get_snapshot_data_from_multi_block(&block_details, &MockDummyStorage::new()))
+            .await
+            .unwrap();
+        assert_eq!(snapshot.voters.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_no_snapshot() {
+        initialize_runtime_constants();
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+
+        mock_client
+            .expect_get_min_nominator_bond()
+            .returning(|_storage: &MockDummyStorage| Ok(0));
+
+        mock_client
+            .expect_get_min_validator_bond()
+            .returning(|_storage: &MockDummyStorage| Ok(0));
+
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(None));
+
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+
+        raw_client
+            .expect_get_validators()
             .returning(|_at: Option<H256>| Ok(vec![AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap()]));
 
+        mock_client
+            .expect_get_total_issuance()
+            .returning(|_storage: &MockDummyStorage| Ok(1_000_000_000));
         raw_client
             .expect_get_all_list_bags()
             .returning(|_at: Option<H256>| Ok(vec![100]));
@@ -520,17 +1177,11 @@ mod tests {
             })));
               
         mock_client
-            .expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap())));
-
-        mock_client
-            .expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap(),
-                total: 100,
-                active: 100,
-                unlocking: vec![],
-            })));
+            .expect_ledgers_batch()
+            .returning(|_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active: 100, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
         
         mock_client
             .expect_get_nominator()
@@ -540,35 +1191,11 @@ mod tests {
                 suppressed: false,
             })));
         
-        mock_client
-            .expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap())));
-
-        mock_client
-            .expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap(),
-                total: 100,
-                active: 100,
-                unlocking: vec![],
-            })));
         
         mock_client
             .expect_get_nominator()
             .returning(|_storage: &MockDummyStorage, _nominator: AccountId| Ok(None));
 
-        mock_client
-            .expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap())));
-
-        mock_client
-            .expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap(),
-                total: 100,
-                active: 100,
-                unlocking: vec![],
-            })));
 
         let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
         let result = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
@@ -581,7 +1208,7 @@ mod tests {
         }, &MockDummyStorage::new()).await;
 
         assert!(result.is_ok());
-        let (snapshot, config) = result.unwrap();
+        let (snapshot, config, reconstruction) = result.unwrap();
         let voter_targets = BoundedVec::try_from(vec![AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap()]).map_err(|_| "Too many targets in voter").unwrap();
         let voter = (AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap(),
             100,
@@ -603,6 +1230,168 @@ mod tests {
         assert_eq!(config.min_validator_bond, 0);
         assert_eq!(config.desired_validators, 10);
         assert_eq!(config.max_nominations, 16);
+        assert_eq!(reconstruction, ReconstructionCounts::default());
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_downscales_stake() {
+        initialize_runtime_constants();
+        let validator = AccountId::from([1u8; 32]);
+        // Issuance above u64::MAX, so each stake is scaled down to its vote weight
+        let total_issuance = u128::from(u64::MAX) * 3 + 7;
+        let active = u128::from(u64::MAX) * 2;
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client.expect_get_total_issuance().returning(move |_storage: &MockDummyStorage| Ok(total_issuance));
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let validators = vec![validator.clone()];
+        raw_client.expect_get_validators().returning(move |_at: Option<H256>| Ok(validators.clone()));
+        raw_client.expect_get_all_list_bags().returning(|_at: Option<H256>| Ok(vec![100]));
+        let head = validator.clone();
+        mock_client
+            .expect_list_bags()
+            .returning(move |_storage: &MockDummyStorage, _index: u64| Ok(Some(ListBag { head: Some(head.clone()), tail: None })));
+        mock_client
+            .expect_list_nodes()
+            .returning(|_storage: &MockDummyStorage, account: AccountId| Ok(Some(ListNode { id: account, prev: None, next: None })));
+        mock_client
+            .expect_ledgers_batch()
+            .returning(move |_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active, total: active, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
+        mock_client
+            .expect_get_nominator()
+            .returning(|_storage: &MockDummyStorage, _nominator: AccountId| Ok(None));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let (snapshot, _config, _) = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Snapshot(10),
+            round: 1,
+            n_pages: 1,
+            desired_targets: 10,
+            _block_number: 100,
+        }, &MockDummyStorage::new()).await.unwrap();
+
+        let weight = U128CurrencyToVote::to_vote(active, total_issuance);
+        assert!(u128::from(weight) < active);
+        assert_eq!(snapshot.voters[0].iter().map(|voter| voter.1).collect::<Vec<_>>(), vec![weight]);
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_truncates_targets() {
+        initialize_runtime_constants();
+        let nominator = AccountId::from([1u8; 32]);
+        let validator = AccountId::from([2u8; 32]);
+        // Two more targets than MaxVotesPerVoter
+        let max_votes = <PolkadotMinerConfig as MinerConfig>::MaxVotesPerVoter::get() as usize;
+        let targets: Vec<AccountId> = (0..max_votes + 2).map(|i| AccountId::from([100 + i as u8; 32])).collect();
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client.expect_get_total_issuance().returning(|_storage: &MockDummyStorage| Ok(1_000_000_000));
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let validators = vec![validator.clone()];
+        raw_client.expect_get_validators().returning(move |_at: Option<H256>| Ok(validators.clone()));
+        raw_client.expect_get_all_list_bags().returning(|_at: Option<H256>| Ok(vec![100]));
+        let head = nominator.clone();
+        mock_client
+            .expect_list_bags()
+            .returning(move |_storage: &MockDummyStorage, _index: u64| Ok(Some(ListBag { head: Some(head.clone()), tail: None })));
+        mock_client
+            .expect_list_nodes()
+            .returning(|_storage: &MockDummyStorage, account: AccountId| Ok(Some(ListNode { id: account, prev: None, next: None })));
+        mock_client
+            .expect_ledgers_batch()
+            .returning(|_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active: 100, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
+        let nominations = targets.clone();
+        mock_client
+            .expect_get_nominator()
+            .returning(move |_storage: &MockDummyStorage, _nominator: AccountId| Ok(Some(NominationsLight {
+                targets: nominations.clone(),
+                _submitted_in: 10,
+                suppressed: false,
+            })));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let (snapshot, _config, reconstruction) = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Snapshot(10),
+            round: 1,
+            n_pages: 1,
+            desired_targets: 10,
+            _block_number: 100,
+        }, &MockDummyStorage::new()).await.unwrap();
+
+        assert_eq!(snapshot.voters[0][0].2.to_vec(), targets[..max_votes].to_vec());
+        assert_eq!(reconstruction, ReconstructionCounts { truncated_voters: 1, dropped_nominations: 2, skipped_accounts: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_get_snapshot_data_from_multi_block_counts_skipped_accounts() {
+        initialize_runtime_constants();
+        let nominator = AccountId::from([1u8; 32]);
+        let broken = AccountId::from([2u8; 32]);
+        let validator = AccountId::from([3u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client.expect_get_total_issuance().returning(|_storage: &MockDummyStorage| Ok(1_000_000_000));
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let validators = vec![validator.clone()];
+        raw_client.expect_get_validators().returning(move |_at: Option<H256>| Ok(validators.clone()));
+        raw_client.expect_get_all_list_bags().returning(|_at: Option<H256>| Ok(vec![100]));
+        let head = nominator.clone();
+        mock_client
+            .expect_list_bags()
+            .returning(move |_storage: &MockDummyStorage, _index: u64| Ok(Some(ListBag { head: Some(head.clone()), tail: None })));
+        let next = broken.clone();
+        mock_client
+            .expect_list_nodes()
+            .returning(move |_storage: &MockDummyStorage, account: AccountId| {
+                let following = (account != next).then(|| next.clone());
+                Ok(Some(ListNode { id: account, prev: None, next: following }))
+            });
+        // The ledger of `broken` cannot be decoded
+        let broken_stash = broken.clone();
+        mock_client
+            .expect_ledgers_batch()
+            .returning(move |_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                if stash == broken_stash {
+                    return (stash, Err("undecodable ledger".to_string()));
+                }
+                let ledger = StakingLedger { active: 100, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
+        let target = validator.clone();
+        mock_client
+            .expect_get_nominator()
+            .returning(move |_storage: &MockDummyStorage, _nominator: AccountId| Ok(Some(NominationsLight {
+                targets: vec![target.clone()],
+                _submitted_in: 10,
+                suppressed: false,
+            })));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client)).with_skip_errors(true);
+        let (snapshot, _config, reconstruction) = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
+            block_hash: Some(Hash::zero()),
+            phase: Phase::Snapshot(10),
+            round: 1,
+            n_pages: 1,
+            desired_targets: 10,
+            _block_number: 100,
+        }, &MockDummyStorage::new()).await.unwrap();
+
+        assert_eq!(snapshot.voters[0].iter().map(|voter| voter.0.clone()).collect::<Vec<_>>(), vec![nominator]);
+        assert_eq!(reconstruction, ReconstructionCounts { skipped_accounts: 1, ..ReconstructionCounts::default() });
     }
 
     #[tokio::test]
@@ -621,6 +1410,10 @@ mod tests {
             .expect_get_min_validator_bond()
             .returning(|_storage: &MockDummyStorage| Ok(0));
 
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(None));
+
         let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
         raw_client
             .expect_get_validators()
@@ -628,6 +1421,9 @@ mod tests {
                 let v = validator_id.clone();
                 move |_at: Option<H256>| Ok(vec![v.clone()])
             });
+        mock_client
+            .expect_get_total_issuance()
+            .returning(|_storage: &MockDummyStorage| Ok(1_000_000_000));
         raw_client
             .expect_get_all_list_bags()
             .returning(|_at: Option<H256>| Ok(vec![100]));
@@ -646,22 +1442,11 @@ mod tests {
             });
 
         mock_client
-            .expect_get_controller_from_stash()
-            .returning({
-                let v = validator_id.clone();
-                move |_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(v.clone()))
-            });
-        mock_client
-            .expect_ledger()
-            .returning({
-                let v = validator_id.clone();
-                move |_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                    stash: v.clone(),
-                    total: 500,
-                    active: 500,
-                    unlocking: vec![],
-                }))
-            });
+            .expect_ledgers_batch()
+            .returning(|_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active: 500, total: 500, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
         mock_client
             .expect_get_nominator()
             .returning(|_storage: &MockDummyStorage, _nominator: AccountId| Ok(None));
@@ -677,7 +1462,7 @@ mod tests {
         }, &MockDummyStorage::new()).await;
 
         assert!(result.is_ok(), "get_snapshot_data_from_multi_block failed: {:?}", result);
-        let (snapshot, _config) = result.unwrap();
+        let (snapshot, _config, _) = result.unwrap();
 
         let expected_targets = BoundedVec::try_from(vec![validator_id.clone()]).map_err(|_| "Too many targets").unwrap();
         let expected_voter: crate::multi_block_state_client::VoterData<PolkadotMinerConfig> =
@@ -704,11 +1489,18 @@ mod tests {
             .expect_get_min_validator_bond()
             .returning(|_storage: &MockDummyStorage| Ok(0));
 
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(None));
+
         let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
         raw_client
             .expect_get_validators()
             .returning(|_at: Option<H256>| Ok(vec![AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap()]));
 
+        mock_client
+            .expect_get_total_issuance()
+            .returning(|_storage: &MockDummyStorage| Ok(1_000_000_000));
         raw_client
             .expect_get_all_list_bags()
             .returning(|_at: Option<H256>| Ok(vec![100]));
@@ -718,17 +1510,11 @@ mod tests {
             .returning(|_storage: &MockDummyStorage, _index: u64| Ok(None));
 
         mock_client
-            .expect_get_controller_from_stash()
-            .returning(|_storage: &MockDummyStorage, _stash: AccountId| Ok(Some(AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap())));
-
-        mock_client
-            .expect_ledger()
-            .returning(|_storage: &MockDummyStorage, _account: AccountId| Ok(Some(StakingLedger {
-                stash: AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap(),
-                total: 100,
-                active: 100,
-                unlocking: vec![],
-            })));
+            .expect_ledgers_batch()
+            .returning(|_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active: 100, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
 
         let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
         let result = snapshot_service.get_snapshot_data_from_multi_block(&BlockDetails {
@@ -741,7 +1527,7 @@ mod tests {
         }, &MockDummyStorage::new()).await;
 
         assert!(result.is_ok());
-        let (snapshot, config) = result.unwrap();
+        let (snapshot, config, _) = result.unwrap();
         assert!(snapshot.voters.is_empty());
         assert_eq!(snapshot.targets.len(), 1);
         assert_eq!(config.desired_validators, 10);
@@ -774,6 +1560,10 @@ mod tests {
         mock_client
             .expect_get_min_validator_bond()
             .returning(|_storage: &MockDummyStorage| Ok(0));
+
+        mock_client
+            .expect_get_minimum_active_stake()
+            .returning(|_storage: &MockDummyStorage| Ok(None));
         
         let voter_targets = BoundedVec::try_from(vec![AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap()]).map_err(|_| "Too many targets in voter").unwrap();
         let voter = (AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap(),
@@ -791,6 +1581,7 @@ mod tests {
             .expect_fetch_paged_target_snapshot()
             .returning(move |_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(targets.clone()));
 
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
         mock_client
             .expect_get_validator_prefs()
             .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
@@ -798,7 +1589,14 @@ mod tests {
                 blocked: false,
             }));
         
-        let raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client
+            .expect_get_bonded_pool_ids()
+            .with(eq(Some(Hash::zero())))
+            .returning(|_| Ok(vec![1]));
+
+        mock_client.expect_get_nominator_count().returning(|_storage: &MockDummyStorage| Ok(40_000));
+        mock_client.expect_get_max_nominators_count().returning(|_storage: &MockDummyStorage| Ok(Some(50_000)));
 
         let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
         let result = snapshot_service.build(None).await;
@@ -807,19 +1605,477 @@ mod tests {
         assert_eq!(snapshot.validators, vec![SnapshotValidator {
             stash: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(),
             commission: 0.0,
+            commission_parts: 0,
+            commission_bps: None,
             blocked: false,
         }]);
         assert_eq!(snapshot.nominators, vec![SnapshotNominator {
             stash: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string(),
             stake: 100,
-            nominations: vec!["5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string()]
+            nominations: vec!["5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string()],
+            pool_id: None,
         }]);
         
         assert_eq!(snapshot.config, StakingConfig {
             desired_validators: 10,
             max_nominations: 16,
-            min_nominator_bond: 0,
-            min_validator_bond: 0,
+            nominator_slots: Some(NominatorSlots { count: 40_000, cap: Some(50_000), remaining: Some(10_000) }),
+            ..StakingConfig::default()
         });
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_build_validators_without_snapshot() {
+        initialize_runtime_constants();
+        let bonded = AccountId::from_ss58check("5CSbZ7wG456oty4WoiX6a1J88VUbrCXLhrKVJ9q95BsYH4TZ").unwrap();
+        let underbonded = AccountId::from_ss58check("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap();
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+
+        mock_client.expect_get_storage().with(eq(None)).returning(|_| Ok(MockDummyStorage::new()));
+        mock_client
+            .expect_get_block_details()
+            .returning(|_storage: &MockDummyStorage, _block: Option<H256>| Ok(BlockDetails {
+                block_hash: Some(Hash::zero()),
+                phase: Phase::Off,
+                round: 1,
+                n_pages: 1,
+                desired_targets: 10,
+                _block_number: 100,
+            }));
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(50));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client.expect_get_nominator_count().returning(|_storage: &MockDummyStorage| Ok(10));
+        mock_client.expect_get_max_nominators_count().returning(|_storage: &MockDummyStorage| Ok(None));
+        let bonded_stash = bonded.clone();
+        mock_client
+            .expect_ledgers_batch()
+            .returning(move |_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| {
+                let ledger = StakingLedger { active: if stash == bonded_stash { 100 } else { 10 }, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+                (stash, Ok(Some(ledger)))
+            }).collect()));
+        mock_client.expect_get_active_era().returning(|_| Ok(None));
+        mock_client
+            .expect_get_validator_prefs()
+            .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
+                commission: Perbill::from_percent(5),
+                blocked: false,
+            }));
+
+        // No list bags, nominator or voter snapshot reads are expected
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        let validators = vec![bonded.clone(), underbonded];
+        raw_client
+            .expect_get_validators()
+            .with(eq(Some(Hash::zero())))
+            .returning(move |_| Ok(validators.clone()));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let snapshot = snapshot_service.build_validators(None).await.unwrap();
+        assert_eq!(snapshot.validators, vec![SnapshotValidator {
+            stash: bonded.to_ss58check(),
+            commission: 0.05,
+            commission_parts: 50_000_000,
+            commission_bps: None,
+            blocked: false,
+        }]);
+        assert!(snapshot.nominators.is_empty());
+        assert_eq!(snapshot.config.min_validator_bond, 50);
+        assert_eq!(snapshot.config.nominator_slots, Some(NominatorSlots { count: 10, cap: None, remaining: None }));
+    }
+
+    #[tokio::test]
+    async fn test_attribute_controllers() {
+        let stash = AccountId::from([1u8; 32]);
+        let controller = AccountId::from([2u8; 32]);
+        let listed_stash = AccountId::from([3u8; 32]);
+        let listed_controller = AccountId::from([4u8; 32]);
+        let unbonded = AccountId::from([5u8; 32]);
+        let ledger_of = |stash: &AccountId| StakingLedger { active: 100, total: 100, stash: stash.clone(), unlocking: vec![], legacy_claimed_rewards: None };
+
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        let (controller_key, stash_value, listed_controller_key, listed_stash_value) = (controller.clone(), stash.clone(), listed_controller.clone(), listed_stash.clone());
+        mock_client
+            .expect_get_stash_from_controller()
+            .returning(move |_storage: &MockDummyStorage, account: AccountId| Ok(if account == controller_key {
+                Some(stash_value.clone())
+            } else if account == listed_controller_key {
+                Some(listed_stash_value.clone())
+            } else {
+                None
+            }));
+        mock_client
+            .expect_ledgers_batch()
+            .returning(move |_storage: &MockDummyStorage, stashes: Vec<AccountId>| Ok(stashes.into_iter().map(|stash| (stash.clone(), Ok(Some(ledger_of(&stash))))).collect()));
+
+        let accounts = vec![controller.clone(), listed_stash.clone(), listed_controller.clone(), unbonded.clone()];
+        let mut ledgers: LedgerResults = accounts.iter().map(|account| (account.clone(), Ok(None))).collect();
+        ledgers.insert(listed_stash.clone(), Ok(Some(ledger_of(&listed_stash))));
+
+        let storage = MockDummyStorage::new();
+        let resolved = attribute_controllers(&mock_client, &storage, accounts, &mut ledgers).await.unwrap();
+        // The controller of a listed stash is left alone so the stash is not counted twice
+        assert_eq!(resolved, vec![stash.clone(), listed_stash, listed_controller, unbonded]);
+        assert_eq!(ledgers[&stash].clone().unwrap().map(|l| l.active), Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_attribute_controllers_lookup_error() {
+        let failing = AccountId::from([1u8; 32]);
+        let unbonded = AccountId::from([2u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        let failing_key = failing.clone();
+        mock_client
+            .expect_get_stash_from_controller()
+            .returning(move |_storage: &MockDummyStorage, account: AccountId| if account == failing_key {
+                Err("undecodable Staking::Ledger".into())
+            } else {
+                Ok(None)
+            });
+
+        let accounts = vec![failing.clone(), unbonded.clone()];
+        let mut ledgers: LedgerResults = accounts.iter().map(|account| (account.clone(), Ok(None))).collect();
+        let storage = MockDummyStorage::new();
+        // The lookup error stays with its account instead of aborting the whole walk
+        let resolved = attribute_controllers(&mock_client, &storage, accounts.clone(), &mut ledgers).await.unwrap();
+        assert_eq!(resolved, accounts);
+        assert!(ledgers[&failing].clone().unwrap_err().contains("undecodable Staking::Ledger"));
+        assert_eq!(ledgers[&unbonded], Ok(None));
+        let results = accounts.iter().map(|account| ledgers[account].clone());
+        assert_eq!(collect_resolved(accounts.iter().zip(results), "voter", true), Ok((vec![None], 1)));
+    }
+
+    #[test]
+    fn test_collect_resolved() {
+        let accounts = [AccountId::from([1u8; 32]), AccountId::from([2u8; 32]), AccountId::from([3u8; 32])];
+        let results = || vec![Ok(1), Err("undecodable ledger".to_string()), Ok(3)];
+
+        assert_eq!(collect_resolved(accounts.iter().zip(results()), "voter", true), Ok((vec![1, 3], 1)));
+        assert_eq!(collect_resolved(accounts.iter().zip(results()), "voter", false), Err("undecodable ledger".to_string()));
+    }
+
+    #[test]
+    fn test_dedup_voters() {
+        let validator = AccountId::from([1u8; 32]);
+        let nominator = AccountId::from([2u8; 32]);
+        let other = AccountId::from([3u8; 32]);
+        let voter = |account: &AccountId, targets: Vec<AccountId>| -> VoterData<PolkadotMinerConfig> {
+            (account.clone(), 100, BoundedVec::try_from(targets).unwrap())
+        };
+        // The validator is listed once as its own self vote and once more as a nominator
+        let voters = vec![
+            voter(&validator, vec![validator.clone()]),
+            voter(&nominator, vec![validator.clone()]),
+            voter(&validator, vec![other.clone()]),
+        ];
+
+        let deduped = dedup_voters::<PolkadotMinerConfig>(voters.clone());
+        assert_eq!(deduped, voters[..2].to_vec());
+        assert_eq!(dedup_voters::<PolkadotMinerConfig>(deduped.clone()), deduped);
+    }
+
+    #[tokio::test]
+    async fn test_chilled_targets() {
+        let validating = AccountId::from([1u8; 32]);
+        let chilled = AccountId::from([2u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        let validating_key = validating.clone();
+        mock_client
+            .expect_is_validator()
+            .returning(move |_storage: &MockDummyStorage, validator: AccountId| Ok(validator == validating_key));
+
+        let storage = MockDummyStorage::new();
+        let result = chilled_targets(&mock_client, &storage, &[validating, chilled.clone()]).await.unwrap();
+        assert_eq!(result, HashSet::from([chilled]));
+    }
+
+    #[tokio::test]
+    async fn test_validator_prefs_at() {
+        let recorded = AccountId::from([1u8; 32]);
+        let unrecorded = AccountId::from([2u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_active_era().returning(|_| Ok(Some(ActiveEraInfo { index: 7, start: None })));
+        let recorded_key = recorded.clone();
+        mock_client
+            .expect_get_eras_validator_prefs()
+            .with(always(), eq(7), always())
+            .returning(move |_storage: &MockDummyStorage, _era: EraIndex, validator: AccountId| Ok(
+                (validator == recorded_key).then_some(ValidatorPrefs { commission: Perbill::from_percent(3), blocked: false })
+            ));
+        mock_client
+            .expect_get_validator_prefs()
+            .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
+                commission: Perbill::from_percent(10),
+                blocked: false,
+            }));
+
+        let storage = MockDummyStorage::new();
+        let era = prefs_era(&mock_client, &storage).await.unwrap();
+        assert_eq!(era, Some(7));
+
+        let prefs = validator_prefs_at(&mock_client, &storage, era, recorded.clone()).await.unwrap();
+        assert_eq!(prefs.commission, Perbill::from_percent(3));
+        let prefs = validator_prefs_at(&mock_client, &storage, era, unrecorded).await.unwrap();
+        assert_eq!(prefs.commission, Perbill::from_percent(10));
+        let prefs = validator_prefs_at(&mock_client, &storage, None, recorded).await.unwrap();
+        assert_eq!(prefs.commission, Perbill::from_percent(10));
+    }
+
+    #[tokio::test]
+    async fn test_build_latest_and_hash_agree() {
+        initialize_runtime_constants();
+        let validator = AccountId::from([1u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client
+            .expect_get_block_details()
+            .returning(|_storage: &MockDummyStorage, block: Option<H256>| Ok(BlockDetails {
+                block_hash: block,
+                phase: Phase::Signed(10),
+                round: 1,
+                n_pages: 1,
+                desired_targets: 10,
+                _block_number: 100,
+            }));
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+        mock_client.expect_get_nominator_count().returning(|_storage: &MockDummyStorage| Ok(1));
+        mock_client.expect_get_max_nominators_count().returning(|_storage: &MockDummyStorage| Ok(None));
+        let voter_page: VoterSnapshotPage<PolkadotMinerConfig> = BoundedVec::try_from(vec![
+            (validator.clone(), 100, BoundedVec::try_from(vec![validator.clone()]).unwrap()),
+        ]).unwrap();
+        mock_client
+            .expect_fetch_paged_voter_snapshot()
+            .returning(move |_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(voter_page.clone()));
+        let targets = BoundedVec::try_from(vec![validator.clone()]).unwrap();
+        mock_client
+            .expect_fetch_paged_target_snapshot()
+            .returning(move |_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(targets.clone()));
+        // The era entry and the current prefs differ, so both runs must read the same one
+        mock_client.expect_get_active_era().returning(|_| Ok(Some(ActiveEraInfo { index: 7, start: None })));
+        mock_client
+            .expect_get_eras_validator_prefs()
+            .returning(|_storage: &MockDummyStorage, _era: EraIndex, _validator: AccountId| Ok(Some(ValidatorPrefs {
+                commission: Perbill::from_percent(3),
+                blocked: false,
+            })));
+        mock_client
+            .expect_get_validator_prefs()
+            .returning(|_storage: &MockDummyStorage, _validator: AccountId| Ok(ValidatorPrefs {
+                commission: Perbill::from_percent(10),
+                blocked: false,
+            }));
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client.expect_get_bonded_pool_ids().returning(|_| Ok(vec![]));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let latest = snapshot_service.build(None).await.unwrap();
+        let at_hash = snapshot_service.build(Some(H256::repeat_byte(0xab))).await.unwrap();
+        assert_eq!(latest.validators, at_hash.validators);
+        assert_eq!(latest.validators[0].commission, 0.03);
+    }
+
+    #[tokio::test]
+    async fn test_get_backers() {
+        initialize_runtime_constants();
+        let validator = AccountId::from([1u8; 32]);
+        let other = AccountId::from([2u8; 32]);
+        let small = AccountId::from([3u8; 32]);
+        let large = AccountId::from([4u8; 32]);
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().with(eq(None)).returning(|_| Ok(MockDummyStorage::new()));
+        mock_client
+            .expect_get_block_details()
+            .returning(|_storage: &MockDummyStorage, _block: Option<H256>| Ok(BlockDetails {
+                block_hash: Some(Hash::zero()),
+                phase: Phase::Signed(10),
+                round: 1,
+                n_pages: 2,
+                desired_targets: 10,
+                _block_number: 100,
+            }));
+        mock_client.expect_get_min_nominator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_min_validator_bond().returning(|_storage: &MockDummyStorage| Ok(0));
+        mock_client.expect_get_minimum_active_stake().returning(|_storage: &MockDummyStorage| Ok(None));
+
+        let voter = |who: &AccountId, stake: u64, targets: Vec<AccountId>| (who.clone(), stake, BoundedVec::try_from(targets).unwrap());
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![
+            BoundedVec::try_from(vec![
+                voter(&validator, 1000, vec![validator.clone()]),
+                voter(&small, 100, vec![other.clone(), validator.clone()]),
+            ]).unwrap(),
+            BoundedVec::try_from(vec![voter(&large, 300, vec![validator.clone()])]).unwrap(),
+        ];
+        mock_client
+            .expect_fetch_paged_voter_snapshot()
+            .returning(move |_storage: &MockDummyStorage, _round: u32, page: u32| Ok(voter_pages[page as usize].clone()));
+        let targets = BoundedVec::try_from(vec![validator.clone(), other.clone()]).unwrap();
+        mock_client
+            .expect_fetch_paged_target_snapshot()
+            .returning(move |_storage: &MockDummyStorage, _round: u32, _page: u32| Ok(targets.clone()));
+        mock_client.expect_get_validator_prefs().never();
+
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client.expect_get_bonded_pool_ids().returning(|_| Ok(vec![]));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let backers = snapshot_service.get_backers(None, validator.clone()).await.unwrap();
+        assert_eq!(backers, ValidatorBackers {
+            validator: validator.to_ss58check(),
+            backers: vec![
+                ValidatorNomination { nominator: large.to_ss58check(), stake: 300, pool_id: None },
+                ValidatorNomination { nominator: small.to_ss58check(), stake: 100, pool_id: None },
+            ],
+        });
+    }
+
+    #[tokio::test]
+    async fn test_get_era() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client
+            .expect_get_active_era()
+            .returning(|_storage: &MockDummyStorage| Ok(Some(ActiveEraInfo { index: 10, start: Some(1_700_000_000_000) })));
+        mock_client
+            .expect_get_bonded_eras()
+            .times(1)
+            .returning(|_storage: &MockDummyStorage| Ok(vec![(9, 50), (10, 56)]));
+        mock_client
+            .expect_get_bonded_eras()
+            .returning(|_storage: &MockDummyStorage| Ok(vec![]));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(MockRawClientTrait::<MockRpcClient>::new()));
+        let era = snapshot_service.get_era(None).await.unwrap();
+        assert_eq!(era, EraInfo { index: 10, start: Some(1_700_000_000_000), start_session_index: Some(56) });
+        // The session is optional, the era itself is not
+        let era = snapshot_service.get_era(None).await.unwrap();
+        assert_eq!(era.start_session_index, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_era_without_start() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        // Right after an era change the start is only set by the era's first block
+        mock_client
+            .expect_get_active_era()
+            .returning(|_storage: &MockDummyStorage| Ok(Some(ActiveEraInfo { index: 11, start: None })));
+        mock_client
+            .expect_get_bonded_eras()
+            .returning(|_storage: &MockDummyStorage| Ok(vec![(10, 56)]));
+
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(MockRawClientTrait::<MockRpcClient>::new()));
+        let era = snapshot_service.get_era(None).await.unwrap();
+        assert_eq!(era, EraInfo { index: 11, start: None, start_session_index: None });
+        assert_eq!(era.start_description(), "unknown start");
+    }
+
+    #[tokio::test]
+    async fn test_require_snapshot() {
+        for (phase, available) in [(Phase::Signed(10), true), (Phase::Off, false)] {
+            let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+            mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+            mock_client
+                .expect_get_block_details()
+                .returning(move |_storage: &MockDummyStorage, _block: Option<H256>| Ok(BlockDetails {
+                    block_hash: None,
+                    phase,
+                    round: 1,
+                    n_pages: 1,
+                    desired_targets: 10,
+                    _block_number: 100,
+                }));
+            mock_client.expect_fetch_paged_voter_snapshot().never();
+
+            let result = require_snapshot(&mock_client, None).await;
+            assert_eq!(result.is_ok(), available);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scale_snapshot() {
+        initialize_runtime_constants();
+        let voter = AccountId::from([1u8; 32]);
+        let target = AccountId::from([2u8; 32]);
+        let voter_pages: Vec<VoterSnapshotPage<PolkadotMinerConfig>> = vec![
+            BoundedVec::try_from(vec![(voter.clone(), 100, BoundedVec::try_from(vec![target.clone()]).unwrap())]).unwrap(),
+        ];
+        let targets = TargetSnapshotPage::<PolkadotMinerConfig>::try_from(vec![target.clone()]).unwrap();
+
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        mock_client
+            .expect_get_block_details()
+            .returning(|_storage: &MockDummyStorage, _block: Option<H256>| Ok(BlockDetails {
+                block_hash: None,
+                phase: Phase::Signed(10),
+                round: 3,
+                n_pages: 1,
+                desired_targets: 1,
+                _block_number: 100,
+            }));
+        let mut snapshot_service = MockSnapshotService::<PolkadotMinerConfig, MockDummyStorage>::new();
+        let (pages, page_targets) = (voter_pages.clone(), targets.clone());
+        snapshot_service.expect_get_snapshot_data_from_multi_block().returning(move |_block_details: &BlockDetails, _storage: &MockDummyStorage| {
+            Ok((ElectionSnapshotPage::<PolkadotMinerConfig> { voters: pages.clone(), targets: page_targets.clone() }, StakingConfig {
+                desired_validators: 1,
+                max_nominations: 16,
+                ..StakingConfig::default()
+            }, ReconstructionCounts::default()))
+        });
+
+        let encoded = scale_snapshot(&mock_client, &snapshot_service, None, None).await.unwrap();
+        assert_eq!(encoded.round, 3);
+        assert_eq!(encoded.pages, 1);
+        // Decodes back as the pallet's AllVoterPagesOf and TargetPageOf
+        let decoded_pages = <BoundedVec<VoterSnapshotPage<PolkadotMinerConfig>, <PolkadotMinerConfig as MinerConfig>::Pages> as parity_scale_codec::Decode>::decode(&mut &encoded.voter_pages[..]).unwrap();
+        assert_eq!(decoded_pages.into_inner(), voter_pages);
+        let decoded_targets = <TargetSnapshotPage<PolkadotMinerConfig> as parity_scale_codec::Decode>::decode(&mut &encoded.targets[..]).unwrap();
+        assert_eq!(decoded_targets, targets);
+    }
+
+    #[tokio::test]
+    async fn test_find_snapshots() {
+        let mut mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client.expect_get_block_hash().returning(|number| Ok(Some(H256::from_low_u64_be(number as u64))));
+        mock_client.expect_get_storage().returning(|_| Ok(MockDummyStorage::new()));
+        // Blocks 10, 12 and 14 are scanned in order
+        for phase in [Phase::Off, Phase::Signed(5), Phase::Unsigned(3)] {
+            mock_client.expect_get_phase().times(1).returning(move |_storage: &MockDummyStorage| Ok(phase));
+        }
+        mock_client.expect_get_round().returning(|_storage: &MockDummyStorage| Ok(7));
+
+        let blocks = find_snapshots(&mock_client, &raw_client, 10, 15, 2).await.unwrap();
+        assert_eq!(blocks, vec![
+            SnapshotBlock { number: 12, hash: format!("{:?}", H256::from_low_u64_be(12)), phase: "Signed(5)".to_string(), round: 7 },
+            SnapshotBlock { number: 14, hash: format!("{:?}", H256::from_low_u64_be(14)), phase: "Unsigned(3)".to_string(), round: 7 },
+        ]);
+
+        assert!(find_snapshots(&mock_client, &raw_client, 20, 10, 1).await.is_err());
+        assert!(find_snapshots(&mock_client, &raw_client, 0, MAX_SCANNED_BLOCKS, 1).await.is_err());
+    }
+
+    #[test]
+    fn test_pool_bonded_account() {
+        let account: [u8; 32] = pool_bonded_account(7).into();
+        assert_eq!(&account[..12], b"modlpy/nopls");
+        assert_eq!(account[12], 0);
+        assert_eq!(&account[13..17], &7u32.to_le_bytes());
+        assert!(account[17..].iter().all(|b| *b == 0));
+    }
+
+    #[tokio::test]
+    async fn test_get_pool_accounts() {
+        let mock_client = MockMultiBlockClientTrait::<MockChainClientTrait, PolkadotMinerConfig, MockDummyStorage>::new();
+        let mut raw_client = MockRawClientTrait::<MockRpcClient>::new();
+        raw_client
+            .expect_get_bonded_pool_ids()
+            .returning(|_| Ok(vec![1, 2]));
+        let snapshot_service = SnapshotServiceImpl::new(Arc::new(mock_client), Arc::new(raw_client));
+        let pool_accounts = snapshot_service.get_pool_accounts(None).await.unwrap();
+        assert_eq!(pool_accounts.len(), 2);
+        assert_eq!(pool_accounts.get(&pool_bonded_account(2)), Some(&2));
+    }
+}
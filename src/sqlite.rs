@@ -0,0 +1,181 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use sp_core::H256;
+
+use crate::models::{Chain, SimulationResult};
+
+// Stakes are stored as planck strings since they do not fit in an SQLite integer
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at INTEGER NOT NULL,
+    chain TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    algorithm TEXT NOT NULL,
+    iterations INTEGER NOT NULL,
+    reduce INTEGER NOT NULL,
+    max_nominations INTEGER NOT NULL,
+    min_nominator_bond TEXT NOT NULL,
+    min_validator_bond TEXT NOT NULL,
+    desired_validators INTEGER NOT NULL,
+    sample_size INTEGER,
+    sample_seed TEXT
+);
+CREATE TABLE IF NOT EXISTS validators (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    stash TEXT NOT NULL,
+    self_stake TEXT NOT NULL,
+    total_stake TEXT NOT NULL,
+    commission REAL NOT NULL,
+    blocked INTEGER NOT NULL,
+    nominations_count INTEGER NOT NULL,
+    PRIMARY KEY (run_id, stash)
+);
+CREATE TABLE IF NOT EXISTS nominations (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    validator TEXT NOT NULL,
+    nominator TEXT NOT NULL,
+    stake TEXT NOT NULL,
+    pool_id INTEGER
+);
+";
+
+// Append a simulation run mined at the block `block_hash` (number `block_number`) to the
+// database at `path`, creating the tables if needed
+pub fn write_simulation(path: &str, chain: Chain, block_hash: H256, block_number: u32, result: &SimulationResult) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+    let mut conn = Connection::open(path)
+        .map_err(|e| format!("Error opening SQLite database {} -> {}", path, e))?;
+    insert_simulation(&mut conn, chain, block_hash, block_number, result)
+}
+
+fn insert_simulation(conn: &mut Connection, chain: Chain, block_hash: H256, block_number: u32, result: &SimulationResult) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+    conn.execute_batch(SCHEMA)?;
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let parameters = &result.run_parameters;
+
+    // One transaction per run so a failed write never leaves a partial run behind
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO runs (created_at, chain, block_hash, block_number, algorithm, iterations, reduce, max_nominations, min_nominator_bond, min_validator_bond, desired_validators, sample_size, sample_seed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            created_at,
+            format!("{:?}", chain),
+            format!("{:?}", block_hash),
+            block_number,
+            format!("{:?}", parameters.algorithm),
+            parameters.iterations as i64,
+            parameters.reduce,
+            parameters.max_nominations,
+            parameters.min_nominator_bond.to_string(),
+            parameters.min_validator_bond.to_string(),
+            parameters.desired_validators,
+            parameters.sample.map(|sample| sample.size as i64),
+            parameters.sample.map(|sample| sample.seed.to_string()),
+        ],
+    )?;
+    let run_id = tx.last_insert_rowid();
+    {
+        let mut insert_validator = tx.prepare(
+            "INSERT INTO validators (run_id, stash, self_stake, total_stake, commission, blocked, nominations_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        let mut insert_nomination = tx.prepare(
+            "INSERT INTO nominations (run_id, validator, nominator, stake, pool_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for validator in &result.active_validators {
+            insert_validator.execute(params![
+                run_id,
+                validator.stash,
+                validator.self_stake.to_string(),
+                validator.total_stake.to_string(),
+                validator.commission,
+                validator.blocked,
+                validator.nominations_count as i64,
+            ])?;
+            for nomination in &validator.nominations {
+                insert_nomination.execute(params![
+                    run_id,
+                    validator.stash,
+                    nomination.nominator,
+                    nomination.stake.to_string(),
+                    nomination.pool_id,
+                ])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RunParameters, StakingStats, Validator, ValidatorNomination};
+
+    fn simulation() -> SimulationResult {
+        SimulationResult {
+            run_parameters: RunParameters {
+                iterations: 10,
+                max_nominations: 16,
+                min_nominator_bond: 1,
+                min_validator_bond: 2,
+                desired_validators: 1,
+                ..RunParameters::default()
+            },
+            staking_stats: StakingStats { total_staked: 300, lowest_staked: 300, avg_staked: 300, total_self_stake: 100, total_nominated_stake: 200, min_active_nominator_stake: 50, minimum_active_stake: 50, ..StakingStats::default() },
+            active_validators: vec![Validator {
+                stash: "validator".to_string(),
+                self_stake: 100,
+                total_stake: 300,
+                commission: 0.05,
+                commission_parts: 50_000_000,
+                blocked: false,
+                nominations_count: 2,
+                nominations: vec![
+                    ValidatorNomination { nominator: "alice".to_string(), stake: 150, pool_id: None },
+                    ValidatorNomination { nominator: "pool".to_string(), stake: 50, pool_id: Some(7) },
+                ],
+            }],
+            ..SimulationResult::default()
+        }
+    }
+
+    #[test]
+    fn test_insert_simulation() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let result = simulation();
+
+        let block_hash = H256::repeat_byte(0xab);
+        let first = insert_simulation(&mut conn, Chain::Polkadot, block_hash, 100, &result).unwrap();
+        let second = insert_simulation(&mut conn, Chain::Polkadot, block_hash, 100, &result).unwrap();
+        assert_ne!(first, second);
+
+        let (chain, algorithm, iterations): (String, String, i64) = conn.query_row(
+            "SELECT chain, algorithm, iterations FROM runs WHERE id = ?1", [first], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).unwrap();
+        assert_eq!(chain, "Polkadot");
+        // The resolved block is stored, never the "latest" given on the command line
+        let (stored_hash, stored_number): (String, u32) = conn.query_row(
+            "SELECT block_hash, block_number FROM runs WHERE id = ?1", [first], |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(stored_hash, format!("0x{}", "ab".repeat(32)));
+        assert_eq!(stored_number, 100);
+        assert_eq!(algorithm, "SeqPhragmen");
+        assert_eq!(iterations, 10);
+
+        let total_stake: String = conn.query_row(
+            "SELECT total_stake FROM validators WHERE run_id = ?1 AND stash = 'validator'", [first], |row| row.get(0),
+        ).unwrap();
+        assert_eq!(total_stake, "300");
+
+        let nominations: i64 = conn.query_row("SELECT COUNT(*) FROM nominations", [], |row| row.get(0)).unwrap();
+        assert_eq!(nominations, 4);
+        let pool_id: Option<u32> = conn.query_row(
+            "SELECT pool_id FROM nominations WHERE run_id = ?1 AND nominator = 'pool'", [first], |row| row.get(0),
+        ).unwrap();
+        assert_eq!(pool_id, Some(7));
+    }
+}
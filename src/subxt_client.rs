@@ -1,8 +1,16 @@
 use crate::primitives::{ChainClient};
+use crate::raw_state_client::user_agent_headers;
 use std::{time::Duration};
 use subxt::{backend::rpc::reconnecting_rpc_client::{ExponentialBackoff, RpcClient as ReconnectingRpcClient}};
 use subxt::ext::scale_value;
 
+/// Reconnection attempts before a connection is given up
+pub const DEFAULT_RETRY_ATTEMPTS: usize = 10;
+/// Upper bound of the exponential backoff between reconnection attempts, in seconds
+pub const DEFAULT_MAX_RETRY_DELAY_SECS: u64 = 30;
+/// Largest RPC response accepted, in megabytes
+pub const DEFAULT_MAX_RESPONSE_SIZE_MB: u32 = 20;
+
 #[derive(Clone, Debug)]
 pub struct Client {
 	/// Access to chain APIs such as storage, events etc.
@@ -10,14 +18,19 @@ pub struct Client {
 }
 
 impl Client {
-	pub async fn new(uri: &str, retry_attempts: Option<usize>) -> Result<Self, subxt::Error> {
-		// Create a reconnecting RPC client with exponential backoff
+	pub async fn new(uri: &str, retry_attempts: usize, max_retry_delay: Duration, max_response_size_mb: u32, user_agent: &str) -> Result<Self, subxt::Error> {
+		let headers = user_agent_headers(user_agent).map_err(|e| subxt::Error::Other(e.to_string()))?;
+		// Create a reconnecting RPC client with exponential backoff. The base is also the growth
+		// rate, so 2ms scaled by 250 gives delays of 500ms, 1s, 2s, ...
 		let reconnecting_rpc =
 			ReconnectingRpcClient::builder()
+				.set_headers(headers)
+				.max_response_size(max_response_size_mb.saturating_mul(1024 * 1024))
 				.retry_policy(
-					ExponentialBackoff::from_millis(500)
-						.max_delay(Duration::from_secs(30))
-						.take(retry_attempts.unwrap_or(10)), // Allow up to 10 retry attempts before giving up
+					ExponentialBackoff::from_millis(2)
+						.factor(250)
+						.max_delay(max_retry_delay)
+						.take(retry_attempts),
 				)
 				.build(uri.to_string())
 				.await
@@ -59,12 +72,13 @@ impl Client {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::raw_state_client::DEFAULT_USER_AGENT;
 
 	const URI: &str = "wss://sys.ibp.network/asset-hub-polkadot";
 
 	#[tokio::test]
 	async fn test_client_new_invalid_uri_fails() {
-		let result = Client::new("ws://127.0.0.1:1", Some(1)).await;
+		let result = Client::new("ws://127.0.0.1:1", 1, Duration::from_secs(1), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await;
 		assert!(result.is_err());
 		let err = result.unwrap_err();
 		let msg = err.to_string();
@@ -73,13 +87,13 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_client_new_valid_uri() {
-		let result = Client::new(URI, None).await;
+		let result = Client::new(URI, DEFAULT_RETRY_ATTEMPTS, Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await;
 		assert!(result.is_ok());
 	}
 
 	#[tokio::test]
 	async fn test_get_constants() {
-		let client = Client::new(URI, None).await.unwrap();
+		let client = Client::new(URI, DEFAULT_RETRY_ATTEMPTS, Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await.unwrap();
 		let constants = client.fetch_constant::<u32>("MultiBlockElection", "Pages").await;
 		assert!(constants.is_ok());
 		let constants = constants.unwrap();
@@ -88,7 +102,7 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_constants_invalid_pallet() {
-		let client = Client::new(URI, None).await.unwrap();
+		let client = Client::new(URI, DEFAULT_RETRY_ATTEMPTS, Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await.unwrap();
 		let constants = client.fetch_constant::<u32>("InvalidPallet", "MinNominatorBond").await;
 		assert!(constants.is_err());
 		let err = constants.unwrap_err();
@@ -98,7 +112,7 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_constants_invalid_constant() {
-		let client = Client::new(URI, None).await.unwrap();
+		let client = Client::new(URI, DEFAULT_RETRY_ATTEMPTS, Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await.unwrap();
 		let constants = client.fetch_constant::<u32>("Staking", "InvalidConstant").await;
 		assert!(constants.is_err());
 		let err = constants.unwrap_err();
@@ -108,7 +122,7 @@ mod tests {
 
 	#[tokio::test]
 	async fn test_get_constants_invalid_constant_type() {
-		let client = Client::new(URI, None).await.unwrap();
+		let client = Client::new(URI, DEFAULT_RETRY_ATTEMPTS, Duration::from_secs(DEFAULT_MAX_RETRY_DELAY_SECS), DEFAULT_MAX_RESPONSE_SIZE_MB, DEFAULT_USER_AGENT).await.unwrap();
 		let constants = client.fetch_constant::<String>("MultiBlockElection", "Pages").await;
 		assert!(constants.is_err());
 		let err = constants.unwrap_err();